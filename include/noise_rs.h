@@ -0,0 +1,118 @@
+/* Exported by the noise_rs cdylib and staticlib when built with the `capi` feature, on by default. */
+
+#ifndef NOISE_RS_H
+#define NOISE_RS_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct DoublePerlinNoise DoublePerlinNoise;
+
+typedef struct PerlinNoise PerlinNoise;
+
+typedef struct SimplexNoise SimplexNoise;
+
+typedef struct Voronoi Voronoi;
+
+// A 1.16.1 `PerlinNoise` from a java random seed and the octave list, for example -7 to 0.
+//
+// # Safety
+// `octaves` must point to `octave_count` integers. Returns null if the list is empty, not increasing
+// or has a duplicate.
+PerlinNoise *noise_rs_perlin_noise_new(int64_t seed, const int32_t *octaves, size_t octave_count);
+
+// A 1.16.2+ `PerlinNoise` from a java random seed, the first octave and the octave amplitudes.
+//
+// # Safety
+// `amplitudes` must point to `amplitude_count` doubles.
+PerlinNoise *noise_rs_perlin_noise_create_legacy(int64_t seed, int32_t first_octave, const double *amplitudes, size_t amplitude_count);
+
+// # Safety
+// `noise` must be null or a handle of `noise_rs_perlin_noise_*`. Returns NaN for a null handle.
+double noise_rs_perlin_noise_sample(const PerlinNoise *noise, double x, double y, double z);
+
+// # Safety
+// `noise` must be a perlin noise handle, `xs`, `ys`, `zs` and `out` must point to `count` doubles.
+void noise_rs_perlin_noise_sample_batch(const PerlinNoise *noise, const double *xs, const double *ys, const double *zs, size_t count, double *out);
+
+// # Safety
+// `noise` must be null or a perlin noise handle not freed yet.
+void noise_rs_perlin_noise_free(PerlinNoise *noise);
+
+// A 1.16.1 `DoublePerlinNoise` from a java random seed and the octave list.
+//
+// # Safety
+// `octaves` must point to `octave_count` integers. Returns null if the list is empty, not increasing
+// or has a duplicate.
+DoublePerlinNoise *noise_rs_double_perlin_noise_new(int64_t seed, const int32_t *octaves, size_t octave_count);
+
+// A 1.16.2+ `DoublePerlinNoise` from a java random seed, the first octave and the octave amplitudes.
+//
+// # Safety
+// `amplitudes` must point to `amplitude_count` doubles.
+DoublePerlinNoise *noise_rs_double_perlin_noise_create_legacy(int64_t seed, int32_t first_octave, const double *amplitudes, size_t amplitude_count);
+
+// The 1.18+ noise registered as `name` in a world of `world_seed`, `legacy_random_source` being
+// the flag of the noise settings.
+//
+// # Safety
+// `name` must be a nul terminated UTF-8 string and `amplitudes` must point to `amplitude_count` doubles.
+DoublePerlinNoise *noise_rs_double_perlin_noise_instantiate(int64_t world_seed, bool legacy_random_source, const char *name, int32_t first_octave, const double *amplitudes, size_t amplitude_count);
+
+// # Safety
+// `noise` must be null or a handle of `noise_rs_double_perlin_noise_*`. Returns NaN for a null handle.
+double noise_rs_double_perlin_noise_sample(const DoublePerlinNoise *noise, double x, double y, double z);
+
+// # Safety
+// `noise` must be a double perlin noise handle, `xs`, `ys`, `zs` and `out` must point to `count` doubles.
+void noise_rs_double_perlin_noise_sample_batch(const DoublePerlinNoise *noise, const double *xs, const double *ys, const double *zs, size_t count, double *out);
+
+// # Safety
+// `noise` must be null or a double perlin noise handle not freed yet.
+void noise_rs_double_perlin_noise_free(DoublePerlinNoise *noise);
+
+// A `SimplexNoise` seeded by the java random of `seed`.
+SimplexNoise *noise_rs_simplex_noise_new(int64_t seed);
+
+// # Safety
+// `noise` must be null or a simplex noise handle, not used from another thread at the same time
+// since sampling fills its cache. Returns NaN for a null handle.
+double noise_rs_simplex_noise_sample_2d(SimplexNoise *noise, double x, double z);
+
+// # Safety
+// Same as `noise_rs_simplex_noise_sample_2d`.
+double noise_rs_simplex_noise_sample_3d(SimplexNoise *noise, double x, double y, double z);
+
+// # Safety
+// `noise` must be a simplex noise handle, `xs`, `zs` and `out` must point to `count` doubles.
+void noise_rs_simplex_noise_sample_2d_batch(SimplexNoise *noise, const double *xs, const double *zs, size_t count, double *out);
+
+// # Safety
+// `noise` must be a simplex noise handle, `xs`, `ys`, `zs` and `out` must point to `count` doubles.
+void noise_rs_simplex_noise_sample_3d_batch(SimplexNoise *noise, const double *xs, const double *ys, const double *zs, size_t count, double *out);
+
+// # Safety
+// `noise` must be null or a simplex noise handle not freed yet.
+void noise_rs_simplex_noise_free(SimplexNoise *noise);
+
+// The biome zoom of a world, `column` selecting the y independent 1.15-1.17 overworld zoom.
+Voronoi *noise_rs_voronoi_new(int64_t world_seed, bool column);
+
+// Writes the biome (quart) position the block at `x`, `y`, `z` takes its biome from.
+//
+// # Safety
+// `voronoi` must be null or a voronoi handle, the outputs must be valid or null.
+void noise_rs_voronoi_get_fuzzy_positions(Voronoi *voronoi, int32_t x, int32_t y, int32_t z, int32_t *out_x, int32_t *out_y, int32_t *out_z);
+
+// # Safety
+// `voronoi` must be a voronoi handle, the six arrays must hold `count` integers.
+void noise_rs_voronoi_get_fuzzy_positions_batch(Voronoi *voronoi, const int32_t *xs, const int32_t *ys, const int32_t *zs, size_t count, int32_t *out_xs, int32_t *out_ys, int32_t *out_zs);
+
+// # Safety
+// `voronoi` must be null or a voronoi handle not freed yet.
+void noise_rs_voronoi_free(Voronoi *voronoi);
+
+#endif  /* NOISE_RS_H */
@@ -0,0 +1,439 @@
+//! The 1.18+ aquifers, choosing the fluid or the air filling the open blocks of the noise caves.
+//!
+//! The world is split in cells of 16 by 12 by 16 blocks, each holding an aquifer centred at a
+//! random position of the cell. An open block takes the fluid of its nearest aquifer, unless a
+//! barrier between aquifers of different levels closes it. This follows vanilla 1.18.2
+//! `Aquifer.NoiseBasedAquifer`.
+
+use std::sync::Arc;
+
+use crate::block::Block;
+use crate::density_function::DensityFunction;
+use crate::double_perlin_noise::{DoublePerlinNoise, NoiseParameters};
+use crate::math::{clamped_lerp, lerp1};
+use crate::random_source::{PositionalRandomFactory, RandomSource};
+
+/// Vanilla `DimensionType.WAY_BELOW_MIN_Y`, the fluid level of the dry aquifers.
+pub const WAY_BELOW_MIN_Y: i32 = -32512;
+
+const X_RANGE: i32 = 10;
+const Y_RANGE: i32 = 9;
+const Z_RANGE: i32 = 10;
+const X_SPACING: i32 = 16;
+const Y_SPACING: i32 = 12;
+const Z_SPACING: i32 = 16;
+
+/// Chunk offsets of the preliminary surface samples around an aquifer.
+const SURFACE_SAMPLING_OFFSETS_IN_CHUNKS: [(i32, i32); 13] = [
+    (-2, -1), (-1, -1), (0, -1), (1, -1),
+    (-3, 0), (-2, 0), (-1, 0), (0, 0), (1, 0),
+    (-2, 1), (-1, 1), (0, 1), (1, 1),
+];
+
+/// A fluid filling the open blocks below its level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FluidStatus {
+    pub fluid_level: i32,
+    pub fluid_type: Block,
+}
+
+/// The fluids of a dimension without aquifers, lava below y -54 and the sea elsewhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlobalFluidPicker {
+    lava: FluidStatus,
+    sea: FluidStatus,
+}
+
+/// Provides the preliminary surface level of the noise chunk at block coordinates.
+pub trait PreliminarySurface {
+    fn get_preliminary_surface_level(&mut self, x: i32, z: i32) -> i32;
+}
+
+impl<F: FnMut(i32, i32) -> i32> PreliminarySurface for F {
+    fn get_preliminary_surface_level(&mut self, x: i32, z: i32) -> i32 {
+        self(x, z)
+    }
+}
+
+/// The noises and the random of the aquifers of a world, shared by the chunks.
+#[derive(Clone, Debug)]
+pub struct AquiferNoises {
+    barrier: DensityFunction,
+    fluid_level_floodedness: DensityFunction,
+    fluid_level_spread: DensityFunction,
+    lava: DensityFunction,
+    random_factory: PositionalRandomFactory,
+}
+
+/// The aquifers around a chunk, caching their centers and fluids.
+#[derive(Clone, Debug)]
+pub struct Aquifer<'a> {
+    noises: &'a AquiferNoises,
+    global_fluid_picker: GlobalFluidPicker,
+    location_cache: Vec<Option<(i32, i32, i32)>>,
+    status_cache: Vec<Option<FluidStatus>>,
+    min_grid_x: i32,
+    min_grid_y: i32,
+    min_grid_z: i32,
+    grid_size_x: i32,
+    grid_size_y: i32,
+    grid_size_z: i32,
+    should_schedule_fluid_update: bool,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod aquifer_test {
+    use super::*;
+    use crate::random_source::WorldgenRandom;
+
+    fn noises() -> AquiferNoises {
+        AquiferNoises::overworld(&WorldgenRandom::new(1, false).fork_positional())
+    }
+
+    /// The blocks of a column from y -54, `#` where a barrier or the density closes the block.
+    fn column(noises: &AquiferNoises, x: i32, z: i32, density: f64) -> String {
+        let mut aquifer = Aquifer::new(noises, x >> 4, z >> 4, -64, 384, GlobalFluidPicker::new(63, Block::Water));
+        let mut surface = |_: i32, _: i32| 80;
+        (-54..40).map(|y| match aquifer.compute_substance(x, y, z, density, &mut surface) {
+            None => '#',
+            Some(Block::Air) => '.',
+            Some(Block::Water) => 'W',
+            Some(Block::Lava) => 'L',
+            Some(block) => panic!("{:?}", block),
+        }).collect()
+    }
+
+    #[test]
+    fn test_global_fluids() {
+        let picker = GlobalFluidPicker::new(63, Block::Water);
+        assert_eq!(picker.compute_fluid(0, -55, 0).at(-55), Block::Lava);
+        assert_eq!(picker.compute_fluid(0, -54, 0).at(-54), Block::Water);
+        assert_eq!(picker.compute_fluid(0, 62, 0).at(62), Block::Water);
+        assert_eq!(picker.compute_fluid(0, 63, 0).at(63), Block::Air);
+        assert_eq!(GlobalFluidPicker::new(32, Block::Lava).compute_fluid(0, 31, 0).at(31), Block::Lava);
+    }
+
+    #[test]
+    fn test_locations() {
+        let noises = noises();
+        let mut aquifer = Aquifer::new(&noises, 2, -3, -64, 384, GlobalFluidPicker::new(63, Block::Water));
+        for grid_y in -6..6 {
+            let (x, y, z) = aquifer.get_location(2, grid_y, -4);
+            assert!((32..42).contains(&x) && (grid_y * 12..grid_y * 12 + 9).contains(&y) && (-64..-54).contains(&z));
+        }
+        // vanilla 1.18.2 aquifer centers and fluids of seed 1 under a surface at y 80
+        let mut aquifer = Aquifer::new(&noises, 2, 24, -64, 384, GlobalFluidPicker::new(63, Block::Water));
+        let mut surface = |_: i32, _: i32| 80;
+        let expected = [
+            ((0, -4, 8), (4, -44, 130), FluidStatus::new(63, Block::Water)),
+            ((0, 2, 8), (5, 24, 132), FluidStatus::new(17, Block::Water)),
+            ((2, 0, 8), (41, 6, 128), FluidStatus::new(14, Block::Water)),
+            ((3, -2, 8), (52, -24, 136), FluidStatus::new(63, Block::Water)),
+            ((1, -3, 24), (16, -30, 384), FluidStatus::new(WAY_BELOW_MIN_Y, Block::Water)),
+            ((2, -5, 24), (32, -60, 388), FluidStatus::new(WAY_BELOW_MIN_Y, Block::Lava)),
+            ((2, -4, 24), (40, -45, 393), FluidStatus::new(-60, Block::Water)),
+            ((2, -3, 24), (38, -28, 384), FluidStatus::new(-20, Block::Lava)),
+            ((3, -5, 24), (57, -56, 391), FluidStatus::new(-60, Block::Lava)),
+            ((2, 0, -4), (37, 3, -58), FluidStatus::new(WAY_BELOW_MIN_Y, Block::Water)),
+        ];
+        for &((grid_x, grid_y, grid_z), location, status) in expected.iter() {
+            assert_eq!(aquifer.get_location(grid_x, grid_y, grid_z), location);
+            assert_eq!(aquifer.get_status(location, &mut surface), status);
+        }
+    }
+
+    #[test]
+    fn test_substance() {
+        let noises = noises();
+        let mut aquifer = Aquifer::new(&noises, 0, 0, -64, 384, GlobalFluidPicker::new(63, Block::Water));
+        let mut surface = |_: i32, _: i32| 80;
+        assert_eq!(aquifer.compute_substance(5, 10, 5, 0.5f64, &mut surface), None);
+        assert!(!aquifer.should_schedule_fluid_update());
+        assert_eq!(aquifer.compute_substance(5, -60, 5, -0.5f64, &mut surface), Some(Block::Lava));
+        assert!((-40..40).all(|y| aquifer.compute_substance(5, y, 5, -0.5f64, &mut surface) == Some(Block::Air)));
+        let mut ocean = |_: i32, _: i32| 30;
+        let mut aquifer = Aquifer::new(&noises, 0, 0, -64, 384, GlobalFluidPicker::new(63, Block::Water));
+        assert_eq!(aquifer.compute_substance(5, 20, 5, -0.5f64, &mut ocean), Some(Block::Air));
+        assert_eq!(aquifer.compute_substance(5, 21, 5, -0.5f64, &mut ocean), None);
+        assert!((23..63).all(|y| aquifer.compute_substance(5, y, 5, -0.5f64, &mut ocean) == Some(Block::Water)));
+        let location = aquifer.get_location(0, 2, 0);
+        assert_eq!(aquifer.get_status(location, &mut ocean), FluidStatus::new(63, Block::Water));
+    }
+
+    #[test]
+    fn test_vanilla_columns() {
+        // vanilla 1.18.2 columns of seed 1 under a surface at y 80, barriers closing the blocks between aquifers
+        let noises = noises();
+        assert_eq!(column(&noises, 0, 120, -0.05f64), "WWWWWWW#####....##LLLLLLLLLLLLL.#.######............##WWWWWWWWWWWWWWWWW.......................");
+        assert_eq!(column(&noises, 48, 120, -0.05f64), ".....##WWWWWWWWWWWWWWWWWWW#L.............................###WWWWWWWW..........................");
+        assert_eq!(column(&noises, 48, 384, -0.05f64), "....##LLLLLLLLLLLLLLLLLLLLLLLLLLLL................#####WWWWWWWWWWWWWWWWWWW....................");
+        assert_eq!(column(&noises, 24, 480, -0.05f64), "......#.######L###............................................................................");
+    }
+}
+
+impl FluidStatus {
+    pub fn new(fluid_level: i32, fluid_type: Block) -> Self {
+        FluidStatus { fluid_level, fluid_type }
+    }
+
+    pub fn at(&self, y: i32) -> Block {
+        if y < self.fluid_level { self.fluid_type } else { Block::Air }
+    }
+}
+
+impl GlobalFluidPicker {
+    pub fn new(sea_level: i32, default_fluid: Block) -> Self {
+        GlobalFluidPicker {
+            lava: FluidStatus::new(-54, Block::Lava),
+            sea: FluidStatus::new(sea_level, default_fluid),
+        }
+    }
+
+    pub fn compute_fluid(&self, _x: i32, y: i32, _z: i32) -> FluidStatus {
+        if y < self.lava.fluid_level.min(self.sea.fluid_level) { self.lava } else { self.sea }
+    }
+}
+
+impl AquiferNoises {
+    pub fn new(barrier: DensityFunction, fluid_level_floodedness: DensityFunction, fluid_level_spread: DensityFunction, lava: DensityFunction, random_factory: PositionalRandomFactory) -> Self {
+        AquiferNoises { barrier, fluid_level_floodedness, fluid_level_spread, lava, random_factory }
+    }
+
+    /// The vanilla 1.18 aquifer noises of a world.
+    pub fn overworld(factory: &PositionalRandomFactory) -> Self {
+        let noise = |name: &str, first_octave: i32, y_scale: f64| {
+            let noise = Arc::new(DoublePerlinNoise::instantiate(factory, name, &NoiseParameters::new(first_octave, vec![1.0f64])));
+            DensityFunction::noise(noise, 1.0f64, y_scale)
+        };
+        Self::new(
+            noise("minecraft:aquifer_barrier", -3, 0.5f64),
+            noise("minecraft:aquifer_fluid_level_floodedness", -7, 0.67f64),
+            noise("minecraft:aquifer_fluid_level_spread", -5, 0.7142857142857143f64),
+            noise("minecraft:aquifer_lava", -1, 1.0f64),
+            factory.from_hash_of("minecraft:aquifer").fork_positional(),
+        )
+    }
+}
+
+/// How close a block is to being equally far from two aquifers, from squared distances.
+fn similarity(first_distance: i32, second_distance: i32) -> f64 {
+    1.0f64 - (second_distance - first_distance).abs() as f64 / 25.0f64
+}
+
+impl<'a> Aquifer<'a> {
+    /// The aquifers of the chunk at `chunk_x`, `chunk_z` in a dimension from `min_y` spanning
+    /// `height` blocks.
+    pub fn new(noises: &'a AquiferNoises, chunk_x: i32, chunk_z: i32, min_y: i32, height: i32, global_fluid_picker: GlobalFluidPicker) -> Self {
+        let min_grid_x = (chunk_x * 16).div_euclid(X_SPACING) - 1;
+        let max_grid_x = (chunk_x * 16 + 15).div_euclid(X_SPACING) + 1;
+        let min_grid_y = min_y.div_euclid(Y_SPACING) - 1;
+        let max_grid_y = (min_y + height).div_euclid(Y_SPACING) + 1;
+        let min_grid_z = (chunk_z * 16).div_euclid(Z_SPACING) - 1;
+        let max_grid_z = (chunk_z * 16 + 15).div_euclid(Z_SPACING) + 1;
+        let grid_size_x = max_grid_x - min_grid_x + 1;
+        let grid_size_y = max_grid_y - min_grid_y + 1;
+        let grid_size_z = max_grid_z - min_grid_z + 1;
+        let size = (grid_size_x * grid_size_y * grid_size_z) as usize;
+        Aquifer {
+            noises,
+            global_fluid_picker,
+            location_cache: vec![None; size],
+            status_cache: vec![None; size],
+            min_grid_x,
+            min_grid_y,
+            min_grid_z,
+            grid_size_x,
+            grid_size_y,
+            grid_size_z,
+            should_schedule_fluid_update: false,
+        }
+    }
+
+    fn get_index(&self, grid_x: i32, grid_y: i32, grid_z: i32) -> Option<usize> {
+        let x = grid_x - self.min_grid_x;
+        let y = grid_y - self.min_grid_y;
+        let z = grid_z - self.min_grid_z;
+        if x < 0 || x >= self.grid_size_x || y < 0 || y >= self.grid_size_y || z < 0 || z >= self.grid_size_z {
+            return None;
+        }
+        Some(((y * self.grid_size_z + z) * self.grid_size_x + x) as usize)
+    }
+
+    /// Whether the last open block computed borders another aquifer, vanilla then ticks its fluid.
+    pub fn should_schedule_fluid_update(&self) -> bool {
+        self.should_schedule_fluid_update
+    }
+
+    /// The center of the aquifer of a cell.
+    pub fn get_location(&mut self, grid_x: i32, grid_y: i32, grid_z: i32) -> (i32, i32, i32) {
+        let index = self.get_index(grid_x, grid_y, grid_z);
+        if let Some(location) = index.and_then(|index| self.location_cache[index]) {
+            return location;
+        }
+        let mut random = self.noises.random_factory.at(grid_x, grid_y, grid_z);
+        let x = grid_x * X_SPACING + random.next_int_n(X_RANGE);
+        let y = grid_y * Y_SPACING + random.next_int_n(Y_RANGE);
+        let z = grid_z * Z_SPACING + random.next_int_n(Z_RANGE);
+        if let Some(index) = index {
+            self.location_cache[index] = Some((x, y, z));
+        }
+        (x, y, z)
+    }
+
+    /// The fluid of the aquifer centred at `location`.
+    pub fn get_status<P: PreliminarySurface>(&mut self, location: (i32, i32, i32), surface: &mut P) -> FluidStatus {
+        let (x, y, z) = location;
+        let index = self.get_index(x.div_euclid(X_SPACING), y.div_euclid(Y_SPACING), z.div_euclid(Z_SPACING));
+        if let Some(status) = index.and_then(|index| self.status_cache[index]) {
+            return status;
+        }
+        let status = self.compute_fluid(x, y, z, surface);
+        if let Some(index) = index {
+            self.status_cache[index] = Some(status);
+        }
+        status
+    }
+
+    fn compute_fluid<P: PreliminarySurface>(&self, x: i32, y: i32, z: i32, surface: &mut P) -> FluidStatus {
+        let global = self.global_fluid_picker.compute_fluid(x, y, z);
+        let mut min_surface_level = i32::MAX;
+        let top = y + 12;
+        let bottom = y - 12;
+        let mut fluid_at_surface = false;
+        for &(offset_x, offset_z) in SURFACE_SAMPLING_OFFSETS_IN_CHUNKS.iter() {
+            let sample_x = x + offset_x * 16;
+            let sample_z = z + offset_z * 16;
+            let surface_level = surface.get_preliminary_surface_level(sample_x, sample_z);
+            let surface_y = surface_level + 8;
+            let at_center = offset_x == 0 && offset_z == 0;
+            if at_center && bottom > surface_y {
+                return global;
+            }
+            let above_surface = top > surface_y;
+            if above_surface || at_center {
+                let surface_fluid = self.global_fluid_picker.compute_fluid(sample_x, surface_y, sample_z);
+                if !surface_fluid.at(surface_y).is_air() {
+                    if at_center {
+                        fluid_at_surface = true;
+                    }
+                    if above_surface {
+                        return surface_fluid;
+                    }
+                }
+            }
+            min_surface_level = min_surface_level.min(surface_level);
+        }
+        let depth = min_surface_level + 8 - y;
+        let near_surface = if fluid_at_surface { clamped_lerp(1.0f64, 0.0f64, depth as f64 / 64.0f64) } else { 0.0f64 };
+        let floodedness = self.noises.fluid_level_floodedness.compute(x, y, z).clamp(-1.0f64, 1.0f64);
+        if floodedness > lerp1(1.0f64 - near_surface, -0.3f64, 0.8f64) {
+            return global;
+        }
+        if floodedness <= lerp1(1.0f64 - near_surface, -0.8f64, 0.4f64) {
+            return FluidStatus::new(WAY_BELOW_MIN_Y, global.fluid_type);
+        }
+        let spread_x = x.div_euclid(16);
+        let spread_y = y.div_euclid(40);
+        let spread_z = z.div_euclid(16);
+        let spread = self.noises.fluid_level_spread.compute(spread_x, spread_y, spread_z) * 10.0f64;
+        let fluid_level = spread_y * 40 + 20 + (spread / 3.0f64).floor() as i32 * 3;
+        let level = min_surface_level.min(fluid_level);
+        if fluid_level <= -10 {
+            let lava = self.noises.lava.compute(x.div_euclid(64), y.div_euclid(40), z.div_euclid(64));
+            if lava.abs() > 0.3f64 {
+                return FluidStatus::new(level, Block::Lava);
+            }
+        }
+        FluidStatus::new(level, global.fluid_type)
+    }
+
+    fn calculate_pressure(&self, x: i32, y: i32, z: i32, barrier: &mut Option<f64>, first: FluidStatus, second: FluidStatus) -> f64 {
+        let first_block = first.at(y);
+        let second_block = second.at(y);
+        if (first_block == Block::Lava && second_block == Block::Water) || (first_block == Block::Water && second_block == Block::Lava) {
+            return 2.0f64;
+        }
+        let level_difference = (first.fluid_level - second.fluid_level).abs();
+        if level_difference == 0 {
+            return 0.0f64;
+        }
+        let middle = 0.5f64 * (first.fluid_level + second.fluid_level) as f64;
+        let offset = y as f64 + 0.5f64 - middle;
+        let gap = level_difference as f64 / 2.0f64 - offset.abs();
+        let pressure = if offset > 0.0f64 {
+            if gap > 0.0f64 { gap / 1.5f64 } else { gap / 2.5f64 }
+        } else {
+            let gap = 3.0f64 + gap;
+            if gap > 0.0f64 { gap / 3.0f64 } else { gap / 10.0f64 }
+        };
+        let barrier = if !(-2.0f64..=2.0f64).contains(&pressure) {
+            0.0f64
+        } else {
+            *barrier.get_or_insert_with(|| self.noises.barrier.compute(x, y, z))
+        };
+        2.0f64 * (barrier + pressure)
+    }
+
+    /// The block of an open (`density` not above 0) block, `None` for a solid one.
+    pub fn compute_substance<P: PreliminarySurface>(&mut self, x: i32, y: i32, z: i32, density: f64, surface: &mut P) -> Option<Block> {
+        self.should_schedule_fluid_update = false;
+        if density > 0.0f64 {
+            return None;
+        }
+        if self.global_fluid_picker.compute_fluid(x, y, z).at(y) == Block::Lava {
+            return Some(Block::Lava);
+        }
+        let grid_x = (x - 5).div_euclid(X_SPACING);
+        let grid_y = (y + 1).div_euclid(Y_SPACING);
+        let grid_z = (z - 5).div_euclid(Z_SPACING);
+        let mut distances = [i32::MAX; 3];
+        let mut locations = [(0, 0, 0); 3];
+        for offset_x in 0..=1 {
+            for offset_y in -1..=1 {
+                for offset_z in 0..=1 {
+                    let location = self.get_location(grid_x + offset_x, grid_y + offset_y, grid_z + offset_z);
+                    let (dx, dy, dz) = (location.0 - x, location.1 - y, location.2 - z);
+                    let distance = dx * dx + dy * dy + dz * dz;
+                    if distances[0] >= distance {
+                        distances = [distance, distances[0], distances[1]];
+                        locations = [location, locations[0], locations[1]];
+                    } else if distances[1] >= distance {
+                        distances = [distances[0], distance, distances[1]];
+                        locations = [locations[0], location, locations[1]];
+                    } else if distances[2] >= distance {
+                        distances[2] = distance;
+                        locations[2] = location;
+                    }
+                }
+            }
+        }
+        let closest = self.get_status(locations[0], surface);
+        let first_similarity = similarity(distances[0], distances[1]);
+        let block = closest.at(y);
+        if first_similarity <= 0.0f64 {
+            self.should_schedule_fluid_update = first_similarity >= similarity(100, 144);
+            return Some(block);
+        }
+        if block == Block::Water && self.global_fluid_picker.compute_fluid(x, y - 1, z).at(y - 1) == Block::Lava {
+            self.should_schedule_fluid_update = true;
+            return Some(block);
+        }
+        let mut barrier: Option<f64> = None;
+        let second = self.get_status(locations[1], surface);
+        if density + first_similarity * self.calculate_pressure(x, y, z, &mut barrier, closest, second) > 0.0f64 {
+            return None;
+        }
+        let third = self.get_status(locations[2], surface);
+        let second_similarity = similarity(distances[0], distances[2]);
+        if second_similarity > 0.0f64 && density + first_similarity * second_similarity * self.calculate_pressure(x, y, z, &mut barrier, closest, third) > 0.0f64 {
+            return None;
+        }
+        let third_similarity = similarity(distances[1], distances[2]);
+        if third_similarity > 0.0f64 && density + first_similarity * third_similarity * self.calculate_pressure(x, y, z, &mut barrier, second, third) > 0.0f64 {
+            return None;
+        }
+        self.should_schedule_fluid_update = true;
+        Some(block)
+    }
+}
@@ -0,0 +1,347 @@
+//! Renders the biomes of a region as a PNG, one color per biome, with an optional legend.
+//!
+//! ```text
+//! biome-map --seed 1 --dimension nether --x=-512 --z=-512 --width 1024 --height 1024 --legend -o nether.png
+//! biome-map --seed 1 --dimension end --version 1.18 --scale 16 -o end.png
+//! biome-map --seed 1 --dimension overworld --version 1.18 --scale 4 -o overworld.png
+//! ```
+//!
+//! Pixels are blocks zoomed by `Voronoi` unless `--quart` is given, in which case they are the
+//! noise (quart) positions the biome sources work on. The overworld is only rendered from 1.18 on,
+//! the 1.16 layer stack is not in the crate.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use noise_rs::biome_source::{Biome, BiomeSource, ClimateNetherBiomeSource, EndBiomeSource, NetherBiomeSource, OverworldBiomeSource};
+use noise_rs::math::sha2long;
+use noise_rs::voronoi::Voronoi;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Version {
+    #[value(name = "1.16")]
+    V1_16,
+    #[value(name = "1.17")]
+    V1_17,
+    #[value(name = "1.18")]
+    V1_18,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Dimension {
+    Overworld,
+    Nether,
+    End,
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "biome-map", about = "Renders the Minecraft biomes of a region")]
+struct Args {
+    #[arg(long, allow_hyphen_values = true)]
+    seed: i64,
+    #[arg(long, value_enum, default_value = "1.16")]
+    version: Version,
+    #[arg(long, value_enum)]
+    dimension: Dimension,
+    /// x of the top left corner of the region
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    x: i32,
+    /// y of the rendered slice
+    #[arg(long, default_value_t = 64, allow_hyphen_values = true)]
+    y: i32,
+    /// z of the top left corner of the region
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    z: i32,
+    #[arg(long, default_value_t = 256)]
+    width: usize,
+    #[arg(long, default_value_t = 256)]
+    height: usize,
+    /// blocks (quarts with `--quart`) per pixel
+    #[arg(long, default_value_t = 1)]
+    scale: i32,
+    /// the coordinates are noise (quart) positions, sampled without the biome zoom
+    #[arg(long)]
+    quart: bool,
+    /// draw the names and ids of the rendered biomes right of the map
+    #[arg(long)]
+    legend: bool,
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+fn get_color(biome: Biome) -> [u8; 3] {
+    match biome {
+        Biome::Ocean => [0, 0, 112],
+        Biome::Plains => [141, 179, 96],
+        Biome::Desert => [250, 148, 24],
+        Biome::WindsweptHills => [96, 96, 96],
+        Biome::Forest => [5, 102, 33],
+        Biome::Taiga => [11, 102, 89],
+        Biome::Swamp => [7, 249, 178],
+        Biome::River => [0, 0, 255],
+        Biome::NetherWastes => [191, 59, 59],
+        Biome::TheEnd => [128, 128, 255],
+        Biome::FrozenOcean => [112, 112, 214],
+        Biome::FrozenRiver => [160, 160, 255],
+        Biome::SnowyPlains => [255, 255, 255],
+        Biome::MushroomFields => [255, 0, 255],
+        Biome::Beach => [250, 222, 85],
+        Biome::Jungle => [83, 123, 9],
+        Biome::SparseJungle => [98, 139, 23],
+        Biome::DeepOcean => [0, 0, 48],
+        Biome::StonyShore => [162, 162, 132],
+        Biome::SnowyBeach => [250, 240, 192],
+        Biome::BirchForest => [48, 116, 68],
+        Biome::DarkForest => [64, 81, 26],
+        Biome::SnowyTaiga => [49, 85, 74],
+        Biome::OldGrowthPineTaiga => [89, 102, 81],
+        Biome::WindsweptForest => [80, 112, 80],
+        Biome::Savanna => [189, 178, 95],
+        Biome::SavannaPlateau => [167, 157, 100],
+        Biome::Badlands => [217, 69, 21],
+        Biome::WoodedBadlands => [176, 151, 101],
+        Biome::SmallEndIslands => [75, 75, 171],
+        Biome::EndMidlands => [201, 201, 89],
+        Biome::EndHighlands => [181, 181, 54],
+        Biome::EndBarrens => [112, 112, 204],
+        Biome::WarmOcean => [0, 0, 172],
+        Biome::LukewarmOcean => [0, 0, 144],
+        Biome::ColdOcean => [32, 32, 112],
+        Biome::DeepLukewarmOcean => [0, 0, 64],
+        Biome::DeepColdOcean => [32, 32, 56],
+        Biome::DeepFrozenOcean => [64, 64, 144],
+        Biome::SunflowerPlains => [181, 219, 136],
+        Biome::WindsweptGravellyHills => [136, 136, 136],
+        Biome::FlowerForest => [45, 142, 73],
+        Biome::IceSpikes => [180, 220, 220],
+        Biome::OldGrowthBirchForest => [88, 156, 108],
+        Biome::OldGrowthSpruceTaiga => [129, 142, 121],
+        Biome::WindsweptSavanna => [229, 218, 135],
+        Biome::ErodedBadlands => [255, 109, 61],
+        Biome::BambooJungle => [118, 142, 20],
+        Biome::SoulSandValley => [94, 56, 48],
+        Biome::CrimsonForest => [221, 8, 8],
+        Biome::WarpedForest => [73, 144, 123],
+        Biome::BasaltDeltas => [64, 54, 54],
+        Biome::DripstoneCaves => [78, 48, 18],
+        Biome::LushCaves => [40, 60, 0],
+        Biome::Meadow => [96, 164, 69],
+        Biome::Grove => [71, 114, 108],
+        Biome::SnowySlopes => [196, 196, 196],
+        Biome::JaggedPeaks => [220, 220, 200],
+        Biome::FrozenPeaks => [176, 179, 206],
+        Biome::StonyPeaks => [123, 143, 116],
+    }
+}
+
+fn create_biome_source(args: &Args) -> Result<Box<dyn BiomeSource>, String> {
+    match (args.dimension, args.version) {
+        (Dimension::Nether, Version::V1_16) | (Dimension::Nether, Version::V1_17) => Ok(Box::new(NetherBiomeSource::new(args.seed))),
+        (Dimension::End, _) => Ok(Box::new(EndBiomeSource::new(args.seed))),
+        (Dimension::Nether, Version::V1_18) => Ok(Box::new(ClimateNetherBiomeSource::new(args.seed))),
+        (Dimension::Overworld, Version::V1_18) => Ok(Box::new(OverworldBiomeSource::new(args.seed, false))),
+        (Dimension::Overworld, _) => Err("the 1.16 and 1.17 overworld biome source (layer stack) is not implemented".to_string()),
+    }
+}
+
+/// Biomes of the region row by row, `z` growing downwards.
+fn sample_biomes(args: &Args, source: &mut dyn BiomeSource) -> Vec<Biome> {
+    // every rendered biome source zooms in 3d
+    let mut voronoi = Voronoi::new(sha2long(args.seed as u64) as i64);
+    let mut biomes: Vec<Biome> = Vec::with_capacity(args.width * args.height);
+    for row in 0..args.height {
+        let z = args.z + row as i32 * args.scale;
+        for column in 0..args.width {
+            let x = args.x + column as i32 * args.scale;
+            let (quart_x, quart_y, quart_z) = if args.quart { (x, args.y, z) } else { voronoi.get_fuzzy_positions(x, args.y, z) };
+            biomes.push(source.get_noise_biome(quart_x, quart_y, quart_z));
+        }
+    }
+    biomes
+}
+
+/// A 3x5 pixel font for the legend, one row of three bits per byte.
+fn get_glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        _ => [0; 5],
+    }
+}
+
+const GLYPH_SCALE: usize = 2;
+const LINE_HEIGHT: usize = 7 * GLYPH_SCALE;
+const SWATCH_SIZE: usize = 5 * GLYPH_SCALE;
+const MARGIN: usize = 4;
+
+/// An RGB image being drawn.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas { width, height, pixels: vec![255; width * height * 3] }
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        if x < self.width && y < self.height {
+            let index = (y * self.width + x) * 3;
+            self.pixels[index..index + 3].copy_from_slice(&color);
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        for j in y..y + height {
+            for i in x..x + width {
+                self.set(i, j, color);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str) {
+        for (index, character) in text.chars().enumerate() {
+            let glyph = get_glyph(character);
+            let left = x + index * 4 * GLYPH_SCALE;
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..3 {
+                    if bits >> (2 - column) & 1 == 1 {
+                        self.fill(left + column * GLYPH_SCALE, y + row * GLYPH_SCALE, GLYPH_SCALE, GLYPH_SCALE, [0, 0, 0]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn get_label(biome: Biome, count: usize) -> String {
+    format!("{} {} {}", biome.get_id(), biome.get_name(), count)
+}
+
+/// Draws the map, and the legend of the biomes found in it with their pixel counts if asked to.
+fn render(width: usize, height: usize, biomes: &[Biome], legend: bool) -> Canvas {
+    let mut counts: BTreeMap<Biome, usize> = BTreeMap::new();
+    for biome in biomes {
+        *counts.entry(*biome).or_insert(0) += 1;
+    }
+    let (legend_width, legend_height) = if legend {
+        let characters = counts.iter().map(|(biome, count)| get_label(*biome, *count).len()).max().unwrap_or(0);
+        (MARGIN * 3 + SWATCH_SIZE + characters * 4 * GLYPH_SCALE, MARGIN + counts.len() * LINE_HEIGHT)
+    } else {
+        (0, 0)
+    };
+    let mut canvas = Canvas::new(width + legend_width, height.max(legend_height));
+    for (index, biome) in biomes.iter().enumerate() {
+        canvas.set(index % width, index / width, get_color(*biome));
+    }
+    if legend {
+        for (line, (biome, count)) in counts.iter().enumerate() {
+            let y = MARGIN + line * LINE_HEIGHT;
+            canvas.fill(width + MARGIN, y, SWATCH_SIZE, SWATCH_SIZE, get_color(*biome));
+            canvas.draw_text(width + MARGIN * 2 + SWATCH_SIZE, y, &get_label(*biome, *count));
+        }
+    }
+    canvas
+}
+
+fn write_png(canvas: &Canvas, path: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), canvas.width as u32, canvas.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&canvas.pixels)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    if args.width == 0 || args.height == 0 || args.scale <= 0 {
+        return Err("the region must be at least one pixel wide and high, with a positive scale".into());
+    }
+    let mut source = create_biome_source(&args)?;
+    let biomes = sample_biomes(&args, source.as_mut());
+    let canvas = render(args.width, args.height, &biomes, args.legend);
+    write_png(&canvas, &args.output)
+}
+
+#[cfg(test)]
+mod biome_map_test {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Args {
+        Args::parse_from(["biome-map", "-o", "map.png"].iter().chain(arguments.iter()))
+    }
+
+    #[test]
+    fn test_climate_sources() {
+        let args = parse(&["--seed", "1", "--dimension", "nether", "--version", "1.18", "--quart", "--x=100", "--z=200", "--width", "1", "--height", "1"]);
+        let mut source = create_biome_source(&args).unwrap();
+        assert_eq!(sample_biomes(&args, source.as_mut()), vec![ClimateNetherBiomeSource::new(1).get_noise_biome(100, 64, 200)]);
+        let args = parse(&["--seed", "1", "--dimension", "overworld", "--version", "1.18", "--quart", "--y=16", "--width", "4", "--height", "1"]);
+        let mut source = create_biome_source(&args).unwrap();
+        let mut expected = OverworldBiomeSource::new(1, false);
+        assert_eq!(sample_biomes(&args, source.as_mut()), (0..4).map(|x| expected.get_noise_biome(x, 16, 0)).collect::<Vec<Biome>>());
+        assert!(create_biome_source(&parse(&["--seed", "1", "--dimension", "overworld", "--version", "1.16"])).is_err());
+    }
+
+    #[test]
+    fn test_end_center() {
+        let args = parse(&["--seed", "1", "--dimension", "end", "--x=-8", "--z=-8", "--width", "16", "--height", "16"]);
+        let mut source = create_biome_source(&args).unwrap();
+        assert!(sample_biomes(&args, source.as_mut()).iter().all(|biome| *biome == Biome::TheEnd));
+    }
+
+    #[test]
+    fn test_render() {
+        let biomes = [Biome::NetherWastes, Biome::WarpedForest, Biome::NetherWastes, Biome::NetherWastes];
+        let canvas = render(2, 2, &biomes, false);
+        assert_eq!((canvas.width, canvas.height), (2, 2));
+        assert_eq!(&canvas.pixels[3..6], &get_color(Biome::WarpedForest));
+        let canvas = render(2, 2, &biomes, true);
+        assert_eq!(canvas.width, 2 + MARGIN * 3 + SWATCH_SIZE + get_label(Biome::WarpedForest, 1).len() * 4 * GLYPH_SCALE);
+        assert_eq!(canvas.height, MARGIN + 2 * LINE_HEIGHT);
+        let swatch = ((MARGIN + LINE_HEIGHT) * canvas.width + 2 + MARGIN) * 3;
+        assert_eq!(&canvas.pixels[swatch..swatch + 3], &get_color(Biome::WarpedForest));
+    }
+}
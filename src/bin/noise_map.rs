@@ -0,0 +1,308 @@
+//! Renders a noise over a region as a grayscale image, or dumps the sampled values.
+//!
+//! ```text
+//! noise-map --seed 1 --kind perlin --octaves=-7..0 --width 512 --height 512 -o perlin.png
+//! noise-map --seed 1 --kind double-perlin --first-octave=-7 --amplitudes 1,1 --scale 0.25 -o temperature.csv
+//! noise-map --seed 1 --kind end-islands --x=-256 --z=-256 -o islands.pgm
+//! ```
+//!
+//! The perlin noises take the java random `seed` directly, with either the 1.16.1 `--octaves` list
+//! or the 1.16.2+ `--first-octave` and `--amplitudes`. The simplex noise is the 2d one, and the end
+//! islands are sampled at integer coordinates in units of 8 blocks, the world seed being `seed`.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use java_random::Random;
+use noise_rs::create_range;
+use noise_rs::double_perlin_noise::DoublePerlinNoise;
+use noise_rs::end_islands::EndIslands;
+use noise_rs::perlin_noise::{check_amplitudes, PerlinNoise};
+use noise_rs::simplex_noise::SimplexNoise;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Kind {
+    Perlin,
+    DoublePerlin,
+    Simplex,
+    EndIslands,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Format {
+    /// 8 bit grayscale PNG
+    Png,
+    /// 8 bit binary grayscale PGM (P5)
+    Pgm,
+    /// one line of comma separated values per row
+    Csv,
+    /// row major little endian f64 values
+    Raw,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(Format::Png),
+            "pgm" => Some(Format::Pgm),
+            "csv" => Some(Format::Csv),
+            "raw" | "bin" => Some(Format::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// An octave list, either `low..high` (inclusive) or `a,b,c`, sorted and without duplicates.
+#[derive(Clone, Debug, PartialEq)]
+struct Octaves(Vec<i32>);
+
+impl FromStr for Octaves {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| value.trim().parse::<i32>().map_err(|error| format!("invalid octave {:?}: {}", value, error));
+        let mut octaves: Vec<i32> = match s.split_once("..") {
+            Some((low, high)) => create_range(parse(low)?, parse(high)?),
+            None => s.split(',').map(parse).collect::<Result<Vec<i32>, String>>()?,
+        };
+        if octaves.is_empty() {
+            return Err(format!("no octaves in {:?}", s));
+        }
+        octaves.sort_unstable();
+        octaves.dedup();
+        Ok(Octaves(octaves))
+    }
+}
+
+/// A comma separated amplitude list, checked with [`check_amplitudes`].
+#[derive(Clone, Debug, PartialEq)]
+struct Amplitudes(Vec<f64>);
+
+impl FromStr for Amplitudes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let amplitudes: Vec<f64> = if s.trim().is_empty() {
+            Vec::new()
+        } else {
+            s.split(',').map(|value| value.trim().parse::<f64>().map_err(|error| format!("invalid amplitude {:?}: {}", value, error))).collect::<Result<Vec<f64>, String>>()?
+        };
+        check_amplitudes(&amplitudes).map_err(|error| error.to_string())?;
+        Ok(Amplitudes(amplitudes))
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "noise-map", version, about = "Renders a Minecraft noise over a region")]
+struct Args {
+    /// seed of the noise (the world seed for the end islands)
+    #[arg(long, allow_hyphen_values = true)]
+    seed: i64,
+    #[arg(long, value_enum)]
+    kind: Kind,
+    /// 1.16.1 octaves of the perlin noises, as `low..high` (inclusive) or a comma separated list
+    #[arg(long, default_value = "-7..0", allow_hyphen_values = true)]
+    octaves: Octaves,
+    /// first octave of a 1.16.2+ perlin noise, used with `--amplitudes`
+    #[arg(long, allow_hyphen_values = true, requires = "amplitudes")]
+    first_octave: Option<i32>,
+    /// comma separated octave amplitudes of a 1.16.2+ perlin noise
+    #[arg(long, allow_hyphen_values = true, requires = "first_octave")]
+    amplitudes: Option<Amplitudes>,
+    /// x of the top left corner of the region
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    x: f64,
+    /// y the 3d noises are sampled at
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    y: f64,
+    /// z of the top left corner of the region
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    z: f64,
+    #[arg(long, default_value_t = 256)]
+    width: usize,
+    #[arg(long, default_value_t = 256)]
+    height: usize,
+    /// noise coordinates per pixel
+    #[arg(long, default_value_t = 1.0)]
+    scale: f64,
+    /// value mapped to black, the lowest sampled value by default
+    #[arg(long, allow_hyphen_values = true)]
+    min: Option<f64>,
+    /// value mapped to white, the highest sampled value by default
+    #[arg(long, allow_hyphen_values = true)]
+    max: Option<f64>,
+    /// output format, guessed from the output extension by default
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+fn create_sampler(args: &Args) -> Box<dyn FnMut(f64, f64) -> f64> {
+    let y = args.y;
+    let mut random = Random::with_seed(args.seed as u64);
+    match args.kind {
+        Kind::Perlin => {
+            let noise = match (args.first_octave, &args.amplitudes) {
+                (Some(first_octave), Some(amplitudes)) => PerlinNoise::create_legacy(&mut random, first_octave, amplitudes.0.clone()),
+                _ => PerlinNoise::new(&mut random, args.octaves.0.clone()),
+            };
+            Box::new(move |x, z| noise.sample_default(x, y, z))
+        }
+        Kind::DoublePerlin => {
+            let noise = match (args.first_octave, &args.amplitudes) {
+                (Some(first_octave), Some(amplitudes)) => DoublePerlinNoise::create_legacy(&mut random, first_octave, amplitudes.0.clone()),
+                _ => DoublePerlinNoise::new(&mut random, args.octaves.0.clone()),
+            };
+            Box::new(move |x, z| noise.sample(x, y, z))
+        }
+        Kind::Simplex => {
+            let mut noise = SimplexNoise::init(random);
+            Box::new(move |x, z| noise.get_value_2d(x, z))
+        }
+        Kind::EndIslands => {
+            let mut islands = EndIslands::new(args.seed as u64);
+            Box::new(move |x, z| islands.get_height_value(x.floor() as i32, z.floor() as i32) as f64)
+        }
+    }
+}
+
+/// Samples the region row by row, `z` growing downwards.
+fn sample_region(args: &Args) -> Vec<f64> {
+    let mut sample = create_sampler(args);
+    let mut values: Vec<f64> = Vec::with_capacity(args.width * args.height);
+    for row in 0..args.height {
+        let z = args.z + row as f64 * args.scale;
+        for column in 0..args.width {
+            values.push(sample(args.x + column as f64 * args.scale, z));
+        }
+    }
+    values
+}
+
+fn to_grayscale(values: &[f64], min: Option<f64>, max: Option<f64>) -> Vec<u8> {
+    let min = min.unwrap_or_else(|| values.iter().cloned().fold(f64::INFINITY, f64::min));
+    let max = max.unwrap_or_else(|| values.iter().cloned().fold(f64::NEG_INFINITY, f64::max));
+    let range = max - min;
+    values
+        .iter()
+        .map(|&value| {
+            if range > 0.0f64 {
+                (((value - min) / range).clamp(0.0f64, 1.0f64) * 255.0f64).round() as u8
+            } else {
+                0u8
+            }
+        })
+        .collect()
+}
+
+fn write_pgm<W: Write>(writer: &mut W, width: usize, height: usize, pixels: &[u8]) -> std::io::Result<()> {
+    write!(writer, "P5\n{} {}\n255\n", width, height)?;
+    writer.write_all(pixels)
+}
+
+fn write_png<W: Write>(writer: W, width: usize, height: usize, pixels: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    Ok(())
+}
+
+fn write_csv<W: Write>(writer: &mut W, width: usize, values: &[f64]) -> std::io::Result<()> {
+    for row in values.chunks(width) {
+        let line: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+        writeln!(writer, "{}", line.join(","))?;
+    }
+    Ok(())
+}
+
+fn write_raw<W: Write>(writer: &mut W, values: &[f64]) -> std::io::Result<()> {
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    if args.width == 0 || args.height == 0 {
+        return Err("the region must be at least one pixel wide and high".into());
+    }
+    let format = match args.format.or_else(|| Format::from_path(&args.output)) {
+        Some(format) => format,
+        None => return Err(format!("cannot guess the format of {}, pass --format", args.output.display()).into()),
+    };
+    let values = sample_region(&args);
+    let mut writer = BufWriter::new(File::create(&args.output)?);
+    match format {
+        Format::Png => write_png(&mut writer, args.width, args.height, &to_grayscale(&values, args.min, args.max))?,
+        Format::Pgm => write_pgm(&mut writer, args.width, args.height, &to_grayscale(&values, args.min, args.max))?,
+        Format::Csv => write_csv(&mut writer, args.width, &values)?,
+        Format::Raw => write_raw(&mut writer, &values)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod noise_map_test {
+    use super::*;
+
+    #[test]
+    fn test_octaves() {
+        assert_eq!("-3..0".parse::<Octaves>(), Ok(Octaves(vec![-3, -2, -1, 0])));
+        assert_eq!("-7, -5,0".parse::<Octaves>(), Ok(Octaves(vec![-7, -5, 0])));
+        assert_eq!("0,-7,-5,0".parse::<Octaves>(), Ok(Octaves(vec![-7, -5, 0])));
+        assert!("0..-1".parse::<Octaves>().is_err());
+        assert!("a".parse::<Octaves>().is_err());
+    }
+
+    #[test]
+    fn test_amplitudes() {
+        assert_eq!("1, 0,0.5".parse::<Amplitudes>(), Ok(Amplitudes(vec![1.0f64, 0.0f64, 0.5f64])));
+        assert!("".parse::<Amplitudes>().is_err());
+        assert!("1,,1".parse::<Amplitudes>().is_err());
+        let error = Args::try_parse_from(["noise-map", "--seed", "1", "--kind", "perlin", "--first-octave=-3", "--amplitudes=", "-o", "perlin.png"]).unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::from_path(Path::new("map.PNG")), Some(Format::Png));
+        assert_eq!(Format::from_path(Path::new("map.bin")), Some(Format::Raw));
+        assert_eq!(Format::from_path(Path::new("map")), None);
+    }
+
+    #[test]
+    fn test_grayscale() {
+        assert_eq!(to_grayscale(&[-1.0f64, 0.0f64, 1.0f64], None, None), vec![0, 128, 255]);
+        assert_eq!(to_grayscale(&[-1.0f64, 0.0f64, 1.0f64], Some(0.0f64), Some(0.5f64)), vec![0, 0, 255]);
+        assert_eq!(to_grayscale(&[2.0f64, 2.0f64], None, None), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_writers() {
+        let mut pgm: Vec<u8> = Vec::new();
+        write_pgm(&mut pgm, 2, 1, &[0, 255]).unwrap();
+        assert_eq!(pgm, b"P5\n2 1\n255\n\x00\xff".to_vec());
+        let mut csv: Vec<u8> = Vec::new();
+        write_csv(&mut csv, 2, &[0.5f64, -1.0f64, 2.0f64, 0.0f64]).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "0.5,-1\n2,0\n");
+        let mut raw: Vec<u8> = Vec::new();
+        write_raw(&mut raw, &[1.0f64]).unwrap();
+        assert_eq!(raw, 1.0f64.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_sample_region() {
+        let args = Args::parse_from(["noise-map", "--seed", "1", "--kind", "end-islands", "--width", "2", "--height", "2", "--scale", "3", "-o", "islands.png"]);
+        assert_eq!(sample_region(&args), vec![80.0f64, 76.0f64, 76.0f64, 66.058876f32 as f64]);
+    }
+}
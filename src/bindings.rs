@@ -0,0 +1,73 @@
+//! Noise construction and argument checks of the language bindings.
+
+use alloc::vec::Vec;
+
+use java_random::Random;
+
+use crate::double_perlin_noise::{DoublePerlinNoise, NoiseParameters};
+use crate::perlin_noise::{check_amplitudes, check_octaves, OctaveError, PerlinNoise};
+use crate::random_source::{RandomSource, WorldgenRandom};
+
+pub(crate) const LENGTH_MISMATCH: &str = "coordinate arrays must have the same length";
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod bindings_test {
+    use super::*;
+
+    #[test]
+    fn test_octave_errors() {
+        assert!(perlin_with_octaves(1, vec![-1, 0]).is_ok());
+        assert_eq!(perlin_with_octaves(1, vec![]).err(), Some(OctaveError::Empty));
+        assert_eq!(perlin_with_octaves(1, vec![0, -7]).err(), Some(OctaveError::Unsorted));
+        assert_eq!(double_perlin_with_octaves(1, vec![-3, -3]).err(), Some(OctaveError::Duplicate(-3)));
+        assert_eq!(perlin_with_amplitudes(1, -7, vec![]).err(), Some(OctaveError::Empty));
+        assert_eq!(double_perlin_with_amplitudes(1, -7, vec![]).err(), Some(OctaveError::Empty));
+        assert_eq!(double_perlin_with_parameters(1, false, "minecraft:temperature", -10, vec![]).err(), Some(OctaveError::Empty));
+    }
+
+    #[test]
+    fn test_common_length() {
+        assert_eq!(common_length(&[3, 3, 3]), Some(3));
+        assert_eq!(common_length(&[0, 0]), Some(0));
+        assert_eq!(common_length(&[3, 2, 3]), None);
+    }
+}
+
+/// The length of the coordinate arrays of a batch call, `None` if they differ.
+pub(crate) fn common_length(lengths: &[usize]) -> Option<usize> {
+    let length = lengths[0];
+    if lengths.iter().any(|&other| other != length) {
+        return None;
+    }
+    Some(length)
+}
+
+/// The 1.16.1 noise of the java random `seed` and the `octaves` list.
+pub(crate) fn perlin_with_octaves(seed: i64, octaves: Vec<i32>) -> Result<PerlinNoise, OctaveError> {
+    check_octaves(&octaves)?;
+    Ok(PerlinNoise::new(&mut Random::with_seed(seed as u64), octaves))
+}
+
+/// The 1.16.2+ noise of the java random `seed`, the first octave and the octave amplitudes.
+pub(crate) fn perlin_with_amplitudes(seed: i64, first_octave: i32, amplitudes: Vec<f64>) -> Result<PerlinNoise, OctaveError> {
+    check_amplitudes(&amplitudes)?;
+    Ok(PerlinNoise::create_legacy(&mut Random::with_seed(seed as u64), first_octave, amplitudes))
+}
+
+pub(crate) fn double_perlin_with_octaves(seed: i64, octaves: Vec<i32>) -> Result<DoublePerlinNoise, OctaveError> {
+    check_octaves(&octaves)?;
+    Ok(DoublePerlinNoise::new(&mut Random::with_seed(seed as u64), octaves))
+}
+
+pub(crate) fn double_perlin_with_amplitudes(seed: i64, first_octave: i32, amplitudes: Vec<f64>) -> Result<DoublePerlinNoise, OctaveError> {
+    check_amplitudes(&amplitudes)?;
+    Ok(DoublePerlinNoise::create_legacy(&mut Random::with_seed(seed as u64), first_octave, amplitudes))
+}
+
+/// The 1.18+ noise registered as `name` in a world of `world_seed`.
+pub(crate) fn double_perlin_with_parameters(world_seed: i64, legacy_random_source: bool, name: &str, first_octave: i32, amplitudes: Vec<f64>) -> Result<DoublePerlinNoise, OctaveError> {
+    check_amplitudes(&amplitudes)?;
+    let factory = WorldgenRandom::new(world_seed, legacy_random_source).fork_positional();
+    Ok(DoublePerlinNoise::instantiate(&factory, name, &NoiseParameters::new(first_octave, amplitudes)))
+}
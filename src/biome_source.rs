@@ -0,0 +1,475 @@
+use alloc::vec;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use java_random::Random;
+
+use crate::climate::{Parameter, ParameterList, ParameterPoint, TargetPoint};
+#[cfg(feature = "std")]
+use crate::density_function::DensityFunction;
+use crate::double_perlin_noise::DoublePerlinNoise;
+#[cfg(feature = "std")]
+use crate::double_perlin_noise::NoiseParameters;
+use crate::end_islands::EndIslands;
+#[cfg(feature = "std")]
+use crate::overworld_biome_builder::OverworldBiomeBuilder;
+#[cfg(feature = "std")]
+use crate::random_source::{RandomSource, WorldgenRandom};
+#[cfg(feature = "std")]
+use crate::terrain_shaper::{ClimateSampler, TerrainShaper};
+
+/// The biomes the biome sources place, with their 1.16 numeric ids. Biomes renamed in 1.18 keep the id
+/// of their 1.16 name, the ones added in 1.17 and 1.18 continue after the nether biomes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Biome {
+    Ocean = 0,
+    Plains = 1,
+    Desert = 2,
+    WindsweptHills = 3,
+    Forest = 4,
+    Taiga = 5,
+    Swamp = 6,
+    River = 7,
+    NetherWastes = 8,
+    TheEnd = 9,
+    FrozenOcean = 10,
+    FrozenRiver = 11,
+    SnowyPlains = 12,
+    MushroomFields = 14,
+    Beach = 16,
+    Jungle = 21,
+    SparseJungle = 23,
+    DeepOcean = 24,
+    StonyShore = 25,
+    SnowyBeach = 26,
+    BirchForest = 27,
+    DarkForest = 29,
+    SnowyTaiga = 30,
+    OldGrowthPineTaiga = 32,
+    WindsweptForest = 34,
+    Savanna = 35,
+    SavannaPlateau = 36,
+    Badlands = 37,
+    WoodedBadlands = 38,
+    SmallEndIslands = 40,
+    EndMidlands = 41,
+    EndHighlands = 42,
+    EndBarrens = 43,
+    WarmOcean = 44,
+    LukewarmOcean = 45,
+    ColdOcean = 46,
+    DeepLukewarmOcean = 48,
+    DeepColdOcean = 49,
+    DeepFrozenOcean = 50,
+    SunflowerPlains = 129,
+    WindsweptGravellyHills = 131,
+    FlowerForest = 132,
+    IceSpikes = 140,
+    OldGrowthBirchForest = 155,
+    OldGrowthSpruceTaiga = 160,
+    WindsweptSavanna = 163,
+    ErodedBadlands = 165,
+    BambooJungle = 168,
+    SoulSandValley = 170,
+    CrimsonForest = 171,
+    WarpedForest = 172,
+    BasaltDeltas = 173,
+    DripstoneCaves = 174,
+    LushCaves = 175,
+    Meadow = 177,
+    Grove = 178,
+    SnowySlopes = 179,
+    JaggedPeaks = 180,
+    FrozenPeaks = 181,
+    StonyPeaks = 182,
+}
+
+impl Biome {
+    pub fn get_id(&self) -> i32 {
+        *self as i32
+    }
+
+    /// The path of the biome resource location, `nether_wastes` for `minecraft:nether_wastes`.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Biome::Ocean => "ocean",
+            Biome::Plains => "plains",
+            Biome::Desert => "desert",
+            Biome::WindsweptHills => "windswept_hills",
+            Biome::Forest => "forest",
+            Biome::Taiga => "taiga",
+            Biome::Swamp => "swamp",
+            Biome::River => "river",
+            Biome::NetherWastes => "nether_wastes",
+            Biome::TheEnd => "the_end",
+            Biome::FrozenOcean => "frozen_ocean",
+            Biome::FrozenRiver => "frozen_river",
+            Biome::SnowyPlains => "snowy_plains",
+            Biome::MushroomFields => "mushroom_fields",
+            Biome::Beach => "beach",
+            Biome::Jungle => "jungle",
+            Biome::SparseJungle => "sparse_jungle",
+            Biome::DeepOcean => "deep_ocean",
+            Biome::StonyShore => "stony_shore",
+            Biome::SnowyBeach => "snowy_beach",
+            Biome::BirchForest => "birch_forest",
+            Biome::DarkForest => "dark_forest",
+            Biome::SnowyTaiga => "snowy_taiga",
+            Biome::OldGrowthPineTaiga => "old_growth_pine_taiga",
+            Biome::WindsweptForest => "windswept_forest",
+            Biome::Savanna => "savanna",
+            Biome::SavannaPlateau => "savanna_plateau",
+            Biome::Badlands => "badlands",
+            Biome::WoodedBadlands => "wooded_badlands",
+            Biome::SmallEndIslands => "small_end_islands",
+            Biome::EndMidlands => "end_midlands",
+            Biome::EndHighlands => "end_highlands",
+            Biome::EndBarrens => "end_barrens",
+            Biome::WarmOcean => "warm_ocean",
+            Biome::LukewarmOcean => "lukewarm_ocean",
+            Biome::ColdOcean => "cold_ocean",
+            Biome::DeepLukewarmOcean => "deep_lukewarm_ocean",
+            Biome::DeepColdOcean => "deep_cold_ocean",
+            Biome::DeepFrozenOcean => "deep_frozen_ocean",
+            Biome::SunflowerPlains => "sunflower_plains",
+            Biome::WindsweptGravellyHills => "windswept_gravelly_hills",
+            Biome::FlowerForest => "flower_forest",
+            Biome::IceSpikes => "ice_spikes",
+            Biome::OldGrowthBirchForest => "old_growth_birch_forest",
+            Biome::OldGrowthSpruceTaiga => "old_growth_spruce_taiga",
+            Biome::WindsweptSavanna => "windswept_savanna",
+            Biome::ErodedBadlands => "eroded_badlands",
+            Biome::BambooJungle => "bamboo_jungle",
+            Biome::SoulSandValley => "soul_sand_valley",
+            Biome::CrimsonForest => "crimson_forest",
+            Biome::WarpedForest => "warped_forest",
+            Biome::BasaltDeltas => "basalt_deltas",
+            Biome::DripstoneCaves => "dripstone_caves",
+            Biome::LushCaves => "lush_caves",
+            Biome::Meadow => "meadow",
+            Biome::Grove => "grove",
+            Biome::SnowySlopes => "snowy_slopes",
+            Biome::JaggedPeaks => "jagged_peaks",
+            Biome::FrozenPeaks => "frozen_peaks",
+            Biome::StonyPeaks => "stony_peaks",
+        }
+    }
+}
+
+/// Provides the biome at noise (quart) coordinates, `Voronoi` zooming it to block scale.
+pub trait BiomeSource {
+    fn get_noise_biome(&mut self, x: i32, y: i32, z: i32) -> Biome;
+}
+
+/// Temperature, humidity, altitude, weirdness and offset of the nether biomes, 1.18 keeping all but altitude and weirdness.
+pub(crate) const NETHER_BIOMES: [(Biome, [f32; 5]); 5] = [
+    (Biome::NetherWastes, [0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32]),
+    (Biome::SoulSandValley, [0.0f32, -0.5f32, 0.0f32, 0.0f32, 0.0f32]),
+    (Biome::CrimsonForest, [0.4f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32]),
+    (Biome::WarpedForest, [0.0f32, 0.5f32, 0.0f32, 0.0f32, 0.375f32]),
+    (Biome::BasaltDeltas, [-0.5f32, 0.0f32, 0.0f32, 0.0f32, 0.175f32]),
+];
+
+/// The 1.16 and 1.17 nether preset of `MultiNoiseBiomeSource`, y independent.
+#[derive(Clone, Debug)]
+pub struct NetherBiomeSource {
+    temperature: DoublePerlinNoise,
+    humidity: DoublePerlinNoise,
+    altitude: DoublePerlinNoise,
+    weirdness: DoublePerlinNoise,
+}
+
+/// The 1.18 nether preset of `MultiNoiseBiomeSource`, the 1.16 temperature and humidity noises matched
+/// against a climate parameter list. The other climate values of the nether are 0.
+#[derive(Clone, Debug)]
+pub struct ClimateNetherBiomeSource {
+    temperature: DoublePerlinNoise,
+    humidity: DoublePerlinNoise,
+    parameters: ParameterList<Biome>,
+}
+
+/// The 1.18 overworld preset of `MultiNoiseBiomeSource`.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct OverworldBiomeSource {
+    temperature: DensityFunction,
+    humidity: DensityFunction,
+    climate: ClimateSampler,
+    shaper: TerrainShaper,
+    parameters: ParameterList<Biome>,
+}
+
+/// The 1.16+ `TheEndBiomeSource`.
+#[derive(Clone, Debug)]
+pub struct EndBiomeSource {
+    islands: EndIslands,
+    last_chunk: Option<(i32, i32, Biome)>,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod biome_source_test {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn grid(source: &mut impl BiomeSource, x: i32, y: i32, z: i32, step: i32, size: i32) -> Vec<Vec<Biome>> {
+        (0..size).map(|j| (0..size).map(|i| source.get_noise_biome(x + i * step, y, z + j * step)).collect()).collect()
+    }
+
+    #[test]
+    fn test_nether() {
+        let mut source = NetherBiomeSource::new(1);
+        assert_eq!(source.get_noise_biome(0, 0, 0), Biome::NetherWastes);
+        assert_eq!(source.get_noise_biome(100, 0, 200), Biome::SoulSandValley);
+        assert_eq!(source.get_noise_biome(100, 30, 200), Biome::SoulSandValley);
+        assert_eq!(source.get_noise_biome(-300, 0, 50), Biome::SoulSandValley);
+        assert_eq!(source.get_noise_biome(37, 0, -80), Biome::NetherWastes);
+    }
+
+    #[test]
+    fn test_nether_has_all_biomes() {
+        let mut source = NetherBiomeSource::new(-42);
+        let mut found = vec![];
+        for x in 0..64 {
+            for z in 0..64 {
+                let biome = source.get_noise_biome(x * 16, 0, z * 16);
+                if !found.contains(&biome) {
+                    found.push(biome);
+                }
+            }
+        }
+        assert_eq!(found.len(), NETHER_BIOMES.len());
+    }
+
+    #[test]
+    fn test_climate_nether() {
+        // vanilla 1.18.2 biomes queried row by row, the middle one being as close to nether wastes as to a
+        // crimson forest and taking the crimson forest of the previous query
+        let mut source = ClimateNetherBiomeSource::new(-42);
+        assert_eq!(
+            grid(&mut source, 73, 0, 47, 1, 5),
+            [
+                [Biome::NetherWastes, Biome::NetherWastes, Biome::NetherWastes, Biome::NetherWastes, Biome::NetherWastes],
+                [Biome::NetherWastes, Biome::NetherWastes, Biome::NetherWastes, Biome::NetherWastes, Biome::NetherWastes],
+                [Biome::NetherWastes, Biome::CrimsonForest, Biome::CrimsonForest, Biome::NetherWastes, Biome::NetherWastes],
+                [Biome::CrimsonForest, Biome::CrimsonForest, Biome::CrimsonForest, Biome::CrimsonForest, Biome::CrimsonForest],
+                [Biome::CrimsonForest, Biome::CrimsonForest, Biome::CrimsonForest, Biome::CrimsonForest, Biome::CrimsonForest],
+            ]
+        );
+        assert_eq!(source.get_parameters().find_value_brute_force(&source.sample(75, 0, 49)), Biome::NetherWastes);
+        assert_eq!(source.get_noise_biome(75, 100, 49), Biome::CrimsonForest);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_overworld() {
+        // vanilla 1.18.2 biomes of seed 1 at y 64
+        let mut source = OverworldBiomeSource::new(1, false);
+        assert_eq!(
+            grid(&mut source, -1024, 16, -1024, 256, 6),
+            [
+                [Biome::Forest, Biome::Taiga, Biome::ColdOcean, Biome::ColdOcean, Biome::Forest, Biome::Ocean],
+                [Biome::LukewarmOcean, Biome::DripstoneCaves, Biome::StonyShore, Biome::Taiga, Biome::DeepColdOcean, Biome::DeepOcean],
+                [Biome::Savanna, Biome::Plains, Biome::Grove, Biome::FrozenRiver, Biome::StonyShore, Biome::ColdOcean],
+                [Biome::Swamp, Biome::Forest, Biome::Forest, Biome::SnowyTaiga, Biome::SnowyTaiga, Biome::DeepOcean],
+                [Biome::DeepOcean, Biome::SparseJungle, Biome::LukewarmOcean, Biome::DeepOcean, Biome::DeepOcean, Biome::Plains],
+                [Biome::Plains, Biome::DripstoneCaves, Biome::DripstoneCaves, Biome::LukewarmOcean, Biome::Ocean, Biome::OldGrowthBirchForest],
+            ]
+        );
+        // the middle one is as close to a stony shore as to the beach of the previous query
+        let mut source = OverworldBiomeSource::new(1, false);
+        assert_eq!(
+            grid(&mut source, 46, 16, 27, 1, 5),
+            [
+                [Biome::Beach, Biome::Beach, Biome::Beach, Biome::River, Biome::River],
+                [Biome::Beach, Biome::Beach, Biome::River, Biome::River, Biome::River],
+                [Biome::Beach, Biome::Beach, Biome::Beach, Biome::River, Biome::River],
+                [Biome::StonyShore, Biome::StonyShore, Biome::StonyShore, Biome::StonyShore, Biome::StonyShore],
+                [Biome::StonyShore, Biome::StonyShore, Biome::StonyShore, Biome::StonyShore, Biome::StonyShore],
+            ]
+        );
+        assert_eq!(source.get_parameters().find_value_brute_force(&source.sample(48, 16, 29)), Biome::StonyShore);
+    }
+
+    #[test]
+    fn test_end() {
+        let mut source = EndBiomeSource::new(1);
+        assert_eq!(source.get_noise_biome(0, 0, 0), Biome::TheEnd);
+        assert_eq!(source.get_noise_biome(255, 0, 0), Biome::TheEnd);
+        assert_ne!(source.get_noise_biome(260, 0, 0), Biome::TheEnd);
+    }
+}
+
+/// The nether biome closest to the climate point, the first of equally fit ones winning.
+pub(crate) fn get_nearest_nether_biome(point: &[f32; 5]) -> Biome {
+    let mut best: Biome = NETHER_BIOMES[0].0;
+    let mut best_fitness: f32 = f32::INFINITY;
+    for (biome, parameters) in NETHER_BIOMES.iter() {
+        let mut fitness: f32 = 0.0f32;
+        for i in 0..5 {
+            fitness += (parameters[i] - point[i]) * (parameters[i] - point[i]);
+        }
+        if fitness < best_fitness {
+            best = *biome;
+            best_fitness = fitness;
+        }
+    }
+    best
+}
+
+impl NetherBiomeSource {
+    pub fn new(world_seed: i64) -> Self {
+        let create = |salt: i64| DoublePerlinNoise::create_legacy(&mut Random::with_seed(world_seed.wrapping_add(salt) as u64), -7, vec![1.0f64, 1.0f64]);
+        NetherBiomeSource {
+            temperature: create(0),
+            humidity: create(1),
+            altitude: create(2),
+            weirdness: create(3),
+        }
+    }
+}
+
+impl BiomeSource for NetherBiomeSource {
+    fn get_noise_biome(&mut self, x: i32, _y: i32, z: i32) -> Biome {
+        let (x, z) = (x as f64, z as f64);
+        let point: [f32; 5] = [
+            self.temperature.sample(x, 0.0f64, z) as f32,
+            self.humidity.sample(x, 0.0f64, z) as f32,
+            self.altitude.sample(x, 0.0f64, z) as f32,
+            self.weirdness.sample(x, 0.0f64, z) as f32,
+            0.0f32,
+        ];
+        get_nearest_nether_biome(&point)
+    }
+}
+
+impl ClimateNetherBiomeSource {
+    pub fn new(world_seed: i64) -> Self {
+        let create = |salt: i64| DoublePerlinNoise::create_legacy(&mut Random::with_seed(world_seed.wrapping_add(salt) as u64), -7, vec![1.0f64, 1.0f64]);
+        let zero = Parameter::point(0.0f32);
+        let point = |temperature: f32, humidity: f32, offset: f32| {
+            ParameterPoint::new(Parameter::point(temperature), Parameter::point(humidity), zero, zero, zero, zero, offset)
+        };
+        ClimateNetherBiomeSource {
+            temperature: create(0),
+            humidity: create(1),
+            parameters: ParameterList::new(
+                NETHER_BIOMES.iter().map(|(biome, parameters)| (point(parameters[0], parameters[1], parameters[4]), *biome)).collect(),
+            ),
+        }
+    }
+
+    pub fn get_parameters(&self) -> &ParameterList<Biome> {
+        &self.parameters
+    }
+
+    /// The climate at noise (quart) coordinates, y independent.
+    pub fn sample(&self, x: i32, _y: i32, z: i32) -> TargetPoint {
+        let (x, z) = (x as f64, z as f64);
+        TargetPoint::new(self.temperature.sample(x, 0.0f64, z) as f32, self.humidity.sample(x, 0.0f64, z) as f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32)
+    }
+}
+
+impl BiomeSource for ClimateNetherBiomeSource {
+    fn get_noise_biome(&mut self, x: i32, y: i32, z: i32) -> Biome {
+        let target = self.sample(x, y, z);
+        self.parameters.find_value(&target)
+    }
+}
+
+#[cfg(feature = "std")]
+impl OverworldBiomeSource {
+    pub fn new(world_seed: i64, amplified: bool) -> Self {
+        let factory = WorldgenRandom::new(world_seed, false).fork_positional();
+        let noise = |name: &str, first_octave: i32, amplitudes: Vec<f64>| {
+            Arc::new(DoublePerlinNoise::instantiate(&factory, name, &NoiseParameters::new(first_octave, amplitudes)))
+        };
+        let shift = noise("minecraft:offset", -3, vec![1.0f64, 1.0f64, 1.0f64, 0.0f64]);
+        let shift_x = DensityFunction::flat_cache(DensityFunction::cache_2d(DensityFunction::ShiftA(shift.clone())));
+        let shift_z = DensityFunction::flat_cache(DensityFunction::cache_2d(DensityFunction::ShiftB(shift)));
+        let temperature = noise("minecraft:temperature", -10, vec![1.5f64, 0.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64]);
+        let vegetation = noise("minecraft:vegetation", -8, vec![1.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64]);
+        OverworldBiomeSource {
+            temperature: DensityFunction::shifted_noise_2d(shift_x.clone(), shift_z.clone(), 0.25f64, temperature),
+            humidity: DensityFunction::shifted_noise_2d(shift_x, shift_z, 0.25f64, vegetation),
+            climate: ClimateSampler::overworld(&factory),
+            shaper: TerrainShaper::overworld(amplified),
+            parameters: OverworldBiomeBuilder::build(),
+        }
+    }
+
+    pub fn get_climate(&self) -> &ClimateSampler {
+        &self.climate
+    }
+
+    pub fn get_shaper(&self) -> &TerrainShaper {
+        &self.shaper
+    }
+
+    pub fn get_parameters(&self) -> &ParameterList<Biome> {
+        &self.parameters
+    }
+
+    /// The climate at noise (quart) coordinates, `Climate.Sampler.sample`. The depth falls from 1.5 at
+    /// y -64 to -1.5 at y 320 and is raised by the terrain offset.
+    pub fn sample(&self, x: i32, y: i32, z: i32) -> TargetPoint {
+        let (x, y, z) = (x << 2, y << 2, z << 2);
+        let point = self.climate.get_point(x, y, z);
+        let offset: f64 = (self.shaper.offset(&point) as f64).clamp(-0.81f64, 2.5f64);
+        let depth: f64 = DensityFunction::y_clamped_gradient(-64, 320, 1.5f64, -1.5f64).compute(x, y, z) + offset;
+        TargetPoint::new(
+            self.temperature.compute(x, y, z) as f32,
+            self.humidity.compute(x, y, z) as f32,
+            point.continents,
+            point.erosion,
+            depth as f32,
+            point.weirdness,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl BiomeSource for OverworldBiomeSource {
+    fn get_noise_biome(&mut self, x: i32, y: i32, z: i32) -> Biome {
+        let target = self.sample(x, y, z);
+        self.parameters.find_value(&target)
+    }
+}
+
+impl EndBiomeSource {
+    pub fn new(world_seed: i64) -> Self {
+        EndBiomeSource { islands: EndIslands::new(world_seed as u64), last_chunk: None }
+    }
+
+    pub fn get_islands(&mut self) -> &mut EndIslands {
+        &mut self.islands
+    }
+}
+
+impl BiomeSource for EndBiomeSource {
+    fn get_noise_biome(&mut self, x: i32, _y: i32, z: i32) -> Biome {
+        let chunk_x: i32 = x >> 2;
+        let chunk_z: i32 = z >> 2;
+        if let Some((last_x, last_z, biome)) = self.last_chunk {
+            if last_x == chunk_x && last_z == chunk_z {
+                return biome;
+            }
+        }
+        let biome = if (chunk_x as i64) * (chunk_x as i64) + (chunk_z as i64) * (chunk_z as i64) <= 4096i64 {
+            Biome::TheEnd
+        } else {
+            let height: f32 = self.islands.get_height_value(chunk_x * 2 + 1, chunk_z * 2 + 1);
+            if height > 40.0f32 {
+                Biome::EndHighlands
+            } else if height >= 0.0f32 {
+                Biome::EndMidlands
+            } else if height < -20.0f32 {
+                Biome::SmallEndIslands
+            } else {
+                Biome::EndBarrens
+            }
+        };
+        self.last_chunk = Some((chunk_x, chunk_z, biome));
+        biome
+    }
+}
@@ -0,0 +1,87 @@
+use crate::math::{clamped_lerp, wrap};
+use crate::perlin_noise::PerlinNoise;
+use crate::random_source::RandomSource;
+
+/// The 1.18+ `old_blended_noise`: the 1.16 limit and main noise blend sampled at block coordinates.
+#[derive(Clone, Debug)]
+pub struct BlendedNoise {
+    min_limit_noise: PerlinNoise,
+    max_limit_noise: PerlinNoise,
+    main_noise: PerlinNoise,
+    xz_multiplier: f64,
+    y_multiplier: f64,
+    xz_factor: f64,
+    y_factor: f64,
+    smear_scale_multiplier: f64,
+}
+
+impl BlendedNoise {
+    pub fn new<R: RandomSource>(random: &mut R, xz_scale: f64, y_scale: f64, xz_factor: f64, y_factor: f64, smear_scale_multiplier: f64) -> Self {
+        let min_limit_noise = PerlinNoise::create_legacy(random, -15, vec![1.0f64; 16]);
+        let max_limit_noise = PerlinNoise::create_legacy(random, -15, vec![1.0f64; 16]);
+        let main_noise = PerlinNoise::create_legacy(random, -7, vec![1.0f64; 8]);
+        BlendedNoise {
+            min_limit_noise,
+            max_limit_noise,
+            main_noise,
+            xz_multiplier: 684.412f64 * xz_scale,
+            y_multiplier: 684.412f64 * y_scale,
+            xz_factor,
+            y_factor,
+            smear_scale_multiplier,
+        }
+    }
+
+    /// The vanilla overworld parameters.
+    pub fn overworld<R: RandomSource>(random: &mut R) -> Self {
+        Self::new(random, 0.25f64, 0.125f64, 80.0f64, 160.0f64, 8.0f64)
+    }
+
+    pub fn sample(&self, x: i32, y: i32, z: i32) -> f64 {
+        let scaled_x: f64 = x as f64 * self.xz_multiplier;
+        let scaled_y: f64 = y as f64 * self.y_multiplier;
+        let scaled_z: f64 = z as f64 * self.xz_multiplier;
+        let main_x: f64 = scaled_x / self.xz_factor;
+        let main_y: f64 = scaled_y / self.y_factor;
+        let main_z: f64 = scaled_z / self.xz_factor;
+        let smear: f64 = self.y_multiplier * self.smear_scale_multiplier;
+        let main_smear: f64 = smear / self.y_factor;
+        let mut min_limit: f64 = 0.0f64;
+        let mut max_limit: f64 = 0.0f64;
+        let mut main: f64 = 0.0f64;
+        let mut octave_factor: f64 = 1.0f64;
+        for i in 0..8usize {
+            if let Some(noise) = self.main_noise.get_octave(i) {
+                main += noise.get_noise_value(
+                    wrap(main_x * octave_factor),
+                    wrap(main_y * octave_factor),
+                    wrap(main_z * octave_factor),
+                    main_smear * octave_factor,
+                    main_y * octave_factor) / octave_factor;
+            }
+            octave_factor /= 2.0f64;
+        }
+        let blend: f64 = (main / 10.0f64 + 1.0f64) / 2.0f64;
+        let only_max: bool = blend >= 1.0f64;
+        let only_min: bool = blend <= 0.0f64;
+        octave_factor = 1.0f64;
+        for i in 0..16usize {
+            let sample_x: f64 = wrap(scaled_x * octave_factor);
+            let sample_y: f64 = wrap(scaled_y * octave_factor);
+            let sample_z: f64 = wrap(scaled_z * octave_factor);
+            let y_amplification: f64 = smear * octave_factor;
+            if !only_max {
+                if let Some(noise) = self.min_limit_noise.get_octave(i) {
+                    min_limit += noise.get_noise_value(sample_x, sample_y, sample_z, y_amplification, scaled_y * octave_factor) / octave_factor;
+                }
+            }
+            if !only_min {
+                if let Some(noise) = self.max_limit_noise.get_octave(i) {
+                    max_limit += noise.get_noise_value(sample_x, sample_y, sample_z, y_amplification, scaled_y * octave_factor) / octave_factor;
+                }
+            }
+            octave_factor /= 2.0f64;
+        }
+        clamped_lerp(min_limit / 512.0f64, max_limit / 512.0f64, blend) / 128.0f64
+    }
+}
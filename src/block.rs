@@ -0,0 +1,143 @@
+/// The blocks terrain generation places before features, the surface materials and fluids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Block {
+    Air,
+    Stone,
+    Water,
+    Lava,
+    Bedrock,
+    GrassBlock,
+    Dirt,
+    CoarseDirt,
+    Podzol,
+    Mycelium,
+    Sand,
+    RedSand,
+    Sandstone,
+    RedSandstone,
+    Gravel,
+    Ice,
+    PackedIce,
+    SnowBlock,
+    Terracotta,
+    WhiteTerracotta,
+    OrangeTerracotta,
+    YellowTerracotta,
+    BrownTerracotta,
+    RedTerracotta,
+    LightGrayTerracotta,
+    Netherrack,
+    EndStone,
+    Deepslate,
+    Calcite,
+    PowderSnow,
+    SoulSand,
+    SoulSoil,
+    Basalt,
+    Blackstone,
+    WarpedNylium,
+    CrimsonNylium,
+    WarpedWartBlock,
+    NetherWartBlock,
+}
+
+const ALL_BLOCKS: [Block; 38] = [
+    Block::Air,
+    Block::Stone,
+    Block::Water,
+    Block::Lava,
+    Block::Bedrock,
+    Block::GrassBlock,
+    Block::Dirt,
+    Block::CoarseDirt,
+    Block::Podzol,
+    Block::Mycelium,
+    Block::Sand,
+    Block::RedSand,
+    Block::Sandstone,
+    Block::RedSandstone,
+    Block::Gravel,
+    Block::Ice,
+    Block::PackedIce,
+    Block::SnowBlock,
+    Block::Terracotta,
+    Block::WhiteTerracotta,
+    Block::OrangeTerracotta,
+    Block::YellowTerracotta,
+    Block::BrownTerracotta,
+    Block::RedTerracotta,
+    Block::LightGrayTerracotta,
+    Block::Netherrack,
+    Block::EndStone,
+    Block::Deepslate,
+    Block::Calcite,
+    Block::PowderSnow,
+    Block::SoulSand,
+    Block::SoulSoil,
+    Block::Basalt,
+    Block::Blackstone,
+    Block::WarpedNylium,
+    Block::CrimsonNylium,
+    Block::WarpedWartBlock,
+    Block::NetherWartBlock,
+];
+
+impl Block {
+    /// The path of the block resource location, `grass_block` for `minecraft:grass_block`.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Block::Air => "air",
+            Block::Stone => "stone",
+            Block::Water => "water",
+            Block::Lava => "lava",
+            Block::Bedrock => "bedrock",
+            Block::GrassBlock => "grass_block",
+            Block::Dirt => "dirt",
+            Block::CoarseDirt => "coarse_dirt",
+            Block::Podzol => "podzol",
+            Block::Mycelium => "mycelium",
+            Block::Sand => "sand",
+            Block::RedSand => "red_sand",
+            Block::Sandstone => "sandstone",
+            Block::RedSandstone => "red_sandstone",
+            Block::Gravel => "gravel",
+            Block::Ice => "ice",
+            Block::PackedIce => "packed_ice",
+            Block::SnowBlock => "snow_block",
+            Block::Terracotta => "terracotta",
+            Block::WhiteTerracotta => "white_terracotta",
+            Block::OrangeTerracotta => "orange_terracotta",
+            Block::YellowTerracotta => "yellow_terracotta",
+            Block::BrownTerracotta => "brown_terracotta",
+            Block::RedTerracotta => "red_terracotta",
+            Block::LightGrayTerracotta => "light_gray_terracotta",
+            Block::Netherrack => "netherrack",
+            Block::EndStone => "end_stone",
+            Block::Deepslate => "deepslate",
+            Block::Calcite => "calcite",
+            Block::PowderSnow => "powder_snow",
+            Block::SoulSand => "soul_sand",
+            Block::SoulSoil => "soul_soil",
+            Block::Basalt => "basalt",
+            Block::Blackstone => "blackstone",
+            Block::WarpedNylium => "warped_nylium",
+            Block::CrimsonNylium => "crimson_nylium",
+            Block::WarpedWartBlock => "warped_wart_block",
+            Block::NetherWartBlock => "nether_wart_block",
+        }
+    }
+
+    /// The block of a resource location, with or without the `minecraft` namespace.
+    pub fn from_name(name: &str) -> Option<Block> {
+        let path = name.strip_prefix("minecraft:").unwrap_or(name);
+        ALL_BLOCKS.iter().copied().find(|block| block.get_name() == path)
+    }
+
+    pub fn is_air(&self) -> bool {
+        *self == Block::Air
+    }
+
+    pub fn is_fluid(&self) -> bool {
+        *self == Block::Water || *self == Block::Lava
+    }
+}
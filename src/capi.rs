@@ -0,0 +1,333 @@
+//! C bindings for the `cdylib` and `staticlib` targets, built with the `capi` feature (on by
+//! default), the header is `include/noise_rs.h`.
+//!
+//! Every noise is an opaque handle created by a `*_new`/`*_create` function and released with the
+//! matching `*_free`. Batch functions read `count` coordinates from each input array and write
+//! `count` results to the output arrays, they do nothing if a pointer is null.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::slice;
+
+use java_random::Random;
+
+use crate::double_perlin_noise::{DoublePerlinNoise, NoiseParameters};
+use crate::perlin_noise::{check_octaves, PerlinNoise};
+use crate::random_source::{RandomSource, WorldgenRandom};
+use crate::simplex_noise::SimplexNoise;
+use crate::voronoi::{Voronoi, VoronoiMode};
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod capi_test {
+    use super::*;
+    use crate::create_range;
+    use std::ptr;
+
+    #[test]
+    fn test_perlin_handles() {
+        unsafe {
+            let octaves = create_range(-7, 0);
+            let perlin = noise_rs_perlin_noise_new(1, octaves.as_ptr(), octaves.len());
+            let expected = PerlinNoise::new(&mut Random::with_seed(1), octaves);
+            assert_eq!(noise_rs_perlin_noise_sample(perlin, 1.5, 2.0, -3.25), expected.sample_default(1.5, 2.0, -3.25));
+            let xs = [0.0f64, 10.5, -3.0];
+            let ys = [0.0f64, 64.0, 1.0];
+            let zs = [5.0f64, -2.0, 7.5];
+            let mut out = [0.0f64; 3];
+            noise_rs_perlin_noise_sample_batch(perlin, xs.as_ptr(), ys.as_ptr(), zs.as_ptr(), 3, out.as_mut_ptr());
+            for i in 0..3 {
+                assert_eq!(out[i], expected.sample_default(xs[i], ys[i], zs[i]));
+            }
+            noise_rs_perlin_noise_free(perlin);
+            assert!(noise_rs_perlin_noise_sample(ptr::null(), 0.0, 0.0, 0.0).is_nan());
+
+            let unsorted = [0, -7];
+            assert!(noise_rs_perlin_noise_new(1, unsorted.as_ptr(), 2).is_null());
+            assert!(noise_rs_perlin_noise_new(1, unsorted.as_ptr(), 0).is_null());
+            let duplicated = [-3, -3];
+            assert!(noise_rs_double_perlin_noise_new(1, duplicated.as_ptr(), 2).is_null());
+        }
+    }
+
+    #[test]
+    fn test_instantiate_and_voronoi() {
+        unsafe {
+            let amplitudes = [1.0f64, 1.0f64];
+            let noise = noise_rs_double_perlin_noise_instantiate(42, false, "minecraft:temperature\0".as_ptr() as *const c_char, -10, amplitudes.as_ptr(), 2);
+            let factory = WorldgenRandom::new(42, false).fork_positional();
+            let expected = DoublePerlinNoise::instantiate(&factory, "minecraft:temperature", &NoiseParameters::new(-10, amplitudes.to_vec()));
+            assert_eq!(noise_rs_double_perlin_noise_sample(noise, 100.0, 0.0, -7.0), expected.sample(100.0, 0.0, -7.0));
+            noise_rs_double_perlin_noise_free(noise);
+
+            let voronoi = noise_rs_voronoi_new(1551515151585454, false);
+            let (mut x, mut y, mut z) = (0, 0, 0);
+            noise_rs_voronoi_get_fuzzy_positions(voronoi, 7, 3, -13, &mut x, &mut y, &mut z);
+            assert_eq!((x, y, z), Voronoi::new(1551515151585454).get_fuzzy_positions(7, 3, -13));
+            noise_rs_voronoi_free(voronoi);
+        }
+    }
+}
+
+unsafe fn as_slice<'a, T>(pointer: *const T, count: usize) -> &'a [T] {
+    slice::from_raw_parts(pointer, count)
+}
+
+unsafe fn as_mut_slice<'a, T>(pointer: *mut T, count: usize) -> &'a mut [T] {
+    slice::from_raw_parts_mut(pointer, count)
+}
+
+fn into_handle<T>(value: T) -> *mut T {
+    Box::into_raw(Box::new(value))
+}
+
+unsafe fn free_handle<T>(handle: *mut T) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// A 1.16.1 `PerlinNoise` from a java random seed and the octave list, for example -7 to 0.
+///
+/// # Safety
+/// `octaves` must point to `octave_count` integers. Returns null if the list is empty, not increasing
+/// or has a duplicate.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_perlin_noise_new(seed: i64, octaves: *const i32, octave_count: usize) -> *mut PerlinNoise {
+    if octaves.is_null() || check_octaves(as_slice(octaves, octave_count)).is_err() {
+        return std::ptr::null_mut();
+    }
+    into_handle(PerlinNoise::new(&mut Random::with_seed(seed as u64), as_slice(octaves, octave_count).to_vec()))
+}
+
+/// A 1.16.2+ `PerlinNoise` from a java random seed, the first octave and the octave amplitudes.
+///
+/// # Safety
+/// `amplitudes` must point to `amplitude_count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_perlin_noise_create_legacy(seed: i64, first_octave: i32, amplitudes: *const f64, amplitude_count: usize) -> *mut PerlinNoise {
+    if amplitudes.is_null() || amplitude_count == 0 {
+        return std::ptr::null_mut();
+    }
+    into_handle(PerlinNoise::create_legacy(&mut Random::with_seed(seed as u64), first_octave, as_slice(amplitudes, amplitude_count).to_vec()))
+}
+
+/// # Safety
+/// `noise` must be null or a handle of `noise_rs_perlin_noise_*`. Returns NaN for a null handle.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_perlin_noise_sample(noise: *const PerlinNoise, x: f64, y: f64, z: f64) -> f64 {
+    match noise.as_ref() {
+        Some(noise) => noise.sample_default(x, y, z),
+        None => f64::NAN,
+    }
+}
+
+/// # Safety
+/// `noise` must be a perlin noise handle, `xs`, `ys`, `zs` and `out` must point to `count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_perlin_noise_sample_batch(noise: *const PerlinNoise, xs: *const f64, ys: *const f64, zs: *const f64, count: usize, out: *mut f64) {
+    if noise.is_null() || xs.is_null() || ys.is_null() || zs.is_null() || out.is_null() {
+        return;
+    }
+    let noise = &*noise;
+    let (xs, ys, zs) = (as_slice(xs, count), as_slice(ys, count), as_slice(zs, count));
+    for (i, value) in as_mut_slice(out, count).iter_mut().enumerate() {
+        *value = noise.sample_default(xs[i], ys[i], zs[i]);
+    }
+}
+
+/// # Safety
+/// `noise` must be null or a perlin noise handle not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_perlin_noise_free(noise: *mut PerlinNoise) {
+    free_handle(noise)
+}
+
+/// A 1.16.1 `DoublePerlinNoise` from a java random seed and the octave list.
+///
+/// # Safety
+/// `octaves` must point to `octave_count` integers. Returns null if the list is empty, not increasing
+/// or has a duplicate.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_double_perlin_noise_new(seed: i64, octaves: *const i32, octave_count: usize) -> *mut DoublePerlinNoise {
+    if octaves.is_null() || check_octaves(as_slice(octaves, octave_count)).is_err() {
+        return std::ptr::null_mut();
+    }
+    into_handle(DoublePerlinNoise::new(&mut Random::with_seed(seed as u64), as_slice(octaves, octave_count).to_vec()))
+}
+
+/// A 1.16.2+ `DoublePerlinNoise` from a java random seed, the first octave and the octave amplitudes.
+///
+/// # Safety
+/// `amplitudes` must point to `amplitude_count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_double_perlin_noise_create_legacy(seed: i64, first_octave: i32, amplitudes: *const f64, amplitude_count: usize) -> *mut DoublePerlinNoise {
+    if amplitudes.is_null() || amplitude_count == 0 {
+        return std::ptr::null_mut();
+    }
+    into_handle(DoublePerlinNoise::create_legacy(&mut Random::with_seed(seed as u64), first_octave, as_slice(amplitudes, amplitude_count).to_vec()))
+}
+
+/// The 1.18+ noise registered as `name` in a world of `world_seed`, `legacy_random_source` being
+/// the flag of the noise settings.
+///
+/// # Safety
+/// `name` must be a nul terminated UTF-8 string and `amplitudes` must point to `amplitude_count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_double_perlin_noise_instantiate(world_seed: i64, legacy_random_source: bool, name: *const c_char, first_octave: i32, amplitudes: *const f64, amplitude_count: usize) -> *mut DoublePerlinNoise {
+    if name.is_null() || amplitudes.is_null() || amplitude_count == 0 {
+        return std::ptr::null_mut();
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(name) => name,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let factory = WorldgenRandom::new(world_seed, legacy_random_source).fork_positional();
+    let parameters = NoiseParameters::new(first_octave, as_slice(amplitudes, amplitude_count).to_vec());
+    into_handle(DoublePerlinNoise::instantiate(&factory, name, &parameters))
+}
+
+/// # Safety
+/// `noise` must be null or a handle of `noise_rs_double_perlin_noise_*`. Returns NaN for a null handle.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_double_perlin_noise_sample(noise: *const DoublePerlinNoise, x: f64, y: f64, z: f64) -> f64 {
+    match noise.as_ref() {
+        Some(noise) => noise.sample(x, y, z),
+        None => f64::NAN,
+    }
+}
+
+/// # Safety
+/// `noise` must be a double perlin noise handle, `xs`, `ys`, `zs` and `out` must point to `count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_double_perlin_noise_sample_batch(noise: *const DoublePerlinNoise, xs: *const f64, ys: *const f64, zs: *const f64, count: usize, out: *mut f64) {
+    if noise.is_null() || xs.is_null() || ys.is_null() || zs.is_null() || out.is_null() {
+        return;
+    }
+    let noise = &*noise;
+    let (xs, ys, zs) = (as_slice(xs, count), as_slice(ys, count), as_slice(zs, count));
+    for (i, value) in as_mut_slice(out, count).iter_mut().enumerate() {
+        *value = noise.sample(xs[i], ys[i], zs[i]);
+    }
+}
+
+/// # Safety
+/// `noise` must be null or a double perlin noise handle not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_double_perlin_noise_free(noise: *mut DoublePerlinNoise) {
+    free_handle(noise)
+}
+
+/// A `SimplexNoise` seeded by the java random of `seed`.
+#[no_mangle]
+pub extern "C" fn noise_rs_simplex_noise_new(seed: i64) -> *mut SimplexNoise {
+    into_handle(SimplexNoise::init(Random::with_seed(seed as u64)))
+}
+
+/// # Safety
+/// `noise` must be null or a simplex noise handle, not used from another thread at the same time
+/// since sampling fills its cache. Returns NaN for a null handle.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_simplex_noise_sample_2d(noise: *mut SimplexNoise, x: f64, z: f64) -> f64 {
+    match noise.as_mut() {
+        Some(noise) => noise.get_value_2d(x, z),
+        None => f64::NAN,
+    }
+}
+
+/// # Safety
+/// Same as `noise_rs_simplex_noise_sample_2d`.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_simplex_noise_sample_3d(noise: *mut SimplexNoise, x: f64, y: f64, z: f64) -> f64 {
+    match noise.as_mut() {
+        Some(noise) => noise.get_value_3d(x, y, z),
+        None => f64::NAN,
+    }
+}
+
+/// # Safety
+/// `noise` must be a simplex noise handle, `xs`, `zs` and `out` must point to `count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_simplex_noise_sample_2d_batch(noise: *mut SimplexNoise, xs: *const f64, zs: *const f64, count: usize, out: *mut f64) {
+    if noise.is_null() || xs.is_null() || zs.is_null() || out.is_null() {
+        return;
+    }
+    let noise = &mut *noise;
+    let (xs, zs) = (as_slice(xs, count), as_slice(zs, count));
+    for (i, value) in as_mut_slice(out, count).iter_mut().enumerate() {
+        *value = noise.get_value_2d(xs[i], zs[i]);
+    }
+}
+
+/// # Safety
+/// `noise` must be a simplex noise handle, `xs`, `ys`, `zs` and `out` must point to `count` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_simplex_noise_sample_3d_batch(noise: *mut SimplexNoise, xs: *const f64, ys: *const f64, zs: *const f64, count: usize, out: *mut f64) {
+    if noise.is_null() || xs.is_null() || ys.is_null() || zs.is_null() || out.is_null() {
+        return;
+    }
+    let noise = &mut *noise;
+    let (xs, ys, zs) = (as_slice(xs, count), as_slice(ys, count), as_slice(zs, count));
+    for (i, value) in as_mut_slice(out, count).iter_mut().enumerate() {
+        *value = noise.get_value_3d(xs[i], ys[i], zs[i]);
+    }
+}
+
+/// # Safety
+/// `noise` must be null or a simplex noise handle not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_simplex_noise_free(noise: *mut SimplexNoise) {
+    free_handle(noise)
+}
+
+/// The biome zoom of a world, `column` selecting the y independent 1.15-1.17 overworld zoom.
+#[no_mangle]
+pub extern "C" fn noise_rs_voronoi_new(world_seed: i64, column: bool) -> *mut Voronoi {
+    let mode = if column { VoronoiMode::Column } else { VoronoiMode::Fuzzy };
+    into_handle(Voronoi::with_mode(world_seed, mode))
+}
+
+/// Writes the biome (quart) position the block at `x`, `y`, `z` takes its biome from.
+///
+/// # Safety
+/// `voronoi` must be null or a voronoi handle, the outputs must be valid or null.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_voronoi_get_fuzzy_positions(voronoi: *mut Voronoi, x: i32, y: i32, z: i32, out_x: *mut i32, out_y: *mut i32, out_z: *mut i32) {
+    if let Some(voronoi) = voronoi.as_mut() {
+        let (fuzzy_x, fuzzy_y, fuzzy_z) = voronoi.get_fuzzy_positions(x, y, z);
+        if let Some(out_x) = out_x.as_mut() {
+            *out_x = fuzzy_x;
+        }
+        if let Some(out_y) = out_y.as_mut() {
+            *out_y = fuzzy_y;
+        }
+        if let Some(out_z) = out_z.as_mut() {
+            *out_z = fuzzy_z;
+        }
+    }
+}
+
+/// # Safety
+/// `voronoi` must be a voronoi handle, the six arrays must hold `count` integers.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_voronoi_get_fuzzy_positions_batch(voronoi: *mut Voronoi, xs: *const i32, ys: *const i32, zs: *const i32, count: usize, out_xs: *mut i32, out_ys: *mut i32, out_zs: *mut i32) {
+    if voronoi.is_null() || xs.is_null() || ys.is_null() || zs.is_null() || out_xs.is_null() || out_ys.is_null() || out_zs.is_null() {
+        return;
+    }
+    let voronoi = &mut *voronoi;
+    let (xs, ys, zs) = (as_slice(xs, count), as_slice(ys, count), as_slice(zs, count));
+    let (out_xs, out_ys, out_zs) = (as_mut_slice(out_xs, count), as_mut_slice(out_ys, count), as_mut_slice(out_zs, count));
+    for i in 0..count {
+        let (x, y, z) = voronoi.get_fuzzy_positions(xs[i], ys[i], zs[i]);
+        out_xs[i] = x;
+        out_ys[i] = y;
+        out_zs[i] = z;
+    }
+}
+
+/// # Safety
+/// `voronoi` must be null or a voronoi handle not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn noise_rs_voronoi_free(voronoi: *mut Voronoi) {
+    free_handle(voronoi)
+}
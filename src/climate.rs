@@ -0,0 +1,358 @@
+use alloc::vec::Vec;
+
+/// The fixed point representation 1.18 climate values are compared in.
+pub fn quantize_coord(value: f32) -> i64 {
+    (value * 10000.0f32) as i64
+}
+
+/// The number of climate values compared, the offset of a parameter point is the seventh one.
+const PARAMETER_COUNT: usize = 7;
+
+/// A quantized interval of a climate value, `Climate.Parameter`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parameter {
+    min: i64,
+    max: i64,
+}
+
+/// The quantized climate of a position, `Climate.TargetPoint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TargetPoint {
+    pub temperature: i64,
+    pub humidity: i64,
+    pub continentalness: i64,
+    pub erosion: i64,
+    pub depth: i64,
+    pub weirdness: i64,
+}
+
+/// The climate intervals a value is placed in, `Climate.ParameterPoint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParameterPoint {
+    pub temperature: Parameter,
+    pub humidity: Parameter,
+    pub continentalness: Parameter,
+    pub erosion: Parameter,
+    pub depth: Parameter,
+    pub weirdness: Parameter,
+    pub offset: i64,
+}
+
+/// The values of a `MultiNoiseBiomeSource` with the climate intervals they are placed in, searched
+/// through the vanilla R-tree.
+#[derive(Clone, Debug)]
+pub struct ParameterList<T> {
+    values: Vec<(ParameterPoint, T)>,
+    index: Node,
+    /// The leaf of the previous search, vanilla keeps it in a thread local to warm start the next one.
+    last_result: Option<usize>,
+}
+
+/// A node of `Climate.RTree`, leaves hold the index of their value in the parameter list.
+#[derive(Clone, Debug)]
+enum Node {
+    Leaf { space: [Parameter; PARAMETER_COUNT], value: usize },
+    SubTree { space: [Parameter; PARAMETER_COUNT], children: Vec<Node> },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Leaf {
+    space: [Parameter; PARAMETER_COUNT],
+    value: usize,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod climate_test {
+    use super::*;
+    use crate::overworld_biome_builder::OverworldBiomeBuilder;
+    use alloc::vec;
+    use java_random::Random;
+
+    #[test]
+    fn test_parameter() {
+        let parameter = Parameter::span(-0.45f32, -0.15f32);
+        assert_eq!((parameter.get_min(), parameter.get_max()), (-4500, -1500));
+        assert_eq!(parameter.distance(-2000), 0);
+        assert_eq!(parameter.distance(-1000), 500);
+        assert_eq!(parameter.distance(-5000), 500);
+        assert_eq!(Parameter::span_of(Parameter::point(-1.0f32), parameter), Parameter::span(-1.0f32, -0.15f32));
+        assert_eq!(quantize_coord(0.26666668f32), 2666);
+        assert_eq!(quantize_coord(-0.26666668f32), -2666);
+    }
+
+    #[test]
+    fn test_find_value() {
+        let zero = Parameter::point(0.0f32);
+        let point = |temperature: f32, offset: f32| ParameterPoint::new(Parameter::point(temperature), zero, zero, zero, zero, zero, offset);
+        let mut list = ParameterList::new(vec![(point(0.0f32, 0.0f32), 0), (point(0.5f32, 0.0f32), 1), (point(0.5f32, 0.1f32), 2), (point(-0.5f32, 0.0f32), 3)]);
+        let target = |temperature: f32| TargetPoint::new(temperature, 0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32);
+        assert_eq!(list.find_value(&target(0.1f32)), 0);
+        assert_eq!(list.find_value(&target(0.4f32)), 1);
+        assert_eq!(list.find_value(&target(2.0f32)), 1);
+        assert_eq!(point(0.5f32, 0.1f32).fitness(&target(0.5f32)), 1000000);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        // -0.25 is as close to 0.0 as to -0.5, like vanilla the tie goes to the previous result when it is
+        // one of the fittest values and to the first one searched otherwise
+        let zero = Parameter::point(0.0f32);
+        let point = |temperature: f32| ParameterPoint::new(Parameter::point(temperature), zero, zero, zero, zero, zero, 0.0f32);
+        let mut list = ParameterList::new(vec![(point(0.0f32), 0), (point(-0.5f32), 1), (point(0.5f32), 2)]);
+        let target = |temperature: f32| TargetPoint::new(temperature, 0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32);
+        assert_eq!(list.find_value(&target(-0.25f32)), 0);
+        assert_eq!(list.find_value(&target(-0.5f32)), 1);
+        assert_eq!(list.find_value(&target(-0.25f32)), 1);
+        assert_eq!(list.find_value(&target(0.5f32)), 2);
+        assert_eq!(list.find_value(&target(-0.25f32)), 0);
+        assert_eq!(list.find_value_brute_force(&target(-0.25f32)), 0);
+    }
+
+    #[test]
+    fn test_search_is_fittest() {
+        let mut list = OverworldBiomeBuilder::build();
+        let mut random = Random::with_seed(1);
+        let mut next = || random.next_float() * 2.4f32 - 1.2f32;
+        for _ in 0..10000 {
+            let target = TargetPoint::new(next(), next(), next(), next(), next(), next());
+            let (searched, _) = list.search(&target);
+            list.last_result = Some(searched);
+            let fittest = list.find_index_brute_force(&target);
+            assert_eq!(list.values[searched].0.fitness(&target), list.values[fittest].0.fitness(&target));
+        }
+    }
+}
+
+impl Parameter {
+    pub fn point(value: f32) -> Self {
+        Self::span(value, value)
+    }
+
+    pub fn span(min: f32, max: f32) -> Self {
+        assert!(min <= max, "min {} is larger than max {}", min, max);
+        Parameter { min: quantize_coord(min), max: quantize_coord(max) }
+    }
+
+    /// The interval from the start of `min` to the end of `max`.
+    pub fn span_of(min: Parameter, max: Parameter) -> Self {
+        assert!(min.min <= max.max, "min {} is larger than max {}", min.min, max.max);
+        Parameter { min: min.min, max: max.max }
+    }
+
+    pub fn get_min(&self) -> i64 {
+        self.min
+    }
+
+    pub fn get_max(&self) -> i64 {
+        self.max
+    }
+
+    /// How far a quantized value is outside of the interval, 0 inside.
+    pub fn distance(&self, value: i64) -> i64 {
+        let above: i64 = value - self.max;
+        let below: i64 = self.min - value;
+        if above > 0 { above } else { below.max(0) }
+    }
+
+    fn union(&self, other: &Parameter) -> Self {
+        Parameter { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// The truncated center the R-tree sorts on.
+    fn center(&self) -> i64 {
+        (self.min + self.max) / 2
+    }
+}
+
+impl TargetPoint {
+    pub fn new(temperature: f32, humidity: f32, continentalness: f32, erosion: f32, depth: f32, weirdness: f32) -> Self {
+        TargetPoint {
+            temperature: quantize_coord(temperature),
+            humidity: quantize_coord(humidity),
+            continentalness: quantize_coord(continentalness),
+            erosion: quantize_coord(erosion),
+            depth: quantize_coord(depth),
+            weirdness: quantize_coord(weirdness),
+        }
+    }
+
+    /// The values compared against the parameter space, with 0 for the offset.
+    pub fn to_parameter_array(&self) -> [i64; PARAMETER_COUNT] {
+        [self.temperature, self.humidity, self.continentalness, self.erosion, self.depth, self.weirdness, 0]
+    }
+}
+
+impl ParameterPoint {
+    pub fn new(temperature: Parameter, humidity: Parameter, continentalness: Parameter, erosion: Parameter, depth: Parameter, weirdness: Parameter, offset: f32) -> Self {
+        ParameterPoint { temperature, humidity, continentalness, erosion, depth, weirdness, offset: quantize_coord(offset) }
+    }
+
+    /// The squared distance of the target to the intervals, the offset counting as a distance on its own.
+    pub fn fitness(&self, target: &TargetPoint) -> i64 {
+        let square = |distance: i64| distance * distance;
+        square(self.temperature.distance(target.temperature))
+            + square(self.humidity.distance(target.humidity))
+            + square(self.continentalness.distance(target.continentalness))
+            + square(self.erosion.distance(target.erosion))
+            + square(self.depth.distance(target.depth))
+            + square(self.weirdness.distance(target.weirdness))
+            + square(self.offset)
+    }
+
+    /// The intervals the R-tree compares, the offset being a point of its own.
+    pub fn parameter_space(&self) -> [Parameter; PARAMETER_COUNT] {
+        let offset = Parameter { min: self.offset, max: self.offset };
+        [self.temperature, self.humidity, self.continentalness, self.erosion, self.depth, self.weirdness, offset]
+    }
+}
+
+impl<T: Copy> ParameterList<T> {
+    pub fn new(values: Vec<(ParameterPoint, T)>) -> Self {
+        assert!(!values.is_empty(), "a parameter list needs at least one value");
+        let leaves: Vec<Leaf> = values.iter().enumerate().map(|(value, (point, _))| Leaf { space: point.parameter_space(), value }).collect();
+        let index = Node::build(leaves);
+        ParameterList { values, index, last_result: None }
+    }
+
+    pub fn get_values(&self) -> &[(ParameterPoint, T)] {
+        &self.values
+    }
+
+    /// The value of the fittest parameter point. Like vanilla the search starts from the previous
+    /// result, so which of several equally fit values is returned depends on the earlier queries.
+    pub fn find_value(&mut self, target: &TargetPoint) -> T {
+        let (index, _) = self.search(target);
+        self.last_result = Some(index);
+        self.values[index].1
+    }
+
+    /// The first of the fittest values in list order, vanilla's `findValueBruteForce`.
+    pub fn find_value_brute_force(&self, target: &TargetPoint) -> T {
+        self.values[self.find_index_brute_force(target)].1
+    }
+
+    fn search(&self, target: &TargetPoint) -> (usize, i64) {
+        let target = target.to_parameter_array();
+        let candidate = self.last_result.map(|value| (value, distance(&self.values[value].0.parameter_space(), &target)));
+        self.index.search(&target, candidate).expect("the search always reaches a leaf")
+    }
+
+    fn find_index_brute_force(&self, target: &TargetPoint) -> usize {
+        let mut best: usize = 0;
+        let mut best_fitness: i64 = i64::MAX;
+        for (index, (point, _)) in self.values.iter().enumerate() {
+            let fitness: i64 = point.fitness(target);
+            if fitness < best_fitness {
+                best = index;
+                best_fitness = fitness;
+            }
+        }
+        best
+    }
+}
+
+fn distance(space: &[Parameter; PARAMETER_COUNT], target: &[i64; PARAMETER_COUNT]) -> i64 {
+    space.iter().zip(target.iter()).map(|(parameter, &value)| parameter.distance(value) * parameter.distance(value)).sum()
+}
+
+fn union(spaces: impl Iterator<Item = [Parameter; PARAMETER_COUNT]>) -> [Parameter; PARAMETER_COUNT] {
+    spaces.reduce(|a, b| {
+        let mut space = a;
+        for (parameter, other) in space.iter_mut().zip(b.iter()) {
+            *parameter = parameter.union(other);
+        }
+        space
+    })
+    .expect("a subtree needs at least one child")
+}
+
+/// The centers of a space from dimension `first` on, wrapping around, which sorts like vanilla's
+/// chained comparators.
+fn sort_key(space: &[Parameter; PARAMETER_COUNT], first: usize, absolute: bool) -> [i64; PARAMETER_COUNT] {
+    let mut key = [0i64; PARAMETER_COUNT];
+    for (j, center) in key.iter_mut().enumerate() {
+        let value = space[(first + j) % PARAMETER_COUNT].center();
+        *center = if absolute { value.abs() } else { value };
+    }
+    key
+}
+
+/// Vanilla's `Math.pow(10, Math.floor(Math.log(size - 0.01) / Math.log(10)))`, the largest power
+/// of ten below the size.
+fn bucket_size(size: usize) -> usize {
+    let mut bucket_size: usize = 1;
+    while bucket_size * 10 < size {
+        bucket_size *= 10;
+    }
+    bucket_size
+}
+
+fn cost(space: &[Parameter; PARAMETER_COUNT]) -> i64 {
+    space.iter().map(|parameter| (parameter.max - parameter.min).abs()).sum()
+}
+
+impl Node {
+    fn space(&self) -> [Parameter; PARAMETER_COUNT] {
+        match self {
+            Node::Leaf { space, .. } | Node::SubTree { space, .. } => *space,
+        }
+    }
+
+    fn subtree(children: Vec<Node>) -> Self {
+        Node::SubTree { space: union(children.iter().map(Node::space)), children }
+    }
+
+    /// Vanilla's `RTree.build`: small lists become a node sorted by distance to the origin, larger
+    /// ones are split into buckets along the dimension with the smallest total extent.
+    fn build(mut leaves: Vec<Leaf>) -> Self {
+        if leaves.len() == 1 {
+            return Node::Leaf { space: leaves[0].space, value: leaves[0].value };
+        }
+        if leaves.len() <= 10 {
+            leaves.sort_by_key(|leaf| leaf.space.iter().map(|parameter| parameter.center().abs()).sum::<i64>());
+            return Self::subtree(leaves.iter().map(|leaf| Node::Leaf { space: leaf.space, value: leaf.value }).collect());
+        }
+        let size = bucket_size(leaves.len());
+        let mut best_cost: i64 = i64::MAX;
+        let mut best_dimension: usize = 0;
+        let mut best_order: Vec<Leaf> = Vec::new();
+        for dimension in 0..PARAMETER_COUNT {
+            // sorting the same list every time keeps the order of the previous dimension for ties
+            leaves.sort_by_key(|leaf| sort_key(&leaf.space, dimension, false));
+            let cost: i64 = leaves.chunks(size).map(|bucket| cost(&union(bucket.iter().map(|leaf| leaf.space)))).sum();
+            if best_cost > cost {
+                best_cost = cost;
+                best_dimension = dimension;
+                best_order = leaves.clone();
+            }
+        }
+        let mut buckets: Vec<([Parameter; PARAMETER_COUNT], &[Leaf])> =
+            best_order.chunks(size).map(|bucket| (union(bucket.iter().map(|leaf| leaf.space)), bucket)).collect();
+        buckets.sort_by_key(|(space, _)| sort_key(space, best_dimension, true));
+        Self::subtree(buckets.into_iter().map(|(_, bucket)| Self::build(bucket.to_vec())).collect())
+    }
+
+    /// Vanilla's `RTree.Node.search`, a child is only visited when it could hold a strictly fitter leaf
+    /// than the best one found so far.
+    fn search(&self, target: &[i64; PARAMETER_COUNT], candidate: Option<(usize, i64)>) -> Option<(usize, i64)> {
+        match self {
+            Node::Leaf { space, value } => Some((*value, distance(space, target))),
+            Node::SubTree { children, .. } => {
+                let mut result = candidate;
+                let mut best: i64 = candidate.map_or(i64::MAX, |(_, distance)| distance);
+                for child in children.iter() {
+                    if best > distance(&child.space(), target) {
+                        if let Some((value, distance)) = child.search(target, result) {
+                            if best > distance {
+                                best = distance;
+                                result = Some((value, distance));
+                            }
+                        }
+                    }
+                }
+                result
+            }
+        }
+    }
+}
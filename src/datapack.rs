@@ -0,0 +1,411 @@
+//! Noise and noise settings definitions loaded from datapack json, built into samplers for a seed.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use crate::double_perlin_noise::{DoublePerlinNoise, NoiseParameters};
+use crate::noise_settings::{NoiseGeneratorSettings, NoiseSamplingSettings, NoiseSettings, NoiseSlideSettings};
+use crate::perlin_noise::PerlinNoise;
+use crate::random_source::{PositionalRandomFactory, RandomSource, WorldgenRandom};
+
+#[derive(Debug)]
+pub enum DatapackError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// A file that parsed as json but is not a valid definition, with the reason.
+    Invalid(String),
+    /// A resource location that is not registered.
+    Missing(String),
+}
+
+impl fmt::Display for DatapackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatapackError::Io(error) => write!(f, "io error: {}", error),
+            DatapackError::Json(error) => write!(f, "json error: {}", error),
+            DatapackError::Invalid(reason) => write!(f, "invalid definition: {}", reason),
+            DatapackError::Missing(location) => write!(f, "unknown resource location {}", location),
+        }
+    }
+}
+
+impl std::error::Error for DatapackError {}
+
+impl From<io::Error> for DatapackError {
+    fn from(error: io::Error) -> Self {
+        DatapackError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for DatapackError {
+    fn from(error: serde_json::Error) -> Self {
+        DatapackError::Json(error)
+    }
+}
+
+/// The fields of a `worldgen/noise_settings` file the noises depend on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseSettingsDefinition {
+    pub legacy_random_source: bool,
+    pub sea_level: i32,
+    pub min_y: i32,
+    pub height: i32,
+    pub noise_size_horizontal: i32,
+    pub noise_size_vertical: i32,
+    /// The whole preset for the 1.16 and 1.17 format, which has the bedrock positions.
+    pub generator_settings: Option<NoiseGeneratorSettings>,
+    /// Noises referenced from the noise router and the surface rule, in order of appearance.
+    pub noise_references: Vec<String>,
+}
+
+#[cfg(test)]
+mod datapack_test {
+    use super::*;
+
+    const CONTINENTALNESS: &str = r#"{"firstOctave": -9, "amplitudes": [1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 1.0, 1.0, 1.0]}"#;
+
+    #[test]
+    fn test_parse_noise() {
+        let parameters = parse_noise_parameters(CONTINENTALNESS).unwrap();
+        assert_eq!(parameters, NoiseParameters::new(-9, vec![1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 1.0, 1.0, 1.0]));
+        assert!(parse_noise_parameters(r#"{"firstOctave": -9}"#).is_err());
+        assert!(parse_noise_parameters(r#"{"firstOctave": 4294967287, "amplitudes": [1.0]}"#).is_err());
+        assert_eq!(resource_location("continentalness"), "minecraft:continentalness");
+    }
+
+    #[test]
+    fn test_parse_legacy_settings() {
+        let json = r#"{"bedrock_roof_position": -10, "bedrock_floor_position": 0, "sea_level": 63, "disable_mob_generation": false,
+            "noise": {"height": 256, "sampling": {"xz_scale": 0.9999999814507745, "y_scale": 0.9999999814507745, "xz_factor": 80.0, "y_factor": 160.0},
+            "top_slide": {"target": -10, "size": 3, "offset": 0}, "bottom_slide": {"target": -30, "size": 0, "offset": 0},
+            "size_horizontal": 1, "size_vertical": 2, "density_factor": 1.0, "density_offset": -0.46875,
+            "simplex_surface_noise": true, "random_density_offset": true}}"#;
+        let settings = parse_noise_settings(json).unwrap();
+        assert_eq!(settings.generator_settings, Some(NoiseGeneratorSettings::overworld(false)));
+        assert!(settings.legacy_random_source);
+    }
+
+    #[test]
+    fn test_configure() {
+        let mut datapack = Datapack::new();
+        datapack.register_noise("minecraft:continentalness", parse_noise_parameters(CONTINENTALNESS).unwrap());
+        datapack.register_noise_settings("custom:world", parse_noise_settings(
+            r#"{"sea_level": 63, "legacy_random_source": false, "noise": {"min_y": -64, "height": 384, "size_horizontal": 1, "size_vertical": 2},
+            "noise_router": {"continents": {"type": "minecraft:shifted_noise", "noise": "minecraft:continentalness", "shift_x": {"type": "minecraft:shift_a", "argument": "minecraft:offset"}}}}"#).unwrap());
+        assert_eq!(datapack.get_noise_settings("custom:world").unwrap().noise_references, vec!["minecraft:continentalness", "minecraft:offset"]);
+        let mut noises = datapack.configure(1234, "custom:world").unwrap();
+        let noise = noises.get_double_perlin_noise("continentalness").unwrap();
+        let factory = WorldgenRandom::new(1234, false).fork_positional();
+        let expected = DoublePerlinNoise::instantiate(&factory, "minecraft:continentalness", &NoiseParameters::new(-9, vec![1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 1.0, 1.0, 1.0]));
+        assert_eq!(noise.sample(12.5, 0.0, -40.25), expected.sample(12.5, 0.0, -40.25));
+        assert!(noises.get_double_perlin_noise("minecraft:offset").is_none());
+        assert!(datapack.configure(1234, "custom:missing").is_err());
+    }
+
+    #[test]
+    fn test_legacy_offset() {
+        let mut parameters = HashMap::new();
+        parameters.insert("minecraft:offset".to_string(), NoiseParameters::new(-3, vec![1.0, 1.0, 1.0, 0.0]));
+        let mut legacy = ConfiguredNoises::new(1234, true, parameters.clone());
+        let offset = legacy.get_double_perlin_noise("offset").unwrap();
+        assert_eq!((offset.sample(12.5, 0.0, -40.25), offset.sample(-300.0, 0.0, 71.0)), (0.0, 0.0));
+        let mut noises = ConfiguredNoises::new(1234, false, parameters);
+        assert_ne!(noises.get_double_perlin_noise("offset").unwrap().sample(12.5, 0.0, -40.25), 0.0);
+    }
+
+    #[test]
+    fn test_load() {
+        let root = std::env::temp_dir().join(format!("noise_rs_datapack_{}", std::process::id()));
+        let noise = root.join("data").join("custom").join("worldgen").join("noise").join("cave");
+        fs::create_dir_all(&noise).unwrap();
+        fs::write(noise.join("entrance.json"), CONTINENTALNESS).unwrap();
+        let mut datapack = Datapack::new();
+        let count = datapack.load(&root);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(count.unwrap(), 1);
+        assert_eq!(datapack.get_noise_parameters("custom:cave/entrance").unwrap().first_octave, -9);
+    }
+}
+
+/// Prefixes a resource location without namespace with `minecraft:`.
+pub fn resource_location(location: &str) -> String {
+    if location.contains(':') {
+        location.to_string()
+    } else {
+        format!("minecraft:{}", location)
+    }
+}
+
+fn get_field<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value, DatapackError> {
+    object.get(name).ok_or_else(|| DatapackError::Invalid(format!("missing field {}", name)))
+}
+
+fn get_object<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Map<String, Value>, DatapackError> {
+    get_field(object, name)?.as_object().ok_or_else(|| DatapackError::Invalid(format!("{} is not an object", name)))
+}
+
+fn get_f64(object: &Map<String, Value>, name: &str) -> Result<f64, DatapackError> {
+    get_field(object, name)?.as_f64().ok_or_else(|| DatapackError::Invalid(format!("{} is not a number", name)))
+}
+
+fn get_i32(object: &Map<String, Value>, name: &str) -> Result<i32, DatapackError> {
+    let value = get_field(object, name)?.as_i64().ok_or_else(|| DatapackError::Invalid(format!("{} is not an integer", name)))?;
+    i32::try_from(value).map_err(|_| DatapackError::Invalid(format!("{} is out of the integer range", name)))
+}
+
+fn get_bool_or(object: &Map<String, Value>, name: &str, default: bool) -> Result<bool, DatapackError> {
+    match object.get(name) {
+        None => Ok(default),
+        Some(value) => value.as_bool().ok_or_else(|| DatapackError::Invalid(format!("{} is not a boolean", name))),
+    }
+}
+
+fn noise_parameters_from_value(value: &Value) -> Result<NoiseParameters, DatapackError> {
+    let object = value.as_object().ok_or_else(|| DatapackError::Invalid("noise is not an object".to_string()))?;
+    let amplitudes = get_field(object, "amplitudes")?
+        .as_array()
+        .ok_or_else(|| DatapackError::Invalid("amplitudes is not an array".to_string()))?
+        .iter()
+        .map(|amplitude| amplitude.as_f64().ok_or_else(|| DatapackError::Invalid("amplitude is not a number".to_string())))
+        .collect::<Result<Vec<f64>, DatapackError>>()?;
+    Ok(NoiseParameters::new(get_i32(object, "firstOctave")?, amplitudes))
+}
+
+/// Parses a `worldgen/noise` file.
+pub fn parse_noise_parameters(json: &str) -> Result<NoiseParameters, DatapackError> {
+    noise_parameters_from_value(&serde_json::from_str(json)?)
+}
+
+fn slide_from_value(object: &Map<String, Value>) -> Result<NoiseSlideSettings, DatapackError> {
+    Ok(NoiseSlideSettings { target: get_i32(object, "target")?, size: get_i32(object, "size")?, offset: get_i32(object, "offset")? })
+}
+
+fn legacy_settings_from_value(settings: &Map<String, Value>, noise: &Map<String, Value>) -> Result<NoiseGeneratorSettings, DatapackError> {
+    let sampling = get_object(noise, "sampling")?;
+    Ok(NoiseGeneratorSettings {
+        noise_settings: NoiseSettings {
+            height: get_i32(noise, "height")?,
+            noise_sampling_settings: NoiseSamplingSettings {
+                xz_scale: get_f64(sampling, "xz_scale")?,
+                y_scale: get_f64(sampling, "y_scale")?,
+                xz_factor: get_f64(sampling, "xz_factor")?,
+                y_factor: get_f64(sampling, "y_factor")?,
+            },
+            top_slide_settings: slide_from_value(get_object(noise, "top_slide")?)?,
+            bottom_slide_settings: slide_from_value(get_object(noise, "bottom_slide")?)?,
+            noise_size_horizontal: get_i32(noise, "size_horizontal")?,
+            noise_size_vertical: get_i32(noise, "size_vertical")?,
+            density_factor: get_f64(noise, "density_factor")?,
+            density_offset: get_f64(noise, "density_offset")?,
+            use_simplex_surface_noise: get_bool_or(noise, "simplex_surface_noise", false)?,
+            random_density_offset: get_bool_or(noise, "random_density_offset", false)?,
+            island_noise_override: get_bool_or(noise, "island_noise_override", false)?,
+            is_amplified: get_bool_or(noise, "amplified", false)?,
+        },
+        bedrock_roof_position: get_i32(settings, "bedrock_roof_position")?,
+        bedrock_floor_position: get_i32(settings, "bedrock_floor_position")?,
+        sea_level: get_i32(settings, "sea_level")?,
+    })
+}
+
+fn collect_noise_references(value: &Value, references: &mut Vec<String>) {
+    match value {
+        Value::Array(values) => values.iter().for_each(|value| collect_noise_references(value, references)),
+        Value::Object(object) => {
+            let is_shift = match object.get("type").and_then(Value::as_str) {
+                Some(function_type) => matches!(resource_location(function_type).as_str(), "minecraft:shift_a" | "minecraft:shift_b" | "minecraft:shift"),
+                None => false,
+            };
+            for (key, value) in object {
+                match value {
+                    Value::String(location) if key == "noise" || (is_shift && key == "argument") => {
+                        let location = resource_location(location);
+                        if !references.contains(&location) {
+                            references.push(location);
+                        }
+                    }
+                    _ => collect_noise_references(value, references),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parses a `worldgen/noise_settings` file, in the 1.16 or the 1.18+ format.
+pub fn parse_noise_settings(json: &str) -> Result<NoiseSettingsDefinition, DatapackError> {
+    let value: Value = serde_json::from_str(json)?;
+    let settings = value.as_object().ok_or_else(|| DatapackError::Invalid("noise settings is not an object".to_string()))?;
+    let noise = get_object(settings, "noise")?;
+    let generator_settings = if settings.contains_key("bedrock_roof_position") {
+        Some(legacy_settings_from_value(settings, noise)?)
+    } else {
+        None
+    };
+    let mut noise_references = vec![];
+    for field in ["noise_router", "surface_rule"].iter() {
+        if let Some(value) = settings.get(*field) {
+            collect_noise_references(value, &mut noise_references);
+        }
+    }
+    Ok(NoiseSettingsDefinition {
+        // the random source flag appeared with 1.18, older presets are all legacy
+        legacy_random_source: get_bool_or(settings, "legacy_random_source", generator_settings.is_some())?,
+        sea_level: get_i32(settings, "sea_level")?,
+        min_y: if noise.contains_key("min_y") { get_i32(noise, "min_y")? } else { 0 },
+        height: get_i32(noise, "height")?,
+        noise_size_horizontal: get_i32(noise, "size_horizontal")?,
+        noise_size_vertical: get_i32(noise, "size_vertical")?,
+        generator_settings,
+        noise_references,
+    })
+}
+
+/// The noise and noise settings definitions of one or more datapacks, keyed by resource location.
+#[derive(Clone, Debug, Default)]
+pub struct Datapack {
+    noises: HashMap<String, NoiseParameters>,
+    noise_settings: HashMap<String, NoiseSettingsDefinition>,
+}
+
+impl Datapack {
+    pub fn new() -> Self {
+        Datapack::default()
+    }
+
+    /// Loads `data/<namespace>/worldgen/noise` and `noise_settings` of the datapack at `root`, later
+    /// definitions replacing earlier ones as with stacked datapacks. Returns the number of files read.
+    pub fn load(&mut self, root: &Path) -> Result<usize, DatapackError> {
+        let mut count: usize = 0;
+        let data = root.join("data");
+        if !data.is_dir() {
+            return Err(DatapackError::Invalid(format!("{} has no data directory", root.display())));
+        }
+        for namespace in fs::read_dir(data)? {
+            let namespace = namespace?;
+            let name = namespace.file_name().to_string_lossy().into_owned();
+            let worldgen = namespace.path().join("worldgen");
+            let mut files = vec![];
+            Self::list_json(&worldgen.join("noise"), "", &mut files)?;
+            for (path, location) in files.drain(..) {
+                self.register_noise(&format!("{}:{}", name, location), parse_noise_parameters(&fs::read_to_string(path)?)?);
+                count += 1;
+            }
+            Self::list_json(&worldgen.join("noise_settings"), "", &mut files)?;
+            for (path, location) in files.drain(..) {
+                self.register_noise_settings(&format!("{}:{}", name, location), parse_noise_settings(&fs::read_to_string(path)?)?);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn list_json(directory: &Path, prefix: &str, files: &mut Vec<(std::path::PathBuf, String)>) -> Result<(), DatapackError> {
+        if !directory.is_dir() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            if path.is_dir() {
+                Self::list_json(&path, &format!("{}{}/", prefix, name), files)?;
+            } else if let Some(stem) = name.strip_suffix(".json") {
+                files.push((path.clone(), format!("{}{}", prefix, stem)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn register_noise(&mut self, location: &str, parameters: NoiseParameters) {
+        self.noises.insert(resource_location(location), parameters);
+    }
+
+    pub fn register_noise_settings(&mut self, location: &str, settings: NoiseSettingsDefinition) {
+        self.noise_settings.insert(resource_location(location), settings);
+    }
+
+    pub fn get_noise_parameters(&self, location: &str) -> Option<&NoiseParameters> {
+        self.noises.get(&resource_location(location))
+    }
+
+    pub fn get_noise_settings(&self, location: &str) -> Option<&NoiseSettingsDefinition> {
+        self.noise_settings.get(&resource_location(location))
+    }
+
+    /// The noises of a world of `seed` generated with the noise settings `settings`.
+    pub fn configure(&self, seed: i64, settings: &str) -> Result<ConfiguredNoises, DatapackError> {
+        let definition = self.get_noise_settings(settings).ok_or_else(|| DatapackError::Missing(resource_location(settings)))?;
+        Ok(ConfiguredNoises::new(seed, definition.legacy_random_source, self.noises.clone()))
+    }
+}
+
+/// The noises of a world, created on first use and shared afterwards.
+#[derive(Clone, Debug)]
+pub struct ConfiguredNoises {
+    seed: i64,
+    legacy_random_source: bool,
+    factory: PositionalRandomFactory,
+    parameters: HashMap<String, NoiseParameters>,
+    noises: HashMap<String, Arc<DoublePerlinNoise>>,
+}
+
+impl ConfiguredNoises {
+    pub fn new(seed: i64, legacy_random_source: bool, parameters: HashMap<String, NoiseParameters>) -> Self {
+        ConfiguredNoises {
+            seed,
+            legacy_random_source,
+            factory: WorldgenRandom::new(seed, legacy_random_source).fork_positional(),
+            parameters,
+            noises: HashMap::new(),
+        }
+    }
+
+    pub fn get_factory(&self) -> &PositionalRandomFactory {
+        &self.factory
+    }
+
+    /// The `DoublePerlinNoise` registered at `location`, with the legacy random source exceptions
+    /// of vanilla: the temperature and vegetation noises keep their 1.16 nether biome seeding and
+    /// the offset noise, which shifts the climate coordinates, is always 0.
+    pub fn get_double_perlin_noise(&mut self, location: &str) -> Option<Arc<DoublePerlinNoise>> {
+        let location = resource_location(location);
+        if let Some(noise) = self.noises.get(&location) {
+            return Some(noise.clone());
+        }
+        let legacy_offset: Option<i64> = match location.as_str() {
+            "minecraft:temperature" if self.legacy_random_source => Some(0),
+            "minecraft:vegetation" if self.legacy_random_source => Some(1),
+            _ => None,
+        };
+        let noise = match legacy_offset {
+            Some(offset) => {
+                let mut random = WorldgenRandom::new(self.seed.wrapping_add(offset), true);
+                DoublePerlinNoise::create_legacy(&mut random, -7, vec![1.0f64, 1.0f64])
+            }
+            None if self.legacy_random_source && location == "minecraft:offset" => {
+                DoublePerlinNoise::instantiate(&self.factory, &location, &NoiseParameters::new(0, vec![0.0f64]))
+            }
+            None => {
+                let parameters = self.parameters.get(&location)?;
+                DoublePerlinNoise::instantiate(&self.factory, &location, parameters)
+            }
+        };
+        let noise = Arc::new(noise);
+        self.noises.insert(location, noise.clone());
+        Some(noise)
+    }
+
+    /// A single `PerlinNoise` with the parameters registered at `location`, seeded like the double perlin one.
+    pub fn create_perlin_noise(&self, location: &str) -> Option<PerlinNoise> {
+        let location = resource_location(location);
+        let parameters = self.parameters.get(&location)?;
+        Some(PerlinNoise::create(&mut self.factory.from_hash_of(&location), parameters.first_octave, parameters.amplitudes.clone()))
+    }
+}
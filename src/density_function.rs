@@ -0,0 +1,418 @@
+use std::cell::Cell;
+use std::sync::Arc;
+
+use crate::blended_noise::BlendedNoise;
+use crate::double_perlin_noise::DoublePerlinNoise;
+use crate::math::{clamped_lerp, lerp1};
+use crate::terrain_shaper::{CubicSpline, TerrainPoint};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TwoArgumentType {
+    Add,
+    Mul,
+    Min,
+    Max,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MappedType {
+    Abs,
+    Square,
+    Cube,
+    HalfNegative,
+    QuarterNegative,
+    Squeeze,
+}
+
+/// Maps the input of a `weird_scaled_sampler` to the scale of the noise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RarityValueMapper {
+    /// `type_1`, the 3D spaghetti caves rarity.
+    Tunnels,
+    /// `type_2`, the 2D spaghetti caves rarity.
+    Caves,
+}
+
+impl RarityValueMapper {
+    pub fn get_rarity(&self, value: f64) -> f64 {
+        match self {
+            RarityValueMapper::Tunnels => {
+                if value < -0.5f64 {
+                    0.75f64
+                } else if value < 0.0f64 {
+                    1.0f64
+                } else if value < 0.5f64 {
+                    1.5f64
+                } else {
+                    2.0f64
+                }
+            }
+            RarityValueMapper::Caves => {
+                if value < -0.75f64 {
+                    0.5f64
+                } else if value < -0.5f64 {
+                    0.75f64
+                } else if value < 0.5f64 {
+                    1.0f64
+                } else if value < 0.75f64 {
+                    2.0f64
+                } else {
+                    3.0f64
+                }
+            }
+        }
+    }
+}
+
+/// Corner values of the last noise cell an `interpolated` marker was evaluated in.
+type CellCache = Cell<Option<((i32, i32, i32), [f64; 8])>>;
+
+/// Last position and value computed by a caching marker.
+#[derive(Clone, Debug, Default)]
+pub struct PositionCache {
+    position: Cell<Option<(i32, i32, i32)>>,
+    value: Cell<f64>,
+}
+
+impl PositionCache {
+    fn get_or_compute<F: FnOnce() -> f64>(&self, position: (i32, i32, i32), compute: F) -> f64 {
+        if self.position.get() == Some(position) {
+            return self.value.get();
+        }
+        let value = compute();
+        self.position.set(Some(position));
+        self.value.set(value);
+        value
+    }
+}
+
+/// A node of the 1.18+ density function graph.
+///
+/// Caching markers keep their last result in the node, a graph is meant to be evaluated from a
+/// single thread and cloned for others. `flat_cache` samples at the quart position with y=0 and
+/// `interpolated` interpolates its input over the noise cells, as the vanilla noise chunk does;
+/// the other markers do not change the results. Without old chunks, the blending functions are
+/// the identity.
+#[derive(Clone, Debug)]
+pub enum DensityFunction {
+    Constant(f64),
+    TwoArgument(TwoArgumentType, Box<DensityFunction>, Box<DensityFunction>),
+    Mapped(MappedType, Box<DensityFunction>),
+    Clamp { input: Box<DensityFunction>, min: f64, max: f64 },
+    YClampedGradient { from_y: i32, to_y: i32, from_value: f64, to_value: f64 },
+    RangeChoice {
+        input: Box<DensityFunction>,
+        min_inclusive: f64,
+        max_exclusive: f64,
+        when_in_range: Box<DensityFunction>,
+        when_out_of_range: Box<DensityFunction>,
+    },
+    Noise { noise: Arc<DoublePerlinNoise>, xz_scale: f64, y_scale: f64 },
+    ShiftedNoise {
+        shift_x: Box<DensityFunction>,
+        shift_y: Box<DensityFunction>,
+        shift_z: Box<DensityFunction>,
+        xz_scale: f64,
+        y_scale: f64,
+        noise: Arc<DoublePerlinNoise>,
+    },
+    ShiftA(Arc<DoublePerlinNoise>),
+    ShiftB(Arc<DoublePerlinNoise>),
+    Shift(Arc<DoublePerlinNoise>),
+    WeirdScaledSampler { input: Box<DensityFunction>, noise: Arc<DoublePerlinNoise>, rarity_value_mapper: RarityValueMapper },
+    OldBlendedNoise(Arc<BlendedNoise>),
+    TerrainShaperSpline {
+        continents: Box<DensityFunction>,
+        erosion: Box<DensityFunction>,
+        weirdness: Box<DensityFunction>,
+        spline: Arc<CubicSpline>,
+        min_value: f64,
+        max_value: f64,
+    },
+    Interpolated { input: Box<DensityFunction>, cell_width: i32, cell_height: i32, cache: CellCache },
+    FlatCache(Box<DensityFunction>, PositionCache),
+    Cache2d(Box<DensityFunction>, PositionCache),
+    CacheOnce(Box<DensityFunction>, PositionCache),
+    CacheAllInCell(Box<DensityFunction>, PositionCache),
+    BlendAlpha,
+    BlendOffset,
+    BlendDensity(Box<DensityFunction>),
+    Beardifier,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod density_function_test {
+    use super::*;
+    use java_random::Random;
+
+    fn test_noise() -> Arc<DoublePerlinNoise> {
+        Arc::new(DoublePerlinNoise::create(&mut Random::with_seed(1), -4, vec![1.0f64, 1.0f64]))
+    }
+
+    #[test]
+    fn test_math() {
+        let function = DensityFunction::add(DensityFunction::constant(0.5), DensityFunction::y_clamped_gradient(0, 10, 0.0, 1.0)).squeeze();
+        assert_eq!(function.compute(0, 5, 0), 0.5 - 1.0 / 24.0);
+        assert_eq!(function.compute(0, -5, 0), 0.25 - 0.125 / 24.0);
+        let choice = DensityFunction::range_choice(DensityFunction::y_clamped_gradient(0, 10, 0.0, 1.0), 0.0, 0.5, DensityFunction::constant(1.0), DensityFunction::constant(2.0));
+        assert_eq!(choice.compute(0, 4, 0), 1.0);
+        assert_eq!(choice.compute(0, 5, 0), 2.0);
+    }
+
+    #[test]
+    fn test_interpolated_on_corners() {
+        let noise = DensityFunction::noise(test_noise(), 0.25, 0.125);
+        let interpolated = DensityFunction::interpolated(noise.clone(), 4, 8);
+        for x in -2..2 {
+            for y in -2..2 {
+                assert_eq!(interpolated.compute(x * 4, y * 8, 7 * 4), noise.compute(x * 4, y * 8, 7 * 4));
+            }
+        }
+        let inside = interpolated.compute(1, 3, 2);
+        assert!(inside != noise.compute(1, 3, 2));
+        assert_eq!(inside, interpolated.clone().compute(1, 3, 2));
+    }
+
+    #[test]
+    fn test_flat_cache_quantizes() {
+        let shift = DensityFunction::flat_cache(DensityFunction::cache_2d(DensityFunction::ShiftA(test_noise())));
+        assert_eq!(shift.compute(5, 70, 9), DensityFunction::ShiftA(test_noise()).compute(4, 0, 8));
+    }
+}
+
+#[allow(clippy::should_implement_trait)]
+impl DensityFunction {
+    pub fn constant(value: f64) -> Self {
+        DensityFunction::Constant(value)
+    }
+
+    pub fn zero() -> Self {
+        DensityFunction::Constant(0.0f64)
+    }
+
+    pub fn add(argument1: DensityFunction, argument2: DensityFunction) -> Self {
+        DensityFunction::TwoArgument(TwoArgumentType::Add, Box::new(argument1), Box::new(argument2))
+    }
+
+    pub fn mul(argument1: DensityFunction, argument2: DensityFunction) -> Self {
+        DensityFunction::TwoArgument(TwoArgumentType::Mul, Box::new(argument1), Box::new(argument2))
+    }
+
+    pub fn min(argument1: DensityFunction, argument2: DensityFunction) -> Self {
+        DensityFunction::TwoArgument(TwoArgumentType::Min, Box::new(argument1), Box::new(argument2))
+    }
+
+    pub fn max(argument1: DensityFunction, argument2: DensityFunction) -> Self {
+        DensityFunction::TwoArgument(TwoArgumentType::Max, Box::new(argument1), Box::new(argument2))
+    }
+
+    /// `a` when `delta` is 0, `b` when it is 1.
+    pub fn lerp(delta: DensityFunction, a: DensityFunction, b: DensityFunction) -> Self {
+        if let DensityFunction::Constant(a) = a {
+            return Self::add(Self::mul(delta, Self::add(b, Self::constant(-a))), Self::constant(a));
+        }
+        let delta = Self::cache_once(delta);
+        let one_minus_delta = Self::add(Self::mul(delta.clone(), Self::constant(-1.0f64)), Self::constant(1.0f64));
+        Self::add(Self::mul(a, one_minus_delta), Self::mul(b, delta))
+    }
+
+    pub fn y_clamped_gradient(from_y: i32, to_y: i32, from_value: f64, to_value: f64) -> Self {
+        DensityFunction::YClampedGradient { from_y, to_y, from_value, to_value }
+    }
+
+    pub fn range_choice(input: DensityFunction, min_inclusive: f64, max_exclusive: f64, when_in_range: DensityFunction, when_out_of_range: DensityFunction) -> Self {
+        DensityFunction::RangeChoice {
+            input: Box::new(input),
+            min_inclusive,
+            max_exclusive,
+            when_in_range: Box::new(when_in_range),
+            when_out_of_range: Box::new(when_out_of_range),
+        }
+    }
+
+    pub fn noise(noise: Arc<DoublePerlinNoise>, xz_scale: f64, y_scale: f64) -> Self {
+        DensityFunction::Noise { noise, xz_scale, y_scale }
+    }
+
+    pub fn shifted_noise_2d(shift_x: DensityFunction, shift_z: DensityFunction, xz_scale: f64, noise: Arc<DoublePerlinNoise>) -> Self {
+        DensityFunction::ShiftedNoise {
+            shift_x: Box::new(shift_x),
+            shift_y: Box::new(Self::zero()),
+            shift_z: Box::new(shift_z),
+            xz_scale,
+            y_scale: 0.0f64,
+            noise,
+        }
+    }
+
+    pub fn weird_scaled_sampler(input: DensityFunction, noise: Arc<DoublePerlinNoise>, rarity_value_mapper: RarityValueMapper) -> Self {
+        DensityFunction::WeirdScaledSampler { input: Box::new(input), noise, rarity_value_mapper }
+    }
+
+    /// A terrain shaper spline evaluated on the climate functions, clamped to `min_value..=max_value`.
+    pub fn terrain_shaper_spline(continents: DensityFunction, erosion: DensityFunction, weirdness: DensityFunction, spline: Arc<CubicSpline>, min_value: f64, max_value: f64) -> Self {
+        DensityFunction::TerrainShaperSpline {
+            continents: Box::new(continents),
+            erosion: Box::new(erosion),
+            weirdness: Box::new(weirdness),
+            spline,
+            min_value,
+            max_value,
+        }
+    }
+
+    /// Interpolates `input` over cells of `cell_width` x `cell_height` x `cell_width` blocks.
+    pub fn interpolated(input: DensityFunction, cell_width: i32, cell_height: i32) -> Self {
+        DensityFunction::Interpolated { input: Box::new(input), cell_width, cell_height, cache: Cell::new(None) }
+    }
+
+    pub fn flat_cache(input: DensityFunction) -> Self {
+        DensityFunction::FlatCache(Box::new(input), PositionCache::default())
+    }
+
+    pub fn cache_2d(input: DensityFunction) -> Self {
+        DensityFunction::Cache2d(Box::new(input), PositionCache::default())
+    }
+
+    pub fn cache_once(input: DensityFunction) -> Self {
+        DensityFunction::CacheOnce(Box::new(input), PositionCache::default())
+    }
+
+    pub fn cache_all_in_cell(input: DensityFunction) -> Self {
+        DensityFunction::CacheAllInCell(Box::new(input), PositionCache::default())
+    }
+
+    fn mapped(self, mapped_type: MappedType) -> Self {
+        DensityFunction::Mapped(mapped_type, Box::new(self))
+    }
+
+    pub fn abs(self) -> Self {
+        self.mapped(MappedType::Abs)
+    }
+
+    pub fn square(self) -> Self {
+        self.mapped(MappedType::Square)
+    }
+
+    pub fn cube(self) -> Self {
+        self.mapped(MappedType::Cube)
+    }
+
+    pub fn half_negative(self) -> Self {
+        self.mapped(MappedType::HalfNegative)
+    }
+
+    pub fn quarter_negative(self) -> Self {
+        self.mapped(MappedType::QuarterNegative)
+    }
+
+    pub fn squeeze(self) -> Self {
+        self.mapped(MappedType::Squeeze)
+    }
+
+    pub fn clamp(self, min: f64, max: f64) -> Self {
+        DensityFunction::Clamp { input: Box::new(self), min, max }
+    }
+
+    fn shift(noise: &DoublePerlinNoise, x: f64, y: f64, z: f64) -> f64 {
+        noise.sample(x * 0.25f64, y * 0.25f64, z * 0.25f64) * 4.0f64
+    }
+
+    pub fn compute(&self, x: i32, y: i32, z: i32) -> f64 {
+        match self {
+            DensityFunction::Constant(value) => *value,
+            DensityFunction::TwoArgument(argument_type, argument1, argument2) => {
+                let value: f64 = argument1.compute(x, y, z);
+                match argument_type {
+                    TwoArgumentType::Add => value + argument2.compute(x, y, z),
+                    TwoArgumentType::Mul => if value == 0.0f64 { 0.0f64 } else { value * argument2.compute(x, y, z) },
+                    TwoArgumentType::Min => value.min(argument2.compute(x, y, z)),
+                    TwoArgumentType::Max => value.max(argument2.compute(x, y, z)),
+                }
+            }
+            DensityFunction::Mapped(mapped_type, input) => {
+                let value: f64 = input.compute(x, y, z);
+                match mapped_type {
+                    MappedType::Abs => value.abs(),
+                    MappedType::Square => value * value,
+                    MappedType::Cube => value * value * value,
+                    MappedType::HalfNegative => if value > 0.0f64 { value } else { value * 0.5f64 },
+                    MappedType::QuarterNegative => if value > 0.0f64 { value } else { value * 0.25f64 },
+                    MappedType::Squeeze => {
+                        let clamped: f64 = value.clamp(-1.0f64, 1.0f64);
+                        clamped / 2.0f64 - clamped * clamped * clamped / 24.0f64
+                    }
+                }
+            }
+            DensityFunction::Clamp { input, min, max } => input.compute(x, y, z).clamp(*min, *max),
+            DensityFunction::YClampedGradient { from_y, to_y, from_value, to_value } => {
+                clamped_lerp(*from_value, *to_value, (y as f64 - *from_y as f64) / (*to_y as f64 - *from_y as f64))
+            }
+            DensityFunction::RangeChoice { input, min_inclusive, max_exclusive, when_in_range, when_out_of_range } => {
+                let value: f64 = input.compute(x, y, z);
+                if value >= *min_inclusive && value < *max_exclusive {
+                    when_in_range.compute(x, y, z)
+                } else {
+                    when_out_of_range.compute(x, y, z)
+                }
+            }
+            DensityFunction::Noise { noise, xz_scale, y_scale } => noise.sample(x as f64 * xz_scale, y as f64 * y_scale, z as f64 * xz_scale),
+            DensityFunction::ShiftedNoise { shift_x, shift_y, shift_z, xz_scale, y_scale, noise } => noise.sample(
+                x as f64 * xz_scale + shift_x.compute(x, y, z),
+                y as f64 * y_scale + shift_y.compute(x, y, z),
+                z as f64 * xz_scale + shift_z.compute(x, y, z)),
+            DensityFunction::ShiftA(noise) => Self::shift(noise, x as f64, 0.0f64, z as f64),
+            DensityFunction::ShiftB(noise) => Self::shift(noise, z as f64, x as f64, 0.0f64),
+            DensityFunction::Shift(noise) => Self::shift(noise, x as f64, y as f64, z as f64),
+            DensityFunction::WeirdScaledSampler { input, noise, rarity_value_mapper } => {
+                let rarity: f64 = rarity_value_mapper.get_rarity(input.compute(x, y, z));
+                rarity * noise.sample(x as f64 / rarity, y as f64 / rarity, z as f64 / rarity).abs()
+            }
+            DensityFunction::OldBlendedNoise(noise) => noise.sample(x, y, z),
+            DensityFunction::TerrainShaperSpline { continents, erosion, weirdness, spline, min_value, max_value } => {
+                let point = TerrainPoint::new(continents.compute(x, y, z), erosion.compute(x, y, z), weirdness.compute(x, y, z));
+                (spline.apply(&point) as f64).clamp(*min_value, *max_value)
+            }
+            DensityFunction::Interpolated { input, cell_width, cell_height, cache } => {
+                let cell = (x.div_euclid(*cell_width), y.div_euclid(*cell_height), z.div_euclid(*cell_width));
+                let corners = match cache.get() {
+                    Some((cached_cell, corners)) if cached_cell == cell => corners,
+                    _ => {
+                        let mut corners = [0f64; 8];
+                        for (i, corner) in corners.iter_mut().enumerate() {
+                            *corner = input.compute(
+                                (cell.0 + (i as i32 >> 2 & 1)) * cell_width,
+                                (cell.1 + (i as i32 >> 1 & 1)) * cell_height,
+                                (cell.2 + (i as i32 & 1)) * cell_width);
+                        }
+                        cache.set(Some((cell, corners)));
+                        corners
+                    }
+                };
+                let x_frac: f64 = x.rem_euclid(*cell_width) as f64 / *cell_width as f64;
+                let y_frac: f64 = y.rem_euclid(*cell_height) as f64 / *cell_height as f64;
+                let z_frac: f64 = z.rem_euclid(*cell_width) as f64 / *cell_width as f64;
+                // y first, then x, then z like the noise chunk interpolator
+                let x0z0: f64 = lerp1(y_frac, corners[0b000], corners[0b010]);
+                let x1z0: f64 = lerp1(y_frac, corners[0b100], corners[0b110]);
+                let x0z1: f64 = lerp1(y_frac, corners[0b001], corners[0b011]);
+                let x1z1: f64 = lerp1(y_frac, corners[0b101], corners[0b111]);
+                lerp1(z_frac, lerp1(x_frac, x0z0, x1z0), lerp1(x_frac, x0z1, x1z1))
+            }
+            DensityFunction::FlatCache(input, cache) => {
+                let quart_x = x >> 2 << 2;
+                let quart_z = z >> 2 << 2;
+                cache.get_or_compute((quart_x, 0, quart_z), || input.compute(quart_x, 0, quart_z))
+            }
+            DensityFunction::Cache2d(input, cache) => cache.get_or_compute((x, 0, z), || input.compute(x, y, z)),
+            DensityFunction::CacheOnce(input, cache) | DensityFunction::CacheAllInCell(input, cache) => {
+                cache.get_or_compute((x, y, z), || input.compute(x, y, z))
+            }
+            DensityFunction::BlendAlpha => 1.0f64,
+            DensityFunction::BlendOffset => 0.0f64,
+            DensityFunction::BlendDensity(input) => input.compute(x, y, z),
+            DensityFunction::Beardifier => 0.0f64,
+        }
+    }
+}
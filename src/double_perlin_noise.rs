@@ -1,9 +1,28 @@
 #![allow(dead_code)]
 
+use crate::noise_trace::NoiseTrace;
 use crate::perlin_noise::PerlinNoise;
+use crate::random_source::{PositionalRandomFactory, RandomSource};
+use alloc::vec::Vec;
 use java_random::Random;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// First octave and octave amplitudes of a noise, as in the `worldgen/noise` registry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseParameters {
+    pub first_octave: i32,
+    pub amplitudes: Vec<f64>,
+}
+
+impl NoiseParameters {
+    pub fn new(first_octave: i32, amplitudes: Vec<f64>) -> Self {
+        NoiseParameters { first_octave, amplitudes }
+    }
+}
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DoublePerlinNoise {
     amplitude: f64,
     first_perlin: PerlinNoise,
@@ -11,8 +30,10 @@ pub struct DoublePerlinNoise {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod double_perlin_test {
     use super::*;
+    use alloc::vec;
     use crate::create_range;
 
     #[test]
@@ -29,6 +50,16 @@ mod double_perlin_test {
         assert_eq!(value, 0.07304369034293899f64)
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let double_perlin = DoublePerlinNoise::create(&mut Random::with_seed(1), -7, vec![1.0f64, 0.0f64, 2.0f64]);
+        let json = serde_json::to_string(&double_perlin).unwrap();
+        let restored: DoublePerlinNoise = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.sample(25f64, 3f64, 24f64), double_perlin.sample(25f64, 3f64, 24f64));
+        assert!(serde_json::from_str::<crate::noise::Noise>(r#"{"x0": 0.0, "y0": 0.0, "z0": 0.0, "permutations": [1, 2]}"#).is_err());
+    }
+
     #[test]
     fn test_gen_1million() {
         let noise = DoublePerlinNoise::new(&mut Random::with_seed(1), create_range(1, 2));
@@ -43,23 +74,77 @@ mod double_perlin_test {
         }
         assert_eq!(score, 32.885536183861234);
     }
+
+    #[test]
+    fn test_create_amplitude() {
+        let noise = DoublePerlinNoise::create(&mut Random::with_seed(1), -7, vec![0.0f64, 1.0f64, 1.0f64, 0.0f64]);
+        assert_eq!(noise.amplitude, 0.16666666666666666f64 / DoublePerlinNoise::create_amplitude(1));
+    }
 }
 
 impl DoublePerlinNoise {
     pub fn new(random: &mut Random, octaves: Vec<i32>) -> Self {
+        Self::build(random, octaves, None)
+    }
+
+    /// `DoublePerlinNoise::new`, recording the random calls of both perlin noises in the trace.
+    pub fn new_traced(random: &mut Random, octaves: Vec<i32>, trace: &mut NoiseTrace) -> Self {
+        Self::build(random, octaves, Some(trace))
+    }
+
+    fn build(random: &mut Random, octaves: Vec<i32>, trace: Option<&mut NoiseTrace>) -> Self {
         let min_octave = octaves.iter().min().unwrap_or(&0);
         let max_octave = octaves.iter().max().unwrap_or(&0);
+        let amplitude = 0.16666666666666666f64 / Self::create_amplitude(max_octave - min_octave);
+        let (first_perlin, second_perlin) = match trace {
+            Some(trace) => {
+                trace.set_perlin(0);
+                let first_perlin = PerlinNoise::new_traced(random, octaves.clone(), trace);
+                trace.set_perlin(1);
+                let second_perlin = PerlinNoise::new_traced(random, octaves, trace);
+                trace.set_perlin(0);
+                (first_perlin, second_perlin)
+            }
+            None => (PerlinNoise::new(random, octaves.clone()), PerlinNoise::new(random, octaves)),
+        };
+        DoublePerlinNoise { amplitude, first_perlin, second_perlin }
+    }
+
+    /// The 1.18+ `NormalNoise`, both halves built with [`PerlinNoise::create`].
+    pub fn create<R: RandomSource>(random: &mut R, first_octave: i32, amplitudes: Vec<f64>) -> Self {
+        let first_perlin = PerlinNoise::create(random, first_octave, amplitudes.clone());
+        let second_perlin = PerlinNoise::create(random, first_octave, amplitudes.clone());
+        Self::from_perlins(first_perlin, second_perlin, &amplitudes)
+    }
+
+    /// The 1.16.2-1.17 layout, both halves built with [`PerlinNoise::create_legacy`].
+    pub fn create_legacy<R: RandomSource>(random: &mut R, first_octave: i32, amplitudes: Vec<f64>) -> Self {
+        let first_perlin = PerlinNoise::create_legacy(random, first_octave, amplitudes.clone());
+        let second_perlin = PerlinNoise::create_legacy(random, first_octave, amplitudes.clone());
+        Self::from_perlins(first_perlin, second_perlin, &amplitudes)
+    }
+
+    /// The noise registered as `name` (for example `minecraft:temperature`) in a 1.18+ world.
+    pub fn instantiate(factory: &PositionalRandomFactory, name: &str, parameters: &NoiseParameters) -> Self {
+        Self::create(&mut factory.from_hash_of(name), parameters.first_octave, parameters.amplitudes.clone())
+    }
+
+    fn from_perlins(first_perlin: PerlinNoise, second_perlin: PerlinNoise, amplitudes: &[f64]) -> Self {
+        let used = amplitudes.iter().enumerate().filter(|(_, &amplitude)| amplitude != 0.0f64).map(|(i, _)| i as i32);
+        let min_octave = used.clone().min().unwrap_or(i32::MAX);
+        let max_octave = used.max().unwrap_or(i32::MIN);
         DoublePerlinNoise {
-            amplitude: 0.16666666666666666f64 / Self::create_amplitude(max_octave - min_octave),
-            first_perlin: PerlinNoise::new(random, octaves.clone()),
-            second_perlin: PerlinNoise::new(random, octaves.clone()),
+            amplitude: 0.16666666666666666f64 / Self::create_amplitude(max_octave.wrapping_sub(min_octave)),
+            first_perlin,
+            second_perlin,
         }
     }
 
-    fn create_amplitude(length: i32) -> f64 {
-        0.1f64 * (1.0f64 + 1.0f64 / ((length + 1) as f64))
+    pub(crate) fn create_amplitude(length: i32) -> f64 {
+        0.1f64 * (1.0f64 + 1.0f64 / (length.wrapping_add(1) as f64))
     }
 
+    #[allow(clippy::needless_return)]
     pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
         let skewed_x = x * 1.0181268882175227f64;
         let skewed_y = y * 1.0181268882175227f64;
@@ -0,0 +1,77 @@
+use java_random::{LCG, Random};
+
+use crate::math;
+use crate::simplex_noise::SimplexNoise;
+
+#[cfg(feature = "const_fn")]
+const SKIP_17292: LCG = LCG::combine_java(17292);
+#[cfg(not(feature = "const_fn"))]
+const SKIP_17292: LCG = LCG { multiplier: 257489430523441, addend: 184379205320524 };
+
+/// The End island noise of 1.16+, driving the outer islands and the end biome choice.
+#[derive(Clone, Debug)]
+pub struct EndIslands {
+    island_noise: SimplexNoise,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod end_islands_test {
+    use super::*;
+
+    #[test]
+    fn test_skip_constant() {
+        let mut random = Random::with_seed(0);
+        random.advance(SKIP_17292);
+        let mut expected = Random::with_seed(0);
+        for _ in 0..17292 {
+            expected.next_int();
+        }
+        assert_eq!(random.next_long(), expected.next_long());
+    }
+
+    #[test]
+    fn test_main_island() {
+        let mut islands = EndIslands::new(1);
+        assert_eq!(islands.get_height_value(0, 0), 80.0f32);
+        assert_eq!(islands.get_height_value(3, 4), 60.0f32);
+        assert!(islands.get_height_value(200, 0) <= 80.0f32);
+    }
+}
+
+impl EndIslands {
+    pub fn new(world_seed: u64) -> Self {
+        let mut random = Random::with_seed(world_seed);
+        random.advance(SKIP_17292);
+        EndIslands { island_noise: SimplexNoise::init(random) }
+    }
+
+    pub fn get_island_noise(&mut self) -> &mut SimplexNoise {
+        &mut self.island_noise
+    }
+
+    /// Vanilla `TheEndBiomeSource.getHeightValue`, in units of 8 blocks: 80 on the main island,
+    /// -100 in the void and in between on the outer islands.
+    pub fn get_height_value(&mut self, x: i32, z: i32) -> f32 {
+        let center_x: i32 = x / 2;
+        let center_z: i32 = z / 2;
+        let offset_x: i32 = x % 2;
+        let offset_z: i32 = z % 2;
+        let distance: f32 = math::sqrt(x.wrapping_mul(x).wrapping_add(z.wrapping_mul(z)) as f32);
+        let mut height: f32 = math::clamp(100.0f32 - distance * 8.0f32, -100.0f32, 80.0f32);
+        for i in -12..=12 {
+            for j in -12..=12 {
+                let island_x: i64 = (center_x + i) as i64;
+                let island_z: i64 = (center_z + j) as i64;
+                if island_x * island_x + island_z * island_z > 4096i64 && self.island_noise.get_value_2d(island_x as f64, island_z as f64) < -0.9f32 as f64 {
+                    let falloff: f32 = (math::abs(island_x as f32) * 3439.0f32 + math::abs(island_z as f32) * 147.0f32) % 13.0f32 + 9.0f32;
+                    let dx: f32 = (offset_x - i * 2) as f32;
+                    let dz: f32 = (offset_z - j * 2) as f32;
+                    let island_height: f32 = math::clamp(100.0f32 - math::sqrt(dx * dx + dz * dz) * falloff, -100.0f32, 80.0f32);
+                    height = math::max(height, island_height);
+                }
+            }
+        }
+        height
+    }
+}
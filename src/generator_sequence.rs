@@ -0,0 +1,128 @@
+//! The noises vanilla terrain generators build one after the other from the world seed.
+//!
+//! A filter usually needs one of them: [`GeneratorVersion::create_perlin_noise`] jumps the random
+//! over the ones before with the [`NoiseLayout`] skips instead of building them. Like those skips
+//! it assumes every octave before takes its 262 calls, which a few seeds in ten thousand break for
+//! the last noises of a sequence (see [`noise_skip`](crate::noise_skip)).
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use java_random::{Random, LCG};
+
+use crate::create_range;
+use crate::noise_skip::NoiseLayout;
+use crate::perlin_noise::PerlinNoise;
+
+/// The versions by generator: 1.14 to 1.17 share the `NoiseChunkGenerator` sequence, 1.18 is the
+/// `BlendedNoise` of the noise settings with `legacy_random_source`, the others being positional.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorVersion {
+    V1_13,
+    V1_14,
+    V1_15,
+    V1_16,
+    V1_17,
+    V1_18,
+}
+
+/// A noise of a generator sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequenceNoise {
+    pub name: &'static str,
+    pub layout: NoiseLayout,
+    /// Vanilla only advances the random past it, the scale noise from 1.14 on.
+    pub skipped: bool,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod generator_sequence_test {
+    use super::*;
+
+    #[test]
+    fn test_sequences() {
+        let names: Vec<&str> = GeneratorVersion::V1_16.get_sequence().iter().map(|noise| noise.name).collect();
+        assert_eq!(names, vec!["min_limit", "max_limit", "main", "surface", "scale", "depth"]);
+        assert!(GeneratorVersion::V1_16.get_sequence()[4].skipped);
+        assert!(!GeneratorVersion::V1_13.get_sequence()[4].skipped);
+        assert_eq!(GeneratorVersion::V1_18.get_noise_index("main"), Some(2));
+        assert_eq!(GeneratorVersion::V1_18.get_noise_index("depth"), None);
+    }
+
+    #[test]
+    fn test_jump_to_depth() {
+        for seed in 0..20u64 {
+            let mut random = Random::with_seed(seed);
+            for octaves in [create_range(-15, 0), create_range(-15, 0), create_range(-7, 0), create_range(-3, 0), create_range(-9, 0)] {
+                PerlinNoise::new(&mut random, octaves);
+            }
+            let depth = PerlinNoise::new(&mut random, create_range(-15, 0));
+            let jumped = GeneratorVersion::V1_16.create_perlin_noise(seed, "depth").unwrap();
+            assert_eq!(jumped.sample_default(10.5f64, 0.0f64, -3.25f64), depth.sample_default(10.5f64, 0.0f64, -3.25f64));
+        }
+    }
+
+    #[test]
+    fn test_jump_to_main() {
+        let mut random = Random::with_seed(42);
+        PerlinNoise::create_legacy(&mut random, -15, vec![1.0f64; 16]);
+        PerlinNoise::create_legacy(&mut random, -15, vec![1.0f64; 16]);
+        let main = PerlinNoise::create_legacy(&mut random, -7, vec![1.0f64; 8]);
+        let jumped = GeneratorVersion::V1_18.create_perlin_noise(42, "main").unwrap();
+        assert_eq!(jumped.sample_default(1.5f64, 2.5f64, 3.5f64), main.sample_default(1.5f64, 2.5f64, 3.5f64));
+    }
+}
+
+impl GeneratorVersion {
+    /// The noises in the order they are built. The surface noise is a `PerlinSimplexNoise` in
+    /// the overworld, which takes the calls of a perlin noise of the same octaves.
+    pub fn get_sequence(&self) -> Vec<SequenceNoise> {
+        let perlin = |name: &'static str, low: i32, skipped: bool| SequenceNoise { name, layout: NoiseLayout::Perlin(create_range(low, 0)), skipped };
+        let legacy_perlin = |name: &'static str, first_octave: i32| SequenceNoise {
+            name,
+            layout: NoiseLayout::LegacyPerlin { first_octave, length: (1 - first_octave) as usize },
+            skipped: false,
+        };
+        match self {
+            GeneratorVersion::V1_13 | GeneratorVersion::V1_14 | GeneratorVersion::V1_15 | GeneratorVersion::V1_16 | GeneratorVersion::V1_17 => vec![
+                perlin("min_limit", -15, false),
+                perlin("max_limit", -15, false),
+                perlin("main", -7, false),
+                perlin("surface", -3, false),
+                // 1.14 replaced the scale noise by a 2620 calls advance
+                perlin("scale", -9, *self != GeneratorVersion::V1_13),
+                perlin("depth", -15, false),
+            ],
+            GeneratorVersion::V1_18 => vec![legacy_perlin("min_limit", -15), legacy_perlin("max_limit", -15), legacy_perlin("main", -7)],
+        }
+    }
+
+    pub fn get_noise_index(&self, name: &str) -> Option<usize> {
+        self.get_sequence().iter().position(|noise| noise.name == name)
+    }
+
+    /// The random of the world seed right before the noise at `index` is built, none if the index
+    /// is past the sequence.
+    pub fn get_random(&self, seed: u64, index: usize) -> Option<Random> {
+        let sequence = self.get_sequence();
+        if index >= sequence.len() {
+            return None;
+        }
+        let calls = sequence[..index].iter().map(|noise| noise.layout.get_calls()).sum::<Option<u64>>()?;
+        let mut random = Random::with_seed(seed);
+        random.advance(LCG::combine_java(calls));
+        Some(random)
+    }
+
+    /// The perlin noise `name` of the sequence, built alone.
+    pub fn create_perlin_noise(&self, seed: u64, name: &str) -> Option<PerlinNoise> {
+        let index = self.get_noise_index(name)?;
+        let mut random = self.get_random(seed, index)?;
+        match &self.get_sequence()[index].layout {
+            NoiseLayout::Perlin(octaves) => Some(PerlinNoise::new(&mut random, octaves.clone())),
+            NoiseLayout::LegacyPerlin { first_octave, length } => Some(PerlinNoise::create_legacy(&mut random, *first_octave, vec![1.0f64; *length])),
+            _ => None,
+        }
+    }
+}
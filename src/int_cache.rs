@@ -0,0 +1,27 @@
+//! The integer keyed cache of the samplers: `intmap` when the feature is on, a std `HashMap` otherwise.
+
+#[cfg(feature = "intmap")]
+pub type IntCache<V> = intmap::IntMap<V>;
+
+#[cfg(not(feature = "intmap"))]
+#[derive(Clone, Debug, Default)]
+pub struct IntCache<V>(std::collections::HashMap<u64, V>);
+
+#[cfg(not(feature = "intmap"))]
+impl<V> IntCache<V> {
+    pub fn new() -> Self {
+        IntCache(std::collections::HashMap::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        IntCache(std::collections::HashMap::with_capacity(capacity))
+    }
+
+    pub fn get(&self, key: u64) -> Option<&V> {
+        self.0.get(&key)
+    }
+
+    pub fn insert(&mut self, key: u64, value: V) -> bool {
+        self.0.insert(key, value).is_none()
+    }
+}
@@ -1,10 +1,78 @@
-mod noise;
+// Without the `std` feature the samplers only need `alloc`, their caches and the world generation
+// modules are left out. Without `alloc` only the single octave noises, the end islands and the
+// voronoi zoom remain.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+pub mod noise;
+#[cfg(feature = "alloc")]
+pub mod noise_trace;
+#[cfg(feature = "alloc")]
+pub mod noise_skip;
+#[cfg(feature = "alloc")]
+pub mod generator_sequence;
+#[cfg(feature = "std")]
+mod int_cache;
+#[cfg(feature = "alloc")]
 pub mod perlin_noise;
 pub mod simplex_noise;
+#[cfg(feature = "alloc")]
+pub mod perlin_simplex_noise;
+pub mod end_islands;
+#[cfg(feature = "alloc")]
+pub mod biome_source;
+#[cfg(feature = "alloc")]
+pub mod climate;
+#[cfg(feature = "alloc")]
+pub mod overworld_biome_builder;
+#[cfg(feature = "alloc")]
+pub mod structure_seed;
+#[cfg(feature = "alloc")]
+pub mod noise_constraints;
+#[cfg(feature = "alloc")]
+pub mod seed_reversal;
+#[cfg(feature = "alloc")]
+pub mod seed_search;
 pub mod math;
+#[cfg(feature = "alloc")]
 pub mod double_perlin_noise;
 pub mod voronoi;
+pub mod noise_settings;
+pub mod block;
+#[cfg(feature = "alloc")]
+pub mod surface_builder;
+#[cfg(feature = "alloc")]
+pub mod surface_rules;
+#[cfg(feature = "std")]
+pub mod noise_chunk_generator;
+pub mod random_source;
+#[cfg(feature = "std")]
+pub mod blended_noise;
+#[cfg(feature = "std")]
+pub mod density_function;
+#[cfg(feature = "std")]
+pub mod terrain_shaper;
+#[cfg(feature = "std")]
+pub mod aquifer;
+#[cfg(feature = "datapack")]
+pub mod datapack;
+#[cfg(feature = "scan")]
+pub mod seed_scan;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(any(feature = "python", feature = "wasm"))]
+mod bindings;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+#[cfg(feature = "alloc")]
 pub fn create_range(low: i32, high: i32) -> Vec<i32> {
     (low..=high).collect()
-}
\ No newline at end of file
+}
@@ -1,10 +1,11 @@
 #![allow(dead_code)]
 
-use std::cmp;
+use core::cmp;
 
+#[cfg(feature = "sha2")]
 use sha2::{Digest, Sha256};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "sha2"))]
 mod tests {
     use super::*;
 
@@ -21,7 +22,7 @@ pub fn lfloor(x: f64) -> i64 {
 
 pub fn modf(x: f64) -> (f64, f64) {
     // https://code.woboq.org/userspace/glibc/sysdeps/ieee754/dbl-64/wordsize-64/s_modf.c.html for proper
-    let i: f64 = x.floor();
+    let i: f64 = floor_f64(x);
     (i, x - i)
 }
 
@@ -58,14 +59,17 @@ pub fn sqr(d: f64) -> f64 {
     d * d
 }
 
+#[allow(clippy::needless_return)]
 pub fn wrap(x: f64) -> f64 {
     return x - (lfloor(x / 3.3554432E7 + 0.5) as f64) * 3.3554432E7;
 }
 
+#[allow(clippy::unnecessary_cast)]
 pub fn dot(g: [i32; 3], d: f64, d2: f64, d3: f64) -> f64 {
     (g[0 as usize]) as f64 * d + (g[1 as usize]) as f64 * d2 + (g[2 as usize]) as f64 * d3
 }
 
+#[allow(clippy::needless_return)]
 pub fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
     return match hash & 0xF {
         0x0 => x + y,
@@ -89,6 +93,7 @@ pub fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn lerp3(sx: f64, sy: f64, sz: f64, x1: f64, x2: f64, x3: f64, x4: f64, x5: f64, x6: f64, x7: f64, x8: f64) -> f64 {
     lerp1(sz, lerp2(sx, sy, x1, x2, x3, x4), lerp2(sx, sy, x5, x6, x7, x8))
 }
@@ -103,6 +108,16 @@ pub fn lerp1(t: f64, v0: f64, v1: f64) -> f64 {
     v0 + (v1 - v0) * t
 }
 
+pub fn clamped_lerp(lower: f64, upper: f64, slide: f64) -> f64 {
+    if slide < 0.0f64 {
+        return lower;
+    }
+    if slide > 1.0f64 {
+        return upper;
+    }
+    lerp1(slide, lower, upper)
+}
+
 pub fn lerp1_bis(t: f64, v0: f64, v1: f64) -> f64 {
     // Precise method, which guarantees v = v1 when t = 1. This method is monotonic only when v0 * v1 < 0. Lerping between same values might not produce the same value
     (1.0f64 - t) * v0 + t * v1
@@ -112,6 +127,7 @@ pub fn abs(a: f32) -> f32 {
     if a <= 0.0f32 { -a } else { a }
 }
 
+#[allow(clippy::needless_return)]
 pub fn clamp(f1: f32, f2: f32, f3: f32) -> f32 {
     if f1 < f2 {
         return f2;
@@ -131,14 +147,58 @@ pub fn floor_mod(x: i64, y: i64) -> i64 {
     modulo
 }
 
+#[allow(clippy::needless_return)]
+#[cfg(feature = "std")]
 pub fn sqrt(f: f32) -> f32 {
     return f.sqrt();
 }
 
+#[allow(clippy::needless_return)]
+#[cfg(not(feature = "std"))]
+pub fn sqrt(f: f32) -> f32 {
+    return libm::sqrtf(f);
+}
+
+/// `f64::floor`, from `libm` without std.
+#[cfg(feature = "std")]
+pub fn floor_f64(x: f64) -> f64 {
+    x.floor()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn floor_f64(x: f64) -> f64 {
+    libm::floor(x)
+}
+
+/// `f64::cos`, from `libm` without std.
+#[cfg(feature = "std")]
+pub fn cos_f64(x: f64) -> f64 {
+    x.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn cos_f64(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+/// `2^exponent`, exact over the octave range.
+#[cfg(feature = "std")]
+pub fn pow2(exponent: i32) -> f64 {
+    2f64.powi(exponent)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn pow2(exponent: i32) -> f64 {
+    libm::ldexp(1.0f64, exponent)
+}
+
 pub fn max(a: f32, b: f32) -> f32 {
     if a >= b { a } else { b }
 }
 
+#[allow(clippy::identity_op, clippy::needless_range_loop, clippy::unnecessary_cast)]
+/// The sha256 hash of the world seed the 1.15+ biome zoom works on, the seed `Voronoi::new` expects.
+#[cfg(feature = "sha2")]
 pub fn sha2long(mut seed: u64) -> u64 {
     let mut bytes: [u8; 8] = [0; 8];
     for i in 0..8 {
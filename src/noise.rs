@@ -1,16 +1,43 @@
+#[cfg(feature = "alloc")]
+use crate::noise_trace::{NoiseTrace, TraceKind};
+use crate::random_source::RandomSource;
+#[cfg(feature = "alloc")]
 use java_random::Random;
 use crate::math::{modf, lerp3, grad, smooth_step};
 use crate::math;
-use std::fmt;
+use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Noise {
     pub x0: f64,
     pub y0: f64,
     pub z0: f64,
+    #[cfg_attr(feature = "serde", serde(with = "permutations_serde"))]
     pub permutations: [u8; 256],
 }
 
+/// Serde only derives arrays up to 32 elements, the permutations go through a sequence.
+#[cfg(feature = "serde")]
+mod permutations_serde {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use alloc::vec::Vec;
+    use core::convert::TryInto;
+
+    pub fn serialize<S: Serializer>(permutations: &[u8; 256], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(permutations.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 256], D::Error> {
+        let permutations: Vec<u8> = Vec::deserialize(deserializer)?;
+        let length = permutations.len();
+        permutations.try_into().map_err(|_| D::Error::invalid_length(length, &"256 permutations"))
+    }
+}
+
 fn unsize<T>(x: &[T]) -> &[T] { x }
 
 impl fmt::Debug for Noise {
@@ -25,8 +52,10 @@ impl fmt::Debug for Noise {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod noise_test {
     use super::*;
+    use java_random::Random;
 
     #[test]
     fn test_gen_1() {
@@ -52,7 +81,8 @@ mod noise_test {
 }
 
 impl Noise {
-    pub fn new(random: &mut Random) -> Noise {
+    #[allow(clippy::manual_swap)]
+    pub fn new<R: RandomSource>(random: &mut R) -> Noise {
         let x0: f64 = random.next_double() * 256.0;
         let y0: f64 = random.next_double() * 256.0;
         let z0: f64 = random.next_double() * 256.0;
@@ -69,6 +99,15 @@ impl Noise {
         Noise { x0, y0, z0, permutations }
     }
 
+    /// `Noise::new`, recording its random calls in the trace.
+    #[cfg(feature = "alloc")]
+    pub fn new_traced(random: &mut Random, trace: &mut NoiseTrace) -> Noise {
+        let state_before = random.get_raw_seed();
+        let noise = Noise::new(random);
+        trace.record(TraceKind::Built, None, state_before, random.get_raw_seed());
+        noise
+    }
+
     pub fn get_noise_value(&self, x: f64, y: f64, z: f64, y_amplification: f64, min_y: f64) -> f64 {
         let offset_x: f64 = x + self.x0;
         let offset_y: f64 = y + self.y0;
@@ -78,7 +117,7 @@ impl Noise {
         let (integer_z, fractional_z) = modf(offset_z);
         let mut clamp_y: f64 = 0f64;
         if y_amplification != 0.0 {
-            clamp_y = (math::min(min_y, fractional_y) / y_amplification).floor() * y_amplification;
+            clamp_y = math::floor_f64(math::min(min_y, fractional_y) / y_amplification) * y_amplification;
         }
         self.sample_and_lerp(integer_x as i32,
                              integer_y as i32,
@@ -91,6 +130,7 @@ impl Noise {
                              smooth_step(fractional_z))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn sample_and_lerp(&self, int_x: i32, int_y: i32, int_z: i32, frac_x: f64, frac_y: f64, frac_z: f64, smooth_x: f64, smooth_y: f64, smooth_z: f64) -> f64 {
         let px_y = (self.lookup(int_x) as i32) + int_y;
         let px1_y = (self.lookup(int_x + 1) as i32) + int_y;
@@ -0,0 +1,629 @@
+#![allow(dead_code)]
+
+use java_random::{LCG, Random};
+
+use crate::block::Block;
+use crate::create_range;
+use crate::end_islands::EndIslands;
+use crate::math::{clamped_lerp, lerp1, wrap};
+use crate::noise_settings::NoiseGeneratorSettings;
+use crate::perlin_noise::PerlinNoise;
+use crate::perlin_simplex_noise::PerlinSimplexNoise;
+use crate::surface_builder::{SurfaceBiomeSource, SurfaceBuilders};
+
+#[cfg(feature = "const_fn")]
+const SKIP_2620: LCG = LCG::combine_java(2620);
+#[cfg(not(feature = "const_fn"))]
+const SKIP_2620: LCG = LCG { multiplier: 114882013573873, addend: 58792699608956 };
+
+/// Provides the depth and scale of the biome at noise (quart) coordinates.
+pub trait NoiseBiomeSource {
+    fn get_depth_and_scale(&mut self, x: i32, y: i32, z: i32) -> (f32, f32);
+}
+
+impl<F: FnMut(i32, i32, i32) -> (f32, f32)> NoiseBiomeSource for F {
+    fn get_depth_and_scale(&mut self, x: i32, y: i32, z: i32) -> (f32, f32) {
+        self(x, y, z)
+    }
+}
+
+/// Which blocks a height lookup stops at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeightmapType {
+    /// Any non air block, fluids below sea level included (`WORLD_SURFACE_WG`).
+    WorldSurface,
+    /// Solid blocks only (`OCEAN_FLOOR_WG`).
+    OceanFloor,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ColumnParameters {
+    depth: f64,
+    scale: f64,
+    random_density: f64,
+}
+
+/// Interpolated noise of a whole chunk, a block is solid where the density is positive.
+#[derive(Clone, Debug)]
+pub struct ChunkNoise {
+    height: i32,
+    density: Vec<f64>,
+    heightmap: [i32; 256],
+}
+
+impl ChunkNoise {
+    /// Density at chunk relative coordinates.
+    pub fn get_density(&self, x: i32, y: i32, z: i32) -> f64 {
+        self.density[((x * 16 + z) * self.height + y) as usize]
+    }
+
+    /// Y of the topmost solid block of the column at chunk relative coordinates, -1 if there is none.
+    pub fn get_height(&self, x: i32, z: i32) -> i32 {
+        self.heightmap[(x + z * 16) as usize]
+    }
+
+    pub fn get_heightmap(&self) -> &[i32; 256] {
+        &self.heightmap
+    }
+}
+
+/// The blocks of a chunk once the surface is built, bedrock aside.
+#[derive(Clone, Debug)]
+pub struct ChunkBlocks {
+    height: i32,
+    blocks: Vec<Block>,
+}
+
+impl ChunkBlocks {
+    /// Block at chunk relative coordinates, air out of the height range.
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> Block {
+        if y < 0 || y >= self.height {
+            return Block::Air;
+        }
+        self.blocks[((x * 16 + z) * self.height + y) as usize]
+    }
+
+    /// The column at chunk relative coordinates, indexed by y.
+    pub fn get_column(&self, x: i32, z: i32) -> &[Block] {
+        &self.blocks[((x * 16 + z) * self.height) as usize..((x * 16 + z + 1) * self.height) as usize]
+    }
+
+    /// Y of the topmost non air block of the column, -1 if there is none.
+    pub fn get_top(&self, x: i32, z: i32) -> i32 {
+        self.get_column(x, z).iter().rposition(|block| !block.is_air()).map_or(-1, |y| y as i32)
+    }
+
+    /// The topmost non air block of the column.
+    pub fn get_top_block(&self, x: i32, z: i32) -> Block {
+        self.get_block(x, self.get_top(x, z), z)
+    }
+}
+
+/// The noise behind the surface depth, simplex octaves in the overworld.
+#[derive(Clone, Debug)]
+enum SurfaceNoise {
+    Simplex(PerlinSimplexNoise),
+    Perlin(PerlinNoise),
+}
+
+/// The 1.16 noise terrain generator (`NoiseChunkGenerator`) without surface and structures.
+#[derive(Clone, Debug)]
+pub struct NoiseChunkGenerator {
+    settings: NoiseGeneratorSettings,
+    chunk_width: i32,
+    chunk_height: i32,
+    chunk_count_x: i32,
+    chunk_count_y: i32,
+    chunk_count_z: i32,
+    min_limit_perlin_noise: PerlinNoise,
+    max_limit_perlin_noise: PerlinNoise,
+    main_perlin_noise: PerlinNoise,
+    surface_noise: SurfaceNoise,
+    depth_noise: PerlinNoise,
+    /// The End islands replacing the biome depth and scale when the settings override them.
+    island_noise: Option<EndIslands>,
+    biome_weights: [f32; 25],
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod noise_chunk_generator_test {
+    use super::*;
+    use crate::surface_builder::{SurfaceBiome, SurfaceBuilderType, CONFIG_GRASS, CONFIG_HELL};
+
+    fn plains(_x: i32, _y: i32, _z: i32) -> (f32, f32) {
+        (0.125f32, 0.05f32)
+    }
+
+    #[test]
+    fn test_column_matches_chunk_corners() {
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let chunk = generator.generate_noise(3, -2, &mut plains);
+        let mut column = vec![0f64; 33];
+        generator.fill_noise_column(&mut column, 12, -8, &mut plains);
+        for cell_y in 0..32 {
+            assert_eq!(chunk.get_density(0, cell_y * 8, 0), column[cell_y as usize]);
+        }
+    }
+
+    #[test]
+    fn test_vanilla_columns() {
+        // 1.16.1 fillNoiseColumn, seed 1, plains everywhere
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let mut column = vec![0f64; 33];
+        generator.fill_noise_column(&mut column, 12, -8, &mut plains);
+        assert_eq!(column[..9], [1369.42004564543f64, 1203.6901068975646f64, 1039.2460090716163f64, 869.4187363809137f64, 704.332255208483f64, 539.2659127135843f64, 376.00057055240893f64, 201.6108113286235f64, 39.857133340214666f64]);
+        assert_eq!(column[29..], [-865.8446040253297f64, -607.0861617577345f64, -310.9704884005978f64, -10.0f64]);
+        generator.fill_noise_column(&mut column, -30, 41, &mut plains);
+        assert_eq!(column[6..11], [341.9416246087062f64, 181.09185353599366f64, 12.696810747330016f64, -38.833197349997874f64, -81.72693701548718f64]);
+        assert_eq!(column[31], -325.7564100897649f64);
+    }
+
+    #[test]
+    fn test_vanilla_heights() {
+        // 1.16.1 getBaseHeight minus one, seed 1, plains everywhere
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let heights = [((0, 0), 64), ((1, 2), 65), ((3, 3), 65), ((5, -7), 65), ((-9, 14), 64), ((100, -37), 73), ((-250, 611), 62), ((1234, -4321), 67)];
+        for &((x, z), height) in heights.iter() {
+            assert_eq!(generator.get_height(x, z, HeightmapType::OceanFloor, &mut plains), height);
+            assert_eq!(generator.get_height(x, z, HeightmapType::WorldSurface, &mut plains), height);
+        }
+    }
+
+    #[test]
+    fn test_heightmap_matches_density() {
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let chunk = generator.generate_noise(0, 0, &mut plains);
+        for x in 0..16 {
+            for z in 0..16 {
+                let height = chunk.get_height(x, z);
+                assert!(height > 40 && height < 100);
+                assert!(chunk.get_density(x, height, z) > 0.0);
+                for y in height + 1..256 {
+                    assert!(chunk.get_density(x, y, z) <= 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_height_matches_chunk() {
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let chunk = generator.generate_noise(-1, 2, &mut plains);
+        for x in 0..16 {
+            for z in 0..16 {
+                let height = generator.get_height(x - 16, z + 32, HeightmapType::OceanFloor, &mut plains);
+                assert_eq!(height, chunk.get_height(x, z));
+                let surface = generator.get_height(x - 16, z + 32, HeightmapType::WorldSurface, &mut plains);
+                assert_eq!(surface, height.max(62));
+            }
+        }
+    }
+
+    #[test]
+    fn test_nether_slides() {
+        let nether_biomes = &mut |_: i32, _: i32, _: i32| (0.1f32, 0.2f32);
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::nether());
+        let mut column = vec![0f64; 17];
+        generator.fill_noise_column(&mut column, 0, 0, nether_biomes);
+        assert_eq!(column[16], 120.0f64);
+        let chunk = generator.generate_noise(0, 0, nether_biomes);
+        assert!(chunk.get_density(0, 0, 0) > 0.0 && chunk.get_density(0, 127, 0) > 0.0);
+        assert!(chunk.get_density(0, 64, 0) <= 0.0);
+        // the heightmap stops at the ceiling
+        assert_eq!(chunk.get_height(0, 0), 127);
+    }
+
+    #[test]
+    fn test_end_islands() {
+        let no_biomes = &mut |_: i32, _: i32, _: i32| -> (f32, f32) { unreachable!() };
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::end());
+        assert_eq!(generator.get_height(0, 0, HeightmapType::OceanFloor, no_biomes), 60);
+        assert_eq!(generator.get_height(100, 0, HeightmapType::OceanFloor, no_biomes), 56);
+        assert_eq!(generator.get_height(1000, 0, HeightmapType::WorldSurface, no_biomes), -1);
+        let chunk = generator.generate_noise(0, 0, no_biomes);
+        assert_eq!(chunk.get_height(15, 15), 60);
+    }
+
+    #[test]
+    fn test_surface_noise() {
+        let generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let mut random = Random::with_seed(1);
+        for octaves in [create_range(-15, 0), create_range(-15, 0), create_range(-7, 0)] {
+            PerlinNoise::new(&mut random, octaves);
+        }
+        let surface_noise = PerlinSimplexNoise::new(&mut random, create_range(-3, 0));
+        assert_eq!(generator.get_surface_noise(20, -5), surface_noise.get_surface_noise_value(1.25f64, -0.3125f64) * 15.0f64);
+
+        // the nether has perlin octaves, their y limit is the x inside the chunk
+        let generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::nether());
+        let mut random = Random::with_seed(1);
+        for octaves in [create_range(-15, 0), create_range(-15, 0), create_range(-7, 0)] {
+            PerlinNoise::new(&mut random, octaves);
+        }
+        let surface_noise = PerlinNoise::new(&mut random, create_range(-3, 0));
+        assert_eq!(generator.get_surface_noise(20, -5), surface_noise.sample_surface(1.25f64, -0.3125f64, 0.0625f64, 0.25f64) * 15.0f64);
+    }
+
+    #[test]
+    fn test_build_surface() {
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let noise = generator.generate_noise(0, 0, &mut plains);
+        let builders = SurfaceBuilders::new(1);
+        let plains_surface = SurfaceBiome::new(SurfaceBuilderType::Default, CONFIG_GRASS, 0.8f32);
+        let chunk = generator.build_surface(0, 0, &noise, &builders, Block::Stone, Block::Water, &mut |_, _, _| plains_surface);
+        for x in 0..16 {
+            for z in 0..16 {
+                let top = chunk.get_top(x, z);
+                assert_eq!(top, noise.get_height(x, z));
+                assert_eq!((chunk.get_block(x, top - 1, z), chunk.get_top_block(x, z)), (Block::Dirt, Block::GrassBlock));
+            }
+        }
+        // synthetic: vanilla runs its nether builders here, which are not implemented, so the
+        // default builder only checks the nether heights and lava sea
+        let mut nether = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::nether());
+        let nether_surface = SurfaceBiome::new(SurfaceBuilderType::Default, CONFIG_HELL, 2.0f32);
+        let noise = nether.generate_noise(0, 0, &mut |_, _, _| (0.1f32, 0.2f32));
+        let chunk = nether.build_surface(0, 0, &noise, &builders, Block::Netherrack, Block::Lava, &mut |_, _, _| nether_surface);
+        let column = chunk.get_column(0, 0);
+        assert_eq!((column[0], column[31], column[64], column[127]), (Block::Netherrack, Block::Lava, Block::Air, Block::Netherrack));
+    }
+
+    #[test]
+    fn test_height_approximate_vanilla() {
+        // 1.16.1 getBaseHeight minus one at the closest cell corner, seed 1, plains everywhere
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        let heights = [((1, 2), 65), ((3, 3), 65), ((5, -7), 64), ((-9, 14), 64), ((100, -37), 73), ((-250, 611), 62), ((1234, -4321), 68)];
+        for &((x, z), height) in heights.iter() {
+            assert_eq!(generator.get_height_approximate(x, z, HeightmapType::OceanFloor, &mut plains), height);
+        }
+        for x in -16..16 {
+            for z in -16..16 {
+                let exact = generator.get_height(x, z, HeightmapType::OceanFloor, &mut plains);
+                let approximate = generator.get_height_approximate(x, z, HeightmapType::OceanFloor, &mut plains);
+                assert!((exact - approximate).abs() <= 4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_height_approximate_on_corners() {
+        let mut generator = NoiseChunkGenerator::new(1, NoiseGeneratorSettings::overworld(false));
+        for x in -4..4 {
+            for z in -4..4 {
+                let exact = generator.get_height(x * 4, z * 4, HeightmapType::OceanFloor, &mut plains);
+                assert_eq!(generator.get_height_approximate(x * 4, z * 4, HeightmapType::OceanFloor, &mut plains), exact);
+            }
+        }
+    }
+}
+
+impl NoiseChunkGenerator {
+    pub fn new(seed: u64, settings: NoiseGeneratorSettings) -> Self {
+        let noise_settings = settings.noise_settings;
+        let chunk_height = noise_settings.noise_size_vertical * 4;
+        let chunk_width = noise_settings.noise_size_horizontal * 4;
+        let mut random = Random::with_seed(seed);
+        let min_limit_perlin_noise = PerlinNoise::new(&mut random, create_range(-15, 0));
+        let max_limit_perlin_noise = PerlinNoise::new(&mut random, create_range(-15, 0));
+        let main_perlin_noise = PerlinNoise::new(&mut random, create_range(-7, 0));
+        let surface_noise = if noise_settings.use_simplex_surface_noise {
+            SurfaceNoise::Simplex(PerlinSimplexNoise::new(&mut random, create_range(-3, 0)))
+        } else {
+            SurfaceNoise::Perlin(PerlinNoise::new(&mut random, create_range(-3, 0)))
+        };
+        random.advance(SKIP_2620);
+        let depth_noise = PerlinNoise::new(&mut random, create_range(-15, 0));
+        let island_noise = if noise_settings.island_noise_override { Some(EndIslands::new(seed)) } else { None };
+        let mut biome_weights = [0f32; 25];
+        for i in -2i32..=2 {
+            for j in -2i32..=2 {
+                biome_weights[(i + 2 + (j + 2) * 5) as usize] = 10.0f32 / (((i * i + j * j) as f32 + 0.2f32) as f64).sqrt() as f32;
+            }
+        }
+        NoiseChunkGenerator {
+            settings,
+            chunk_width,
+            chunk_height,
+            chunk_count_x: 16 / chunk_width,
+            chunk_count_y: noise_settings.height / chunk_height,
+            chunk_count_z: 16 / chunk_width,
+            min_limit_perlin_noise,
+            max_limit_perlin_noise,
+            main_perlin_noise,
+            surface_noise,
+            depth_noise,
+            island_noise,
+            biome_weights,
+        }
+    }
+
+    pub fn get_settings(&self) -> &NoiseGeneratorSettings {
+        &self.settings
+    }
+
+    pub fn get_sea_level(&self) -> i32 {
+        self.settings.sea_level
+    }
+
+    /// Vanilla `sampleAndClampNoise`, which divides each octave by its frequency factor where
+    /// [`PerlinNoise::sample`] weights it by the normalized amplitude, so going through `sample`
+    /// would round the density differently.
+    #[allow(clippy::too_many_arguments)]
+    fn sample_and_clamp_noise(&self, x: i32, y: i32, z: i32, xz_scale: f64, y_scale: f64, xz_factor: f64, y_factor: f64) -> f64 {
+        let mut min_limit: f64 = 0.0f64;
+        let mut max_limit: f64 = 0.0f64;
+        let mut main: f64 = 0.0f64;
+        let mut octave_factor: f64 = 1.0f64;
+        for i in 0..16usize {
+            let sample_x: f64 = wrap(x as f64 * xz_scale * octave_factor);
+            let sample_y: f64 = wrap(y as f64 * y_scale * octave_factor);
+            let sample_z: f64 = wrap(z as f64 * xz_scale * octave_factor);
+            let y_amplification: f64 = y_scale * octave_factor;
+            if let Some(noise) = self.min_limit_perlin_noise.get_octave(i) {
+                min_limit += noise.get_noise_value(sample_x, sample_y, sample_z, y_amplification, y as f64 * y_amplification) / octave_factor;
+            }
+            if let Some(noise) = self.max_limit_perlin_noise.get_octave(i) {
+                max_limit += noise.get_noise_value(sample_x, sample_y, sample_z, y_amplification, y as f64 * y_amplification) / octave_factor;
+            }
+            if i < 8 {
+                if let Some(noise) = self.main_perlin_noise.get_octave(i) {
+                    main += noise.get_noise_value(
+                        wrap(x as f64 * xz_factor * octave_factor),
+                        wrap(y as f64 * y_factor * octave_factor),
+                        wrap(z as f64 * xz_factor * octave_factor),
+                        y_factor * octave_factor,
+                        y as f64 * y_factor * octave_factor) / octave_factor;
+                }
+            }
+            octave_factor /= 2.0f64;
+        }
+        clamped_lerp(min_limit / 512.0f64, max_limit / 512.0f64, (main / 10.0f64 + 1.0f64) / 2.0f64)
+    }
+
+    fn get_random_density(&self, x: i32, z: i32) -> f64 {
+        let noise: f64 = self.depth_noise.sample((x * 200) as f64, 10.0f64, (z * 200) as f64, 1.0f64, 0.0f64, true);
+        let density: f64 = if noise < 0.0f64 { -noise * 0.3f64 } else { noise };
+        let density: f64 = density * 24.575625f64 - 2.0f64;
+        if density < 0.0f64 {
+            density * 0.009486607142857142f64
+        } else {
+            density.min(1.0f64) * 0.006640625f64
+        }
+    }
+
+    fn get_column_parameters<B: NoiseBiomeSource>(&mut self, x: i32, z: i32, biomes: &mut B) -> ColumnParameters {
+        let noise_settings = self.settings.noise_settings;
+        let sea_level = self.settings.sea_level;
+        let random_density: f64 = if noise_settings.random_density_offset { self.get_random_density(x, z) } else { 0.0f64 };
+        if let Some(island_noise) = self.island_noise.as_mut() {
+            let depth: f64 = (island_noise.get_height_value(x, z) - 8.0f32) as f64;
+            return ColumnParameters {
+                depth,
+                scale: if depth > 0.0f64 { 0.25f64 } else { 1.0f64 },
+                random_density,
+            };
+        }
+        let mut weighted_scale: f32 = 0.0f32;
+        let mut weighted_depth: f32 = 0.0f32;
+        let mut total_weight: f32 = 0.0f32;
+        let (center_depth, _) = biomes.get_depth_and_scale(x, sea_level, z);
+        for k in -2..=2 {
+            for l in -2..=2 {
+                let (depth, scale) = biomes.get_depth_and_scale(x + k, sea_level, z + l);
+                let (depth_amplified, scale_amplified) = if noise_settings.is_amplified && depth > 0.0f32 {
+                    (1.0f32 + depth * 2.0f32, 1.0f32 + scale * 4.0f32)
+                } else {
+                    (depth, scale)
+                };
+                let factor: f32 = if depth > center_depth { 0.5f32 } else { 1.0f32 };
+                let weight: f32 = factor * self.biome_weights[(k + 2 + (l + 2) * 5) as usize] / (depth_amplified + 2.0f32);
+                weighted_scale += scale_amplified * weight;
+                weighted_depth += depth_amplified * weight;
+                total_weight += weight;
+            }
+        }
+        let depth: f32 = weighted_depth / total_weight;
+        let scale: f32 = weighted_scale / total_weight;
+        ColumnParameters {
+            depth: (depth * 0.5f32 - 0.125f32) as f64 * 0.265625f64,
+            scale: 96.0f64 / (scale * 0.9f32 + 0.1f32) as f64,
+            random_density,
+        }
+    }
+
+    fn sample_noise_cell(&self, x: i32, y: i32, z: i32, parameters: &ColumnParameters) -> f64 {
+        let noise_settings = self.settings.noise_settings;
+        let sampling = noise_settings.noise_sampling_settings;
+        let xz_scale: f64 = 684.412f64 * sampling.xz_scale;
+        let y_scale: f64 = 684.412f64 * sampling.y_scale;
+        let xz_factor: f64 = xz_scale / sampling.xz_factor;
+        let y_factor: f64 = y_scale / sampling.y_factor;
+        let top_slide = noise_settings.top_slide_settings;
+        let bottom_slide = noise_settings.bottom_slide_settings;
+        let mut noise: f64 = self.sample_and_clamp_noise(x, y, z, xz_scale, y_scale, xz_factor, y_factor);
+        let falloff: f64 = 1.0f64 - y as f64 * 2.0f64 / self.chunk_count_y as f64 + parameters.random_density;
+        let falloff: f64 = falloff * noise_settings.density_factor + noise_settings.density_offset;
+        let falloff: f64 = (falloff + parameters.depth) * parameters.scale;
+        if falloff > 0.0f64 {
+            noise += falloff * 4.0f64;
+        } else {
+            noise += falloff;
+        }
+        if top_slide.size > 0 {
+            noise = clamped_lerp(top_slide.target as f64, noise, ((self.chunk_count_y - y) as f64 - top_slide.offset as f64) / top_slide.size as f64);
+        }
+        if bottom_slide.size > 0 {
+            noise = clamped_lerp(bottom_slide.target as f64, noise, (y as f64 - bottom_slide.offset as f64) / bottom_slide.size as f64);
+        }
+        noise
+    }
+
+    /// Fills the `chunk_count_y + 1` noise values of the column at noise coordinates `x`, `z`.
+    pub fn fill_noise_column<B: NoiseBiomeSource>(&mut self, column: &mut [f64], x: i32, z: i32, biomes: &mut B) {
+        let parameters = self.get_column_parameters(x, z, biomes);
+        for y in 0..=self.chunk_count_y {
+            column[y as usize] = self.sample_noise_cell(x, y, z, &parameters);
+        }
+    }
+
+    fn is_match(&self, density: f64, y: i32, heightmap: HeightmapType) -> bool {
+        density > 0.0f64 || (heightmap == HeightmapType::WorldSurface && y < self.settings.sea_level)
+    }
+
+    /// Y of the first block from the top of the column matching the heightmap, -1 if there is none.
+    ///
+    /// Same result as vanilla `getBaseHeight` minus one, but the noise cells of the four surrounding
+    /// columns are only sampled down to the surface.
+    pub fn get_height<B: NoiseBiomeSource>(&mut self, x: i32, z: i32, heightmap: HeightmapType, biomes: &mut B) -> i32 {
+        let cell_x = x.div_euclid(self.chunk_width);
+        let cell_z = z.div_euclid(self.chunk_width);
+        let x_frac: f64 = x.rem_euclid(self.chunk_width) as f64 / self.chunk_width as f64;
+        let z_frac: f64 = z.rem_euclid(self.chunk_width) as f64 / self.chunk_width as f64;
+        let corners = [(cell_x, cell_z), (cell_x + 1, cell_z), (cell_x, cell_z + 1), (cell_x + 1, cell_z + 1)];
+        let mut parameters = [ColumnParameters::default(); 4];
+        let mut upper = [0f64; 4];
+        for (i, &(corner_x, corner_z)) in corners.iter().enumerate() {
+            parameters[i] = self.get_column_parameters(corner_x, corner_z, biomes);
+            upper[i] = self.sample_noise_cell(corner_x, self.chunk_count_y, corner_z, &parameters[i]);
+        }
+        for cell_y in (0..self.chunk_count_y).rev() {
+            let mut lower = [0f64; 4];
+            for (i, &(corner_x, corner_z)) in corners.iter().enumerate() {
+                lower[i] = self.sample_noise_cell(corner_x, cell_y, corner_z, &parameters[i]);
+            }
+            for dy in (0..self.chunk_height).rev() {
+                let y_frac: f64 = dy as f64 / self.chunk_height as f64;
+                let z0: f64 = lerp1(x_frac, lerp1(y_frac, lower[0], upper[0]), lerp1(y_frac, lower[1], upper[1]));
+                let z1: f64 = lerp1(x_frac, lerp1(y_frac, lower[2], upper[2]), lerp1(y_frac, lower[3], upper[3]));
+                let y = cell_y * self.chunk_height + dy;
+                if self.is_match(lerp1(z_frac, z0, z1), y, heightmap) {
+                    return y;
+                }
+            }
+            upper = lower;
+        }
+        -1
+    }
+
+    /// Cheaper [`get_height`](Self::get_height) only sampling the noise column closest to `x`, `z`,
+    /// the result is exact on the noise cell corners and follows the closest corner in between.
+    ///
+    /// The error is how much the terrain moves over the at most two blocks to that corner, within
+    /// 4 blocks over gentle terrain such as plains but as large as the cliff next to a steep one.
+    pub fn get_height_approximate<B: NoiseBiomeSource>(&mut self, x: i32, z: i32, heightmap: HeightmapType, biomes: &mut B) -> i32 {
+        let cell_x = (x + self.chunk_width / 2).div_euclid(self.chunk_width);
+        let cell_z = (z + self.chunk_width / 2).div_euclid(self.chunk_width);
+        let parameters = self.get_column_parameters(cell_x, cell_z, biomes);
+        let mut upper: f64 = self.sample_noise_cell(cell_x, self.chunk_count_y, cell_z, &parameters);
+        for cell_y in (0..self.chunk_count_y).rev() {
+            let lower: f64 = self.sample_noise_cell(cell_x, cell_y, cell_z, &parameters);
+            for dy in (0..self.chunk_height).rev() {
+                let y = cell_y * self.chunk_height + dy;
+                if self.is_match(lerp1(dy as f64 / self.chunk_height as f64, lower, upper), y, heightmap) {
+                    return y;
+                }
+            }
+            upper = lower;
+        }
+        -1
+    }
+
+    /// The surface depth noise at block coordinates, as vanilla passes it to the surface builders:
+    /// about -15 to 15, the default builder covering the stone `noise / 3 + 3` blocks deep.
+    pub fn get_surface_noise(&self, x: i32, z: i32) -> f64 {
+        let noise: f64 = match &self.surface_noise {
+            SurfaceNoise::Simplex(noise) => noise.get_surface_noise_value(x as f64 * 0.0625f64, z as f64 * 0.0625f64),
+            SurfaceNoise::Perlin(noise) => noise.sample_surface(x as f64 * 0.0625f64, z as f64 * 0.0625f64, 0.0625f64, (x & 15) as f64 * 0.0625f64),
+        };
+        noise * 15.0f64
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Places the default block and fluid of a noise chunk then runs the surface builder of the
+    /// biome of each column, the biome being looked up right above the column.
+    pub fn build_surface<B: SurfaceBiomeSource>(&self, chunk_x: i32, chunk_z: i32, noise: &ChunkNoise, builders: &SurfaceBuilders, default_block: Block, default_fluid: Block, biomes: &mut B) -> ChunkBlocks {
+        let height = noise.height;
+        let sea_level = self.settings.sea_level;
+        let blocks: Vec<Block> = noise.density.iter().enumerate().map(|(i, &density)| {
+            if density > 0.0f64 {
+                default_block
+            } else if (i as i32 % height) < sea_level {
+                default_fluid
+            } else {
+                Block::Air
+            }
+        }).collect();
+        let mut chunk = ChunkBlocks { height, blocks };
+        // setBaseChunkSeed
+        let mut random = Random::with_seed((chunk_x as i64).wrapping_mul(341873128712i64).wrapping_add((chunk_z as i64).wrapping_mul(132897987541i64)) as u64);
+        for x in 0..16 {
+            for z in 0..16 {
+                let block_x = chunk_x * 16 + x;
+                let block_z = chunk_z * 16 + z;
+                let top = chunk.get_top(x, z) + 1;
+                let surface_noise = self.get_surface_noise(block_x, block_z);
+                let biome = biomes.get_surface_biome(block_x, top, block_z);
+                let start = ((x * 16 + z) * height) as usize;
+                let column = &mut chunk.blocks[start..start + height as usize];
+                builders.build_surface(&mut random, column, &biome, block_x, block_z, top, surface_noise, default_block, default_fluid, sea_level);
+            }
+        }
+        chunk
+    }
+
+    /// Samples the noise columns of a chunk and interpolates them over the 4x8x4 cells.
+    pub fn generate_noise<B: NoiseBiomeSource>(&mut self, chunk_x: i32, chunk_z: i32, biomes: &mut B) -> ChunkNoise {
+        let column_size = (self.chunk_count_y + 1) as usize;
+        let mut columns: Vec<Vec<f64>> = Vec::with_capacity(((self.chunk_count_x + 1) * (self.chunk_count_z + 1)) as usize);
+        for cell_x in 0..=self.chunk_count_x {
+            for cell_z in 0..=self.chunk_count_z {
+                let mut column = vec![0f64; column_size];
+                self.fill_noise_column(&mut column, chunk_x * self.chunk_count_x + cell_x, chunk_z * self.chunk_count_z + cell_z, biomes);
+                columns.push(column);
+            }
+        }
+        let height = self.settings.noise_settings.height;
+        let mut density = vec![0f64; (16 * 16 * height) as usize];
+        let column_index = |cell_x: i32, cell_z: i32| (cell_x * (self.chunk_count_z + 1) + cell_z) as usize;
+        for cell_x in 0..self.chunk_count_x {
+            for cell_z in 0..self.chunk_count_z {
+                let c00 = &columns[column_index(cell_x, cell_z)];
+                let c01 = &columns[column_index(cell_x, cell_z + 1)];
+                let c10 = &columns[column_index(cell_x + 1, cell_z)];
+                let c11 = &columns[column_index(cell_x + 1, cell_z + 1)];
+                for cell_y in 0..self.chunk_count_y as usize {
+                    for dy in 0..self.chunk_height {
+                        let y_frac: f64 = dy as f64 / self.chunk_height as f64;
+                        let x0z0: f64 = lerp1(y_frac, c00[cell_y], c00[cell_y + 1]);
+                        let x1z0: f64 = lerp1(y_frac, c10[cell_y], c10[cell_y + 1]);
+                        let x0z1: f64 = lerp1(y_frac, c01[cell_y], c01[cell_y + 1]);
+                        let x1z1: f64 = lerp1(y_frac, c11[cell_y], c11[cell_y + 1]);
+                        let y = cell_y as i32 * self.chunk_height + dy;
+                        for dx in 0..self.chunk_width {
+                            let x_frac: f64 = dx as f64 / self.chunk_width as f64;
+                            let z0: f64 = lerp1(x_frac, x0z0, x1z0);
+                            let z1: f64 = lerp1(x_frac, x0z1, x1z1);
+                            let x = cell_x * self.chunk_width + dx;
+                            for dz in 0..self.chunk_width {
+                                let z_frac: f64 = dz as f64 / self.chunk_width as f64;
+                                let z = cell_z * self.chunk_width + dz;
+                                density[((x * 16 + z) * height + y) as usize] = lerp1(z_frac, z0, z1);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let mut heightmap = [-1i32; 256];
+        for x in 0..16 {
+            for z in 0..16 {
+                let column = &density[((x * 16 + z) * height) as usize..((x * 16 + z + 1) * height) as usize];
+                if let Some(y) = column.iter().rposition(|&value| value > 0.0f64) {
+                    heightmap[(x + z * 16) as usize] = y as i32;
+                }
+            }
+        }
+        ChunkNoise { height, density, heightmap }
+    }
+}
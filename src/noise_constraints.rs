@@ -0,0 +1,602 @@
+//! Narrows down the offsets and permutations of a [`Noise`] from observed values.
+//!
+//! Each permutation index keeps the set of values it can still take and each offset an interval.
+//! [`NoiseConstraints::get_bounds`] gives sound bounds of what `Noise::sample_and_lerp` can return
+//! at a position under these domains, following the `lookup` chains through the sets of possible
+//! values and bounding the `grad` and lerps with interval arithmetic. A cell at a known position
+//! whose chains only go through fixed entries is sampled by `sample_and_lerp` itself.
+//! [`NoiseConstraints::propagate`] tries every possible value of the permutation entries an
+//! observation reaches and drops those the bounds rule out, together with the values another
+//! index is known to hold, until nothing changes. The offsets are narrowed by
+//! [`NoiseConstraints::refine_offsets`].
+//!
+//! The observations are values of a single octave, without y amplification: a `PerlinNoise`
+//! sample is split into octaves by the caller, dividing the coordinates and the value by the
+//! octave's frequency and amplitude.
+
+use core::fmt;
+
+use crate::math::{grad, lerp1, smooth_step};
+use crate::noise::Noise;
+
+/// How far a computed value may stray from the exact one through rounding.
+const BOUND_EPSILON: f64 = 1e-9;
+
+/// Every noise value lies in there, the lerps staying between corner gradients of at most 2.
+const FULL_BOUNDS: Interval = Interval { min: -2.0f64, max: 2.0f64 };
+
+/// A closed interval of reals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Interval {
+    pub fn new(min: f64, max: f64) -> Self {
+        Interval { min, max }
+    }
+
+    pub fn point(value: f64) -> Self {
+        Interval { min: value, max: value }
+    }
+
+    pub fn get_width(&self) -> f64 {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    pub fn intersects(&self, other: &Interval) -> bool {
+        self.min <= other.max + BOUND_EPSILON && other.min <= self.max + BOUND_EPSILON
+    }
+
+    fn hull(&self, other: &Interval) -> Interval {
+        Interval { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+}
+
+/// A set of byte values, the domain of a permutation entry.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValueSet([u64; 4]);
+
+impl ValueSet {
+    pub fn empty() -> Self {
+        ValueSet([0; 4])
+    }
+
+    pub fn full() -> Self {
+        ValueSet([u64::MAX; 4])
+    }
+
+    pub fn single(value: u8) -> Self {
+        let mut set = ValueSet::empty();
+        set.insert(value);
+        set
+    }
+
+    pub fn contains(&self, value: u8) -> bool {
+        self.0[(value >> 6) as usize] >> (value & 63) & 1 == 1
+    }
+
+    pub fn insert(&mut self, value: u8) {
+        self.0[(value >> 6) as usize] |= 1u64 << (value & 63);
+    }
+
+    pub fn remove(&mut self, value: u8) {
+        self.0[(value >> 6) as usize] &= !(1u64 << (value & 63));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.0 == [u64::MAX; 4]
+    }
+
+    /// The value of a single valued set.
+    pub fn get_single(&self) -> Option<u8> {
+        if self.len() == 1 {
+            return self.iter().next();
+        }
+        None
+    }
+
+    pub fn union(&self, other: &ValueSet) -> ValueSet {
+        ValueSet([self.0[0] | other.0[0], self.0[1] | other.0[1], self.0[2] | other.0[2], self.0[3] | other.0[3]])
+    }
+
+    /// The values `(v + offset) & 0xff` of the values `v` of the set.
+    pub fn shifted(&self, offset: i32) -> ValueSet {
+        if self.is_full() {
+            return *self;
+        }
+        let mut set = ValueSet::empty();
+        for value in self.iter() {
+            set.insert((value as i32 + offset) as u8);
+        }
+        set
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=255u8).filter(move |&value| self.contains(value))
+    }
+}
+
+impl fmt::Debug for ValueSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// A noise value known to lie between `min` and `max` at `x`, `y`, `z`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observation {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub value: Interval,
+}
+
+impl Observation {
+    pub fn new(x: f64, y: f64, z: f64, value: f64, tolerance: f64) -> Self {
+        Observation { x, y, z, value: Interval::new(value - tolerance, value + tolerance) }
+    }
+
+    pub fn between(x: f64, y: f64, z: f64, min: f64, max: f64) -> Self {
+        Observation { x, y, z, value: Interval::new(min, max) }
+    }
+
+    /// The noise is known to be positive there, a solid block or one side of a biome border.
+    pub fn positive(x: f64, y: f64, z: f64) -> Self {
+        Observation::between(x, y, z, 0.0f64, f64::INFINITY)
+    }
+
+    pub fn negative(x: f64, y: f64, z: f64) -> Self {
+        Observation::between(x, y, z, f64::NEG_INFINITY, 0.0f64)
+    }
+}
+
+/// No noise satisfies the observations within the domains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contradiction;
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the observations contradict the noise constraints")
+    }
+}
+
+/// The domains of the offsets and permutation entries of a noise.
+#[derive(Clone, Debug)]
+pub struct NoiseConstraints {
+    offsets: [Interval; 3],
+    permutations: [ValueSet; 256],
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod noise_constraints_test {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use java_random::Random;
+
+    fn observe(noise: &Noise, positions: &[(f64, f64, f64)]) -> Vec<Observation> {
+        positions.iter().map(|&(x, y, z)| Observation::new(x, y, z, noise.get_noise_value(x, y, z, 0.0f64, 0.0f64), 1e-12f64)).collect()
+    }
+
+    fn with_known_offsets(noise: &Noise) -> NoiseConstraints {
+        NoiseConstraints::with_offsets(Interval::point(noise.x0), Interval::point(noise.y0), Interval::point(noise.z0))
+    }
+
+    #[test]
+    fn test_value_set() {
+        let mut set = ValueSet::single(255);
+        set.insert(3);
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.shifted(2).iter().collect::<Vec<u8>>(), vec![1, 5]);
+        set.remove(255);
+        assert_eq!(set.get_single(), Some(3));
+        assert!(ValueSet::full().shifted(17).is_full());
+    }
+
+    #[test]
+    fn test_exact_bounds() {
+        let noise = Noise::new(&mut Random::with_seed(1));
+        let constraints = NoiseConstraints::from_noise(&noise);
+        for observation in observe(&noise, &[(0.0f64, 0.0f64, 0.0f64), (12.3f64, -4.5f64, 100.25f64)]) {
+            let bounds = constraints.get_bounds(&observation);
+            assert!(bounds.get_width() < 1e-12f64);
+            assert!((bounds.min - observation.value.min).abs() < 1e-9f64);
+        }
+        assert!(constraints.is_consistent_with(&noise));
+        assert!(!constraints.is_consistent_with(&Noise::new(&mut Random::with_seed(2))));
+    }
+
+    #[test]
+    fn test_propagate() {
+        let noise = Noise::new(&mut Random::with_seed(42));
+        let positions: Vec<(f64, f64, f64)> = (0..40).map(|i| (i as f64 * 0.37f64, (i % 7) as f64 * 0.61f64, (i % 5) as f64 * 0.83f64)).collect();
+        // the permutation is known but for 24 entries, which could hold any of the 24 missing values
+        let mut constraints = with_known_offsets(&noise);
+        for index in 24..256 {
+            constraints.fix(index as u8, noise.permutations[index]).unwrap();
+        }
+        let unknown: usize = (0..24).map(|index| constraints.get_possible_values(index).len()).sum();
+        assert_eq!(unknown, 24 * 24);
+        constraints.propagate(&observe(&noise, &positions)).unwrap();
+        assert!(constraints.is_consistent_with(&noise));
+        assert!((0..24).map(|index| constraints.get_possible_values(index).len()).sum::<usize>() < unknown);
+    }
+
+    #[test]
+    fn test_contradiction() {
+        let noise = Noise::new(&mut Random::with_seed(42));
+        let mut constraints = with_known_offsets(&noise);
+        assert_eq!(constraints.propagate(&[Observation::new(0.5f64, 0.5f64, 0.5f64, 3.0f64, 0.1f64)]), Err(Contradiction));
+        let mut constraints = NoiseConstraints::new();
+        constraints.fix(0, 7).unwrap();
+        assert_eq!(constraints.fix(1, 7), Err(Contradiction));
+    }
+
+    #[test]
+    fn test_unknown_noise() {
+        let noise = Noise::new(&mut Random::with_seed(3));
+        let observations = observe(&noise, &[(0.5f64, 0.25f64, 0.75f64), (3.5f64, 1.0f64, -2.25f64)]);
+        let mut constraints = NoiseConstraints::new();
+        assert_eq!(constraints.get_bounds(&observations[0]), Interval::new(-2.0f64, 2.0f64));
+        constraints.propagate(&observations).unwrap();
+        constraints.refine_offsets(&observations, 8).unwrap();
+        assert!(constraints.is_consistent_with(&noise));
+        assert_eq!(constraints.get_offset(0), Interval::new(0.0f64, 256.0f64));
+        assert_eq!(constraints.get_fixed_count(), 0);
+    }
+
+    #[test]
+    fn test_refine_offsets() {
+        let noise = Noise::new(&mut Random::with_seed(7));
+        let mut constraints = NoiseConstraints::from_noise(&noise);
+        constraints.restrict_offset(0, Interval::new(noise.x0 - 0.5f64, noise.x0 + 0.5f64)).unwrap();
+        let positions: Vec<(f64, f64, f64)> = (0..16).map(|i| (i as f64 * 0.5f64, 0.25f64, i as f64 * 0.125f64)).collect();
+        constraints.refine_offsets(&observe(&noise, &positions), 64).unwrap();
+        let x0 = constraints.get_offset(0);
+        assert!(x0.contains(noise.x0));
+        assert!(x0.get_width() < 0.1f64);
+    }
+}
+
+impl NoiseConstraints {
+    /// Nothing known: offsets anywhere in `[0, 256]` and any permutation.
+    pub fn new() -> Self {
+        let offset = Interval::new(0.0f64, 256.0f64);
+        NoiseConstraints::with_offsets(offset, offset, offset)
+    }
+
+    pub fn with_offsets(x0: Interval, y0: Interval, z0: Interval) -> Self {
+        NoiseConstraints { offsets: [x0, y0, z0], permutations: [ValueSet::full(); 256] }
+    }
+
+    /// The constraints only `noise` satisfies.
+    pub fn from_noise(noise: &Noise) -> Self {
+        let mut constraints = NoiseConstraints::with_offsets(Interval::point(noise.x0), Interval::point(noise.y0), Interval::point(noise.z0));
+        for index in 0..256 {
+            constraints.permutations[index] = ValueSet::single(noise.permutations[index]);
+        }
+        constraints
+    }
+
+    /// The interval of `x0`, `y0` or `z0` for an `axis` of 0, 1 or 2.
+    pub fn get_offset(&self, axis: usize) -> Interval {
+        self.offsets[axis]
+    }
+
+    pub fn restrict_offset(&mut self, axis: usize, interval: Interval) -> Result<(), Contradiction> {
+        let offset = &mut self.offsets[axis];
+        let restricted = Interval::new(offset.min.max(interval.min), offset.max.min(interval.max));
+        if restricted.min > restricted.max {
+            return Err(Contradiction);
+        }
+        *offset = restricted;
+        Ok(())
+    }
+
+    pub fn get_possible_values(&self, index: u8) -> ValueSet {
+        self.permutations[index as usize]
+    }
+
+    pub fn get_fixed_count(&self) -> usize {
+        self.permutations.iter().filter(|values| values.len() == 1).count()
+    }
+
+    pub fn fix(&mut self, index: u8, value: u8) -> Result<(), Contradiction> {
+        if !self.permutations[index as usize].contains(value) {
+            return Err(Contradiction);
+        }
+        self.permutations[index as usize] = ValueSet::single(value);
+        self.propagate_all_different().map(|_| ())
+    }
+
+    pub fn remove(&mut self, index: u8, value: u8) -> Result<(), Contradiction> {
+        self.permutations[index as usize].remove(value);
+        self.propagate_all_different().map(|_| ())
+    }
+
+    /// Whether `noise` lies within the domains.
+    pub fn is_consistent_with(&self, noise: &Noise) -> bool {
+        self.offsets[0].contains(noise.x0)
+            && self.offsets[1].contains(noise.y0)
+            && self.offsets[2].contains(noise.z0)
+            && (0..256).all(|index| self.permutations[index].contains(noise.permutations[index]))
+    }
+
+    /// Sound bounds of the values the noises within the domains take at the observed position.
+    /// Nothing is learnt while the permutation is unknown or an offset covers the 256 cells a
+    /// lookup wraps around, the bounds are then those of any noise.
+    pub fn get_bounds(&self, observation: &Observation) -> Interval {
+        let positions = [observation.x, observation.y, observation.z];
+        let spans_period = positions.iter().zip(&self.offsets).any(|(&position, offset)| (position + offset.max).floor() - (position + offset.min).floor() >= 255.0f64);
+        if spans_period || self.permutations.iter().all(ValueSet::is_full) {
+            return FULL_BOUNDS;
+        }
+        let fixed_noise = self.get_fixed_noise();
+        let cells = |position: f64, offset: Interval| {
+            let (start, end) = (position + offset.min, position + offset.max);
+            let first = start.floor() as i32;
+            (first..=end.floor() as i32).map(move |cell| (cell, Interval::new((start - cell as f64).max(0.0f64), (end - cell as f64).min(1.0f64))))
+        };
+        let mut bounds: Option<Interval> = None;
+        for (cell_x, fraction_x) in cells(observation.x, self.offsets[0]) {
+            for (cell_y, fraction_y) in cells(observation.y, self.offsets[1]) {
+                for (cell_z, fraction_z) in cells(observation.z, self.offsets[2]) {
+                    let cell_bounds = self.get_cell_bounds(&fixed_noise, [cell_x, cell_y, cell_z], [fraction_x, fraction_y, fraction_z]);
+                    bounds = Some(bounds.map_or(cell_bounds, |bounds| bounds.hull(&cell_bounds)));
+                }
+            }
+        }
+        bounds.unwrap_or(Interval::new(f64::NEG_INFINITY, f64::INFINITY))
+    }
+
+    /// Drops the permutation values the observations rule out, until a fixpoint.
+    pub fn propagate(&mut self, observations: &[Observation]) -> Result<(), Contradiction> {
+        self.propagate_all_different()?;
+        loop {
+            let mut changed = false;
+            for observation in observations {
+                if !self.get_bounds(observation).intersects(&observation.value) {
+                    return Err(Contradiction);
+                }
+                for index in self.get_reached_indices(observation).iter() {
+                    let domain = self.permutations[index as usize];
+                    if domain.len() == 1 {
+                        continue;
+                    }
+                    let mut remaining = domain;
+                    for value in domain.iter() {
+                        self.permutations[index as usize] = ValueSet::single(value);
+                        if !self.get_bounds(observation).intersects(&observation.value) {
+                            remaining.remove(value);
+                            changed = true;
+                        }
+                    }
+                    self.permutations[index as usize] = remaining;
+                    if remaining.is_empty() {
+                        return Err(Contradiction);
+                    }
+                }
+                changed |= self.propagate_all_different()?;
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Shrinks each offset interval to the hull of its `divisions` parts the observations allow.
+    pub fn refine_offsets(&mut self, observations: &[Observation], divisions: u32) -> Result<(), Contradiction> {
+        for axis in 0..3 {
+            let offset = self.offsets[axis];
+            let step = offset.get_width() / divisions.max(1) as f64;
+            let mut kept: Option<Interval> = None;
+            for division in 0..divisions.max(1) {
+                let part = Interval::new(offset.min + step * division as f64, offset.min + step * (division + 1) as f64);
+                self.offsets[axis] = part;
+                if observations.iter().all(|observation| self.get_bounds(observation).intersects(&observation.value)) {
+                    kept = Some(kept.map_or(part, |kept| kept.hull(&part)));
+                }
+            }
+            match kept {
+                Some(kept) => self.offsets[axis] = Interval::new(kept.min.max(offset.min), kept.max.min(offset.max)),
+                None => {
+                    self.offsets[axis] = offset;
+                    return Err(Contradiction);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The permutation indices the `lookup` chains of the observation go through, as far as the
+    /// earlier lookups of a chain are fixed.
+    fn get_reached_indices(&self, observation: &Observation) -> ValueSet {
+        let mut reached = ValueSet::empty();
+        let first_cell = |position: f64, offset: Interval| ((position + offset.min).floor() as i32, (position + offset.max).floor() as i32);
+        let (x_start, x_end) = first_cell(observation.x, self.offsets[0]);
+        let (y_start, y_end) = first_cell(observation.y, self.offsets[1]);
+        let (z_start, z_end) = first_cell(observation.z, self.offsets[2]);
+        for cell_x in x_start..=x_end + 1 {
+            let index = cell_x as u8;
+            reached.insert(index);
+            let value = match self.permutations[index as usize].get_single() {
+                Some(value) => value,
+                None => continue,
+            };
+            for cell_y in y_start..=y_end + 1 {
+                let index = (value as i32 + cell_y) as u8;
+                reached.insert(index);
+                let value = match self.permutations[index as usize].get_single() {
+                    Some(value) => value,
+                    None => continue,
+                };
+                for cell_z in z_start..=z_end + 1 {
+                    reached.insert((value as i32 + cell_z) as u8);
+                }
+            }
+        }
+        reached
+    }
+
+    /// The gradient hashes (`& 0xF`) the corner of a cell can get.
+    fn get_corner_hashes(&self, cell: [i32; 3], corner: [i32; 3]) -> u16 {
+        let first = self.permutations[((cell[0] + corner[0]) & 0xff) as usize];
+        let second = self.get_values_at(&first.shifted(cell[1] + corner[1]));
+        let third = self.get_values_at(&second.shifted(cell[2] + corner[2]));
+        let mut hashes: u16 = 0;
+        for value in third.iter() {
+            hashes |= 1 << (value & 0xF);
+            if hashes == u16::MAX {
+                break;
+            }
+        }
+        hashes
+    }
+
+    /// The values the indices of the set can hold.
+    fn get_values_at(&self, indices: &ValueSet) -> ValueSet {
+        let mut values = ValueSet::empty();
+        for index in indices.iter() {
+            values = values.union(&self.permutations[index as usize]);
+            if values.is_full() {
+                break;
+            }
+        }
+        values
+    }
+
+    /// A noise holding the fixed permutation values, the others left at 0, which samples the
+    /// cells whose `lookup` chains only go through fixed entries exactly.
+    fn get_fixed_noise(&self) -> Noise {
+        let mut permutations = [0u8; 256];
+        for (permutation, values) in permutations.iter_mut().zip(self.permutations.iter()) {
+            *permutation = values.get_single().unwrap_or(0);
+        }
+        Noise { x0: self.offsets[0].min, y0: self.offsets[1].min, z0: self.offsets[2].min, permutations }
+    }
+
+    /// Whether the `lookup` chains of the 8 corners of a cell only go through fixed entries.
+    fn is_cell_fixed(&self, fixed_noise: &Noise, cell: [i32; 3]) -> bool {
+        let is_fixed = |index: i32| self.permutations[(index & 0xff) as usize].len() == 1;
+        (cell[0]..=cell[0] + 1).all(|x| {
+            is_fixed(x)
+                && (cell[1]..=cell[1] + 1).all(|y| {
+                    let index = fixed_noise.lookup(x) as i32 + y;
+                    is_fixed(index) && (cell[2]..=cell[2] + 1).all(|z| is_fixed(fixed_noise.lookup(index) as i32 + z))
+                })
+        })
+    }
+
+    fn get_cell_bounds(&self, fixed_noise: &Noise, cell: [i32; 3], fractions: [Interval; 3]) -> Interval {
+        if fractions.iter().all(|fraction| fraction.min == fraction.max) && self.is_cell_fixed(fixed_noise, cell) {
+            let (x, y, z) = (fractions[0].min, fractions[1].min, fractions[2].min);
+            return Interval::point(fixed_noise.sample_and_lerp(cell[0], cell[1], cell[2], x, y, z, smooth_step(x), smooth_step(y), smooth_step(z)));
+        }
+        let mut corners: [Interval; 8] = [Interval::point(0.0f64); 8];
+        for (i, bounds) in corners.iter_mut().enumerate() {
+            let corner = [(i & 1) as i32, (i >> 1 & 1) as i32, (i >> 2 & 1) as i32];
+            let hashes = self.get_corner_hashes(cell, corner);
+            let mut corner_bounds: Option<Interval> = None;
+            for hash in 0..16u8 {
+                if hashes >> hash & 1 == 0 {
+                    continue;
+                }
+                // grad is linear, its extremes over the box of fractions are at the box corners
+                for j in 0..8 {
+                    let x = if j & 1 == 0 { fractions[0].min } else { fractions[0].max } - corner[0] as f64;
+                    let y = if j & 2 == 0 { fractions[1].min } else { fractions[1].max } - corner[1] as f64;
+                    let z = if j & 4 == 0 { fractions[2].min } else { fractions[2].max } - corner[2] as f64;
+                    let value = Interval::point(grad(hash, x, y, z));
+                    corner_bounds = Some(corner_bounds.map_or(value, |bounds| bounds.hull(&value)));
+                }
+            }
+            *bounds = corner_bounds.unwrap_or(Interval::new(f64::INFINITY, f64::NEG_INFINITY));
+        }
+        let smooth = |fraction: Interval| Interval::new(smooth_step(fraction.min), smooth_step(fraction.max));
+        let (smooth_x, smooth_y, smooth_z) = (smooth(fractions[0]), smooth(fractions[1]), smooth(fractions[2]));
+        let lerp_x = |a: Interval, b: Interval| lerp_bounds(smooth_x, a, b);
+        let bottom = lerp_bounds(smooth_y, lerp_x(corners[0], corners[1]), lerp_x(corners[2], corners[3]));
+        let top = lerp_bounds(smooth_y, lerp_x(corners[4], corners[5]), lerp_x(corners[6], corners[7]));
+        lerp_bounds(smooth_z, bottom, top)
+    }
+
+    /// Fixes the values only one index can hold and removes the fixed values from the other
+    /// indices, returning whether a domain changed.
+    fn propagate_all_different(&mut self) -> Result<bool, Contradiction> {
+        let mut changed = false;
+        loop {
+            let mut fixed = ValueSet::empty();
+            for values in self.permutations.iter() {
+                match values.len() {
+                    0 => return Err(Contradiction),
+                    1 => {
+                        let value = values.get_single().unwrap();
+                        if fixed.contains(value) {
+                            return Err(Contradiction);
+                        }
+                        fixed.insert(value);
+                    }
+                    _ => {}
+                }
+            }
+            let mut step_changed = false;
+            for values in self.permutations.iter_mut() {
+                if values.len() > 1 {
+                    for value in fixed.iter() {
+                        if values.contains(value) {
+                            values.remove(value);
+                            step_changed = true;
+                        }
+                    }
+                    if values.is_empty() {
+                        return Err(Contradiction);
+                    }
+                }
+            }
+            for value in 0..=255u8 {
+                if fixed.contains(value) {
+                    continue;
+                }
+                let mut holders = (0..256).filter(|&index| self.permutations[index].contains(value));
+                match (holders.next(), holders.next()) {
+                    (None, _) => return Err(Contradiction),
+                    (Some(index), None) => {
+                        self.permutations[index] = ValueSet::single(value);
+                        step_changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !step_changed {
+                return Ok(changed);
+            }
+            changed = true;
+        }
+    }
+}
+
+impl Default for NoiseConstraints {
+    fn default() -> Self {
+        NoiseConstraints::new()
+    }
+}
+
+/// Bounds of `lerp1(t, a, b)`, increasing in `a` and `b` and linear in `t`.
+fn lerp_bounds(t: Interval, a: Interval, b: Interval) -> Interval {
+    Interval::new(lerp1(t.min, a.min, b.min).min(lerp1(t.max, a.min, b.min)), lerp1(t.min, a.max, b.max).max(lerp1(t.max, a.max, b.max)))
+}
@@ -0,0 +1,124 @@
+/// Scaling of the coordinates fed to the limit and main noises.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseSamplingSettings {
+    pub xz_scale: f64,
+    pub y_scale: f64,
+    pub xz_factor: f64,
+    pub y_factor: f64,
+}
+
+/// Slide towards `target` over `size` noise cells, starting `offset` cells away from the top or bottom.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseSlideSettings {
+    pub target: i32,
+    pub size: i32,
+    pub offset: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseSettings {
+    pub height: i32,
+    pub noise_sampling_settings: NoiseSamplingSettings,
+    pub top_slide_settings: NoiseSlideSettings,
+    pub bottom_slide_settings: NoiseSlideSettings,
+    pub noise_size_horizontal: i32,
+    pub noise_size_vertical: i32,
+    pub density_factor: f64,
+    pub density_offset: f64,
+    pub use_simplex_surface_noise: bool,
+    pub random_density_offset: bool,
+    pub island_noise_override: bool,
+    pub is_amplified: bool,
+}
+
+/// The noise part of a 1.16 `noise_settings` preset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseGeneratorSettings {
+    pub noise_settings: NoiseSettings,
+    pub bedrock_roof_position: i32,
+    pub bedrock_floor_position: i32,
+    pub sea_level: i32,
+}
+
+impl NoiseGeneratorSettings {
+    pub fn overworld(amplified: bool) -> Self {
+        NoiseGeneratorSettings {
+            noise_settings: NoiseSettings {
+                height: 256,
+                noise_sampling_settings: NoiseSamplingSettings {
+                    xz_scale: 0.9999999814507745f64,
+                    y_scale: 0.9999999814507745f64,
+                    xz_factor: 80.0f64,
+                    y_factor: 160.0f64,
+                },
+                top_slide_settings: NoiseSlideSettings { target: -10, size: 3, offset: 0 },
+                bottom_slide_settings: NoiseSlideSettings { target: -30, size: 0, offset: 0 },
+                noise_size_horizontal: 1,
+                noise_size_vertical: 2,
+                density_factor: 1.0f64,
+                density_offset: -0.46875f64,
+                use_simplex_surface_noise: true,
+                random_density_offset: true,
+                island_noise_override: false,
+                is_amplified: amplified,
+            },
+            bedrock_roof_position: -10,
+            bedrock_floor_position: 0,
+            sea_level: 63,
+        }
+    }
+
+    pub fn nether() -> Self {
+        NoiseGeneratorSettings {
+            noise_settings: NoiseSettings {
+                height: 128,
+                noise_sampling_settings: NoiseSamplingSettings {
+                    xz_scale: 1.0f64,
+                    y_scale: 3.0f64,
+                    xz_factor: 80.0f64,
+                    y_factor: 60.0f64,
+                },
+                top_slide_settings: NoiseSlideSettings { target: 120, size: 3, offset: 0 },
+                bottom_slide_settings: NoiseSlideSettings { target: 320, size: 4, offset: -1 },
+                noise_size_horizontal: 1,
+                noise_size_vertical: 2,
+                density_factor: 0.0f64,
+                density_offset: 0.019921875f64,
+                use_simplex_surface_noise: false,
+                random_density_offset: false,
+                island_noise_override: false,
+                is_amplified: false,
+            },
+            bedrock_roof_position: 0,
+            bedrock_floor_position: 0,
+            sea_level: 32,
+        }
+    }
+
+    pub fn end() -> Self {
+        NoiseGeneratorSettings {
+            noise_settings: NoiseSettings {
+                height: 128,
+                noise_sampling_settings: NoiseSamplingSettings {
+                    xz_scale: 2.0f64,
+                    y_scale: 1.0f64,
+                    xz_factor: 80.0f64,
+                    y_factor: 160.0f64,
+                },
+                top_slide_settings: NoiseSlideSettings { target: -3000, size: 64, offset: -46 },
+                bottom_slide_settings: NoiseSlideSettings { target: -30, size: 7, offset: 1 },
+                noise_size_horizontal: 2,
+                noise_size_vertical: 1,
+                density_factor: 0.0f64,
+                density_offset: 0.0f64,
+                use_simplex_surface_noise: true,
+                random_density_offset: false,
+                island_noise_override: true,
+                is_amplified: false,
+            },
+            bedrock_roof_position: -10,
+            bedrock_floor_position: -10,
+            sea_level: 0,
+        }
+    }
+}
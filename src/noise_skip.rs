@@ -0,0 +1,128 @@
+//! LCG jumps over noise constructions, generalizing `SKIP_262`.
+//!
+//! Every `Noise::new` takes 262 calls of the java random, 6 for the offsets and 256 for the
+//! permutation, and vanilla advances by as many for the octaves it does not build. A noise built
+//! from a `Random` therefore takes a number of calls that only depends on its layout, and the
+//! random of a noise built after others can be jumped to directly.
+//!
+//! `next_int_n` draws again when the first draw falls in the biased tail of the range, which a
+//! built octave hits about once in 100 000 constructions: the jumps are those of every octave
+//! taking 262 calls, [`NoiseTrace`](crate::noise_trace::NoiseTrace) tells the actual count.
+
+use alloc::vec::Vec;
+
+use java_random::LCG;
+
+/// Calls a `Noise::new` takes.
+pub const NOISE_CALLS: u64 = 262;
+
+/// The jump over `count` octaves.
+pub fn skip_octaves(count: u64) -> LCG {
+    LCG::combine_java(count * NOISE_CALLS)
+}
+
+/// How a noise is built from a java random.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NoiseLayout {
+    /// A lone `Noise::new`.
+    Noise,
+    /// `PerlinNoise::new` with these octaves.
+    Perlin(Vec<i32>),
+    /// `PerlinNoise::create_legacy` with a first octave and that many amplitudes.
+    LegacyPerlin { first_octave: i32, length: usize },
+    /// `DoublePerlinNoise::new` with these octaves.
+    DoublePerlin(Vec<i32>),
+    /// `DoublePerlinNoise::create_legacy` with a first octave and that many amplitudes.
+    LegacyDoublePerlin { first_octave: i32, length: usize },
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod noise_skip_test {
+    use super::*;
+    use crate::create_range;
+    use crate::double_perlin_noise::DoublePerlinNoise;
+    use crate::noise::Noise;
+    use crate::perlin_noise::{PerlinNoise, SKIP_262};
+    use alloc::vec;
+    use java_random::Random;
+
+    fn assert_skips(layout: &NoiseLayout, build: impl Fn(&mut Random)) {
+        let mut random = Random::with_seed(12345);
+        build(&mut random);
+        let mut skipped = Random::with_seed(12345);
+        skipped.advance(layout.get_skip().unwrap());
+        assert_eq!(skipped.get_raw_seed(), random.get_raw_seed(), "{:?}", layout);
+    }
+
+    #[test]
+    fn test_octaves() {
+        assert_eq!((skip_octaves(1).multiplier, skip_octaves(1).addend), (SKIP_262.multiplier, SKIP_262.addend));
+        assert_eq!(NoiseLayout::Noise.get_calls(), Some(262));
+        assert_skips(&NoiseLayout::Noise, |random| {
+            Noise::new(random);
+        });
+    }
+
+    #[test]
+    fn test_perlin() {
+        for octaves in [create_range(-7, 0), vec![-15, -3, 0], vec![-3, -2], create_range(-16, -1)] {
+            let layout = NoiseLayout::Perlin(octaves.clone());
+            assert_skips(&layout, |random| {
+                PerlinNoise::new(random, octaves.clone());
+            });
+            let double_layout = NoiseLayout::DoublePerlin(octaves.clone());
+            assert_eq!(double_layout.get_calls(), layout.get_calls().map(|calls| 2 * calls));
+            assert_skips(&double_layout, |random| {
+                DoublePerlinNoise::new(random, octaves.clone());
+            });
+        }
+        // the positive octaves reseed the random
+        assert_eq!(NoiseLayout::Perlin(create_range(-1, 2)).get_calls(), None);
+        assert!(NoiseLayout::DoublePerlin(create_range(1, 2)).get_skip().is_none());
+    }
+
+    #[test]
+    fn test_legacy_perlin() {
+        for (first_octave, length) in [(-7, 2), (-8, 8), (-3, 6), (2, 3)] {
+            let layout = NoiseLayout::LegacyPerlin { first_octave, length };
+            assert_skips(&layout, |random| {
+                PerlinNoise::create_legacy(random, first_octave, vec![1.0f64; length]);
+            });
+            assert_skips(&NoiseLayout::LegacyDoublePerlin { first_octave, length }, |random| {
+                DoublePerlinNoise::create_legacy(random, first_octave, vec![1.0f64; length]);
+            });
+        }
+        assert_eq!(NoiseLayout::LegacyPerlin { first_octave: -7, length: 2 }.get_calls(), Some(8 * 262));
+    }
+}
+
+impl NoiseLayout {
+    /// The calls the construction takes from the random it is given, none if it reseeds that
+    /// random (`PerlinNoise::new` with positive octaves).
+    pub fn get_calls(&self) -> Option<u64> {
+        match self {
+            NoiseLayout::Noise => Some(NOISE_CALLS),
+            NoiseLayout::Perlin(octaves) => {
+                let start = -*octaves.first()?;
+                let end = *octaves.last()?;
+                if end > 0 {
+                    return None;
+                }
+                // the octave 0 then one per lower octave, built or skipped
+                Some((1 + start.max(0) as u64) * NOISE_CALLS)
+            }
+            // the positive octaves come from a random of their own
+            NoiseLayout::LegacyPerlin { first_octave, .. } => Some((1 + (-first_octave).max(0) as u64) * NOISE_CALLS),
+            NoiseLayout::DoublePerlin(octaves) => NoiseLayout::Perlin(octaves.clone()).get_calls().map(|calls| 2 * calls),
+            NoiseLayout::LegacyDoublePerlin { first_octave, length } => {
+                NoiseLayout::LegacyPerlin { first_octave: *first_octave, length: *length }.get_calls().map(|calls| 2 * calls)
+            }
+        }
+    }
+
+    /// The jump over the construction, see [`NoiseLayout::get_calls`].
+    pub fn get_skip(&self) -> Option<LCG> {
+        self.get_calls().map(LCG::combine_java)
+    }
+}
@@ -0,0 +1,177 @@
+//! Traces of the random calls made while building noises.
+//!
+//! [`Noise::new_traced`], [`PerlinNoise::new_traced`] and [`DoublePerlinNoise::new_traced`] build
+//! the same noises as their untraced counterparts and record each step in a [`NoiseTrace`]: the
+//! octave built or skipped, the java random state before and after and the number of LCG calls
+//! in between. The states are the internal (scrambled) ones, `Random.seed` on the java side.
+//!
+//! [`Noise::new_traced`]: crate::noise::Noise::new_traced
+//! [`PerlinNoise::new_traced`]: crate::perlin_noise::PerlinNoise::new_traced
+//! [`DoublePerlinNoise::new_traced`]: crate::double_perlin_noise::DoublePerlinNoise::new_traced
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use java_random::{JAVA_LCG, LCG};
+
+/// The most calls a traced step is searched for, far more than an octave takes.
+const MAX_STEP_CALLS: u64 = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceKind {
+    /// An octave built and kept.
+    Built,
+    /// An octave built for its random calls or its value at the origin, but not kept.
+    Discarded,
+    /// An octave the random was advanced past.
+    Skipped,
+    /// The random was reseeded, from the value of the octave 0 for the positive octaves.
+    Reseeded,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub kind: TraceKind,
+    /// The perlin noise of a double perlin noise, 0 or 1, always 0 otherwise.
+    pub perlin: usize,
+    /// The octave, noises of which sample at a frequency of `2^octave`, none for a lone `Noise`.
+    pub octave: Option<i32>,
+    /// LCG calls the step took, 0 for a reseed.
+    pub calls: u64,
+    pub state_before: u64,
+    pub state_after: u64,
+}
+
+/// The steps of one or more noise constructions, in order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NoiseTrace {
+    events: Vec<TraceEvent>,
+    perlin: usize,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod noise_trace_test {
+    use super::*;
+    use alloc::vec;
+    use crate::create_range;
+    use crate::double_perlin_noise::DoublePerlinNoise;
+    use crate::noise::Noise;
+    use crate::perlin_noise::{PerlinNoise, SKIP_262};
+    use java_random::Random;
+
+    #[test]
+    fn test_noise() {
+        let mut trace = NoiseTrace::new();
+        let mut random = Random::with_seed(1);
+        let noise = Noise::new_traced(&mut random, &mut trace);
+        assert_eq!(noise.x0, Noise::new(&mut Random::with_seed(1)).x0);
+        let event = trace.get_events()[0];
+        assert_eq!((event.kind, event.octave, event.calls), (TraceKind::Built, None, 262));
+        assert_eq!((event.state_before, event.state_after), (Random::with_seed(1).get_raw_seed(), random.get_raw_seed()));
+        assert_eq!(trace.get_skip().map(|skip| (skip.multiplier, skip.addend)), Some((SKIP_262.multiplier, SKIP_262.addend)));
+    }
+
+    #[test]
+    fn test_perlin() {
+        let mut trace = NoiseTrace::new();
+        let perlin = PerlinNoise::new_traced(&mut Random::with_seed(1), vec![-3, -1, 0], &mut trace);
+        assert_eq!(perlin.sample_default(1.5f64, 2.5f64, 3.5f64), PerlinNoise::new(&mut Random::with_seed(1), vec![-3, -1, 0]).sample_default(1.5f64, 2.5f64, 3.5f64));
+        let steps: Vec<(TraceKind, Option<i32>, u64)> = trace.get_events().iter().map(|event| (event.kind, event.octave, event.calls)).collect();
+        assert_eq!(steps, vec![
+            (TraceKind::Built, Some(0), 262),
+            (TraceKind::Built, Some(-1), 262),
+            (TraceKind::Skipped, Some(-2), 262),
+            (TraceKind::Built, Some(-3), 262),
+        ]);
+        assert_eq!(trace.get_total_calls(), 4 * 262);
+        // each step starts where the last ended
+        assert!(trace.get_events().windows(2).all(|events| events[0].state_after == events[1].state_before));
+    }
+
+    #[test]
+    fn test_reseed() {
+        let mut trace = NoiseTrace::new();
+        PerlinNoise::new_traced(&mut Random::with_seed(7), create_range(1, 2), &mut trace);
+        let kinds: Vec<(TraceKind, Option<i32>)> = trace.get_events().iter().map(|event| (event.kind, event.octave)).collect();
+        assert_eq!(kinds, vec![
+            (TraceKind::Discarded, Some(0)),
+            (TraceKind::Reseeded, None),
+            (TraceKind::Built, Some(1)),
+            (TraceKind::Built, Some(2)),
+        ]);
+        assert!(trace.get_skip().is_none());
+    }
+
+    #[test]
+    fn test_double_perlin() {
+        let mut trace = NoiseTrace::new();
+        DoublePerlinNoise::new_traced(&mut Random::with_seed(3), create_range(-7, -6), &mut trace);
+        let perlins: Vec<usize> = trace.get_events().iter().map(|event| event.perlin).collect();
+        assert_eq!(perlins, vec![0; 8].into_iter().chain(vec![1; 8]).collect::<Vec<usize>>());
+        assert_eq!(trace.get_total_calls(), 16 * 262);
+    }
+}
+
+impl NoiseTrace {
+    pub fn new() -> Self {
+        NoiseTrace::default()
+    }
+
+    pub fn get_events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Calls taken by all the steps, reseeds aside.
+    pub fn get_total_calls(&self) -> u64 {
+        self.events.iter().map(|event| event.calls).sum()
+    }
+
+    /// The jump over everything traced, none if the random was reseeded along the way.
+    pub fn get_skip(&self) -> Option<LCG> {
+        if self.events.iter().any(|event| event.kind == TraceKind::Reseeded) {
+            return None;
+        }
+        Some(LCG::combine_java(self.get_total_calls()))
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.perlin = 0;
+    }
+
+    pub(crate) fn set_perlin(&mut self, perlin: usize) {
+        self.perlin = perlin;
+    }
+
+    pub(crate) fn record(&mut self, kind: TraceKind, octave: Option<i32>, state_before: u64, state_after: u64) {
+        let calls = if kind == TraceKind::Reseeded { 0 } else { count_calls(state_before, state_after) };
+        self.events.push(TraceEvent { kind, perlin: self.perlin, octave, calls, state_before, state_after });
+    }
+}
+
+impl fmt::Display for NoiseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in self.events.iter() {
+            write!(f, "perlin {} ", event.perlin)?;
+            match event.octave {
+                Some(octave) => write!(f, "octave {} ", octave)?,
+                None => write!(f, "noise ")?,
+            }
+            writeln!(f, "{:?} calls {} state {:#014x} -> {:#014x}", event.kind, event.calls, event.state_before, event.state_after)?;
+        }
+        Ok(())
+    }
+}
+
+/// The LCG calls from one state to the other, stepping one call at a time.
+fn count_calls(state_before: u64, state_after: u64) -> u64 {
+    let mut state = state_before;
+    for calls in 0..MAX_STEP_CALLS {
+        if state == state_after {
+            return calls;
+        }
+        state = state.wrapping_mul(JAVA_LCG.multiplier).wrapping_add(JAVA_LCG.addend) & ((1u64 << 48) - 1);
+    }
+    panic!("no {} calls from {:#x} lead to {:#x}", MAX_STEP_CALLS, state_before, state_after)
+}
@@ -0,0 +1,460 @@
+use alloc::vec::Vec;
+
+use crate::biome_source::Biome;
+use crate::climate::{Parameter, ParameterList, ParameterPoint};
+
+type BiomeTable = [[Option<Biome>; 5]; 5];
+
+const OCEANS: [[Biome; 5]; 2] = [
+    [Biome::DeepFrozenOcean, Biome::DeepColdOcean, Biome::DeepOcean, Biome::DeepLukewarmOcean, Biome::WarmOcean],
+    [Biome::FrozenOcean, Biome::ColdOcean, Biome::Ocean, Biome::LukewarmOcean, Biome::WarmOcean],
+];
+
+const MIDDLE_BIOMES: [[Biome; 5]; 5] = [
+    [Biome::SnowyPlains, Biome::SnowyPlains, Biome::SnowyPlains, Biome::SnowyTaiga, Biome::Taiga],
+    [Biome::Plains, Biome::Plains, Biome::Forest, Biome::Taiga, Biome::OldGrowthSpruceTaiga],
+    [Biome::FlowerForest, Biome::Plains, Biome::Forest, Biome::BirchForest, Biome::DarkForest],
+    [Biome::Savanna, Biome::Savanna, Biome::Forest, Biome::Jungle, Biome::Jungle],
+    [Biome::Desert, Biome::Desert, Biome::Desert, Biome::Desert, Biome::Desert],
+];
+
+const MIDDLE_BIOMES_VARIANT: BiomeTable = [
+    [Some(Biome::IceSpikes), None, Some(Biome::SnowyTaiga), None, None],
+    [None, None, None, None, Some(Biome::OldGrowthPineTaiga)],
+    [Some(Biome::SunflowerPlains), None, None, Some(Biome::OldGrowthBirchForest), None],
+    [None, None, Some(Biome::Plains), Some(Biome::SparseJungle), Some(Biome::BambooJungle)],
+    [None, None, None, None, None],
+];
+
+const PLATEAU_BIOMES: [[Biome; 5]; 5] = [
+    [Biome::SnowyPlains, Biome::SnowyPlains, Biome::SnowyPlains, Biome::SnowyTaiga, Biome::SnowyTaiga],
+    [Biome::Meadow, Biome::Meadow, Biome::Forest, Biome::Taiga, Biome::OldGrowthSpruceTaiga],
+    [Biome::Meadow, Biome::Meadow, Biome::Meadow, Biome::Meadow, Biome::DarkForest],
+    [Biome::SavannaPlateau, Biome::SavannaPlateau, Biome::Forest, Biome::Forest, Biome::Jungle],
+    [Biome::Badlands, Biome::Badlands, Biome::Badlands, Biome::WoodedBadlands, Biome::WoodedBadlands],
+];
+
+const PLATEAU_BIOMES_VARIANT: BiomeTable = [
+    [Some(Biome::IceSpikes), None, None, None, None],
+    [None, None, Some(Biome::Meadow), Some(Biome::Meadow), Some(Biome::OldGrowthPineTaiga)],
+    [None, None, Some(Biome::Forest), Some(Biome::BirchForest), None],
+    [None, None, None, None, None],
+    [Some(Biome::ErodedBadlands), Some(Biome::ErodedBadlands), None, None, None],
+];
+
+const SHATTERED_BIOMES: BiomeTable = [
+    [
+        Some(Biome::WindsweptGravellyHills),
+        Some(Biome::WindsweptGravellyHills),
+        Some(Biome::WindsweptHills),
+        Some(Biome::WindsweptForest),
+        Some(Biome::WindsweptForest),
+    ],
+    [
+        Some(Biome::WindsweptGravellyHills),
+        Some(Biome::WindsweptGravellyHills),
+        Some(Biome::WindsweptHills),
+        Some(Biome::WindsweptForest),
+        Some(Biome::WindsweptForest),
+    ],
+    [Some(Biome::WindsweptHills), Some(Biome::WindsweptHills), Some(Biome::WindsweptHills), Some(Biome::WindsweptForest), Some(Biome::WindsweptForest)],
+    [None, None, None, None, None],
+    [None, None, None, None, None],
+];
+
+/// The 1.18 `OverworldBiomeBuilder`, placing the overworld biomes in the climate space.
+#[derive(Clone, Debug)]
+pub struct OverworldBiomeBuilder {
+    full_range: Parameter,
+    temperatures: [Parameter; 5],
+    humidities: [Parameter; 5],
+    erosions: [Parameter; 7],
+    frozen_range: Parameter,
+    unfrozen_range: Parameter,
+    mushroom_fields_continentalness: Parameter,
+    deep_ocean_continentalness: Parameter,
+    ocean_continentalness: Parameter,
+    coast_continentalness: Parameter,
+    inland_continentalness: Parameter,
+    near_inland_continentalness: Parameter,
+    mid_inland_continentalness: Parameter,
+    far_inland_continentalness: Parameter,
+    values: Vec<(ParameterPoint, Biome)>,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod overworld_biome_builder_test {
+    use super::*;
+    use crate::climate::TargetPoint;
+
+    #[test]
+    fn test_parameters() {
+        let mut parameters = OverworldBiomeBuilder::build();
+        // 3788 surface points at depths 0 and 1 and the two cave biomes, the size of vanilla's list
+        assert_eq!(parameters.get_values().len(), 7578);
+        let target = |temperature: f32, humidity: f32, continentalness: f32, erosion: f32, depth: f32, weirdness: f32| {
+            TargetPoint::new(temperature, humidity, continentalness, erosion, depth, weirdness)
+        };
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, -1.1f32, 0.0f32, 0.0f32, 0.0f32)), Biome::MushroomFields);
+        assert_eq!(parameters.find_value(&target(-0.6f32, 0.0f32, -0.5f32, 0.0f32, 0.0f32, 0.0f32)), Biome::DeepFrozenOcean);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, -0.3f32, 0.0f32, 0.0f32, 0.0f32)), Biome::Ocean);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, 0.1f32, 0.0f32, 0.0f32, 0.0f32)), Biome::River);
+        assert_eq!(parameters.find_value(&target(-0.3f32, -0.5f32, 0.1f32, 0.3f32, 0.0f32, -0.2f32)), Biome::Plains);
+        assert_eq!(parameters.find_value(&target(-0.3f32, -0.5f32, 0.1f32, 0.3f32, 0.0f32, 0.2f32)), Biome::Plains);
+        assert_eq!(parameters.find_value(&target(-0.6f32, -0.5f32, 0.1f32, 0.3f32, 0.0f32, 0.2f32)), Biome::IceSpikes);
+        assert_eq!(parameters.find_value(&target(0.7f32, 0.0f32, 0.5f32, 0.3f32, 0.0f32, 0.2f32)), Biome::Desert);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, 0.5f32, -0.9f32, 0.0f32, 0.6f32)), Biome::FrozenPeaks);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, 0.5f32, -0.9f32, 0.0f32, -0.6f32)), Biome::JaggedPeaks);
+        assert_eq!(parameters.find_value(&target(0.4f32, 0.0f32, 0.5f32, -0.9f32, 0.0f32, 0.6f32)), Biome::StonyPeaks);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, -0.15f32, 0.3f32, 0.0f32, -0.3f32)), Biome::Beach);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.0f32, 0.9f32, 0.0f32, 0.5f32, 0.0f32)), Biome::DripstoneCaves);
+        assert_eq!(parameters.find_value(&target(0.0f32, 0.8f32, 0.1f32, 0.0f32, 0.5f32, 0.0f32)), Biome::LushCaves);
+    }
+}
+
+impl OverworldBiomeBuilder {
+    fn new() -> Self {
+        let temperatures = [
+            Parameter::span(-1.0f32, -0.45f32),
+            Parameter::span(-0.45f32, -0.15f32),
+            Parameter::span(-0.15f32, 0.2f32),
+            Parameter::span(0.2f32, 0.55f32),
+            Parameter::span(0.55f32, 1.0f32),
+        ];
+        OverworldBiomeBuilder {
+            full_range: Parameter::span(-1.0f32, 1.0f32),
+            temperatures,
+            humidities: [
+                Parameter::span(-1.0f32, -0.35f32),
+                Parameter::span(-0.35f32, -0.1f32),
+                Parameter::span(-0.1f32, 0.1f32),
+                Parameter::span(0.1f32, 0.3f32),
+                Parameter::span(0.3f32, 1.0f32),
+            ],
+            erosions: [
+                Parameter::span(-1.0f32, -0.78f32),
+                Parameter::span(-0.78f32, -0.375f32),
+                Parameter::span(-0.375f32, -0.2225f32),
+                Parameter::span(-0.2225f32, 0.05f32),
+                Parameter::span(0.05f32, 0.45f32),
+                Parameter::span(0.45f32, 0.55f32),
+                Parameter::span(0.55f32, 1.0f32),
+            ],
+            frozen_range: temperatures[0],
+            unfrozen_range: Parameter::span_of(temperatures[1], temperatures[4]),
+            mushroom_fields_continentalness: Parameter::span(-1.2f32, -1.05f32),
+            deep_ocean_continentalness: Parameter::span(-1.05f32, -0.455f32),
+            ocean_continentalness: Parameter::span(-0.455f32, -0.19f32),
+            coast_continentalness: Parameter::span(-0.19f32, -0.11f32),
+            inland_continentalness: Parameter::span(-0.11f32, 0.55f32),
+            near_inland_continentalness: Parameter::span(-0.11f32, 0.03f32),
+            mid_inland_continentalness: Parameter::span(0.03f32, 0.3f32),
+            far_inland_continentalness: Parameter::span(0.3f32, 1.0f32),
+            values: Vec::new(),
+        }
+    }
+
+    /// The overworld biomes in the order vanilla adds them.
+    pub fn build() -> ParameterList<Biome> {
+        let mut builder = Self::new();
+        builder.add_off_coast_biomes();
+        builder.add_inland_biomes();
+        builder.add_underground_biomes();
+        ParameterList::new(builder.values)
+    }
+
+    fn add_off_coast_biomes(&mut self) {
+        let (full_range, temperatures) = (self.full_range, self.temperatures);
+        self.add_surface_biome(full_range, full_range, self.mushroom_fields_continentalness, full_range, full_range, 0.0f32, Biome::MushroomFields);
+        for (temperature, (deep_ocean, ocean)) in temperatures.iter().zip(OCEANS[0].iter().zip(OCEANS[1].iter())) {
+            self.add_surface_biome(*temperature, full_range, self.deep_ocean_continentalness, full_range, full_range, 0.0f32, *deep_ocean);
+            self.add_surface_biome(*temperature, full_range, self.ocean_continentalness, full_range, full_range, 0.0f32, *ocean);
+        }
+    }
+
+    fn add_inland_biomes(&mut self) {
+        self.add_mid_slice(Parameter::span(-1.0f32, -0.93333334f32));
+        self.add_high_slice(Parameter::span(-0.93333334f32, -0.7666667f32));
+        self.add_peaks(Parameter::span(-0.7666667f32, -0.56666666f32));
+        self.add_high_slice(Parameter::span(-0.56666666f32, -0.4f32));
+        self.add_mid_slice(Parameter::span(-0.4f32, -0.26666668f32));
+        self.add_low_slice(Parameter::span(-0.26666668f32, -0.05f32));
+        self.add_valleys(Parameter::span(-0.05f32, 0.05f32));
+        self.add_low_slice(Parameter::span(0.05f32, 0.26666668f32));
+        self.add_mid_slice(Parameter::span(0.26666668f32, 0.4f32));
+        self.add_high_slice(Parameter::span(0.4f32, 0.56666666f32));
+        self.add_peaks(Parameter::span(0.56666666f32, 0.7666667f32));
+        self.add_high_slice(Parameter::span(0.7666667f32, 0.93333334f32));
+        self.add_mid_slice(Parameter::span(0.93333334f32, 1.0f32));
+    }
+
+    fn add_peaks(&mut self, weirdness: Parameter) {
+        let erosions = self.erosions;
+        let coast_to_far_inland = Parameter::span_of(self.coast_continentalness, self.far_inland_continentalness);
+        let coast_to_near_inland = Parameter::span_of(self.coast_continentalness, self.near_inland_continentalness);
+        let mid_to_far_inland = Parameter::span_of(self.mid_inland_continentalness, self.far_inland_continentalness);
+        for i in 0..self.temperatures.len() {
+            let temperature = self.temperatures[i];
+            for j in 0..self.humidities.len() {
+                let humidity = self.humidities[j];
+                let middle = pick_middle_biome(i, j, weirdness);
+                let middle_or_badlands_if_hot = pick_middle_biome_or_badlands_if_hot(i, j, weirdness);
+                let middle_or_badlands_if_hot_or_slope_if_cold = pick_middle_biome_or_badlands_if_hot_or_slope_if_cold(i, j, weirdness);
+                let plateau = pick_plateau_biome(i, j, weirdness);
+                let shattered = pick_shattered_biome(i, j, weirdness);
+                let windswept_savanna = maybe_pick_windswept_savanna_biome(i, j, weirdness, shattered);
+                let peak = pick_peak_biome(i, j, weirdness);
+                self.add_surface_biome(temperature, humidity, coast_to_far_inland, erosions[0], weirdness, 0.0f32, peak);
+                self.add_surface_biome(temperature, humidity, coast_to_near_inland, erosions[1], weirdness, 0.0f32, middle_or_badlands_if_hot_or_slope_if_cold);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[1], weirdness, 0.0f32, peak);
+                self.add_surface_biome(temperature, humidity, coast_to_near_inland, Parameter::span_of(erosions[2], erosions[3]), weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[2], weirdness, 0.0f32, plateau);
+                self.add_surface_biome(temperature, humidity, self.mid_inland_continentalness, erosions[3], weirdness, 0.0f32, middle_or_badlands_if_hot);
+                self.add_surface_biome(temperature, humidity, self.far_inland_continentalness, erosions[3], weirdness, 0.0f32, plateau);
+                self.add_surface_biome(temperature, humidity, coast_to_far_inland, erosions[4], weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, coast_to_near_inland, erosions[5], weirdness, 0.0f32, windswept_savanna);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[5], weirdness, 0.0f32, shattered);
+                self.add_surface_biome(temperature, humidity, coast_to_far_inland, erosions[6], weirdness, 0.0f32, middle);
+            }
+        }
+    }
+
+    fn add_high_slice(&mut self, weirdness: Parameter) {
+        let erosions = self.erosions;
+        let coast_to_far_inland = Parameter::span_of(self.coast_continentalness, self.far_inland_continentalness);
+        let coast_to_near_inland = Parameter::span_of(self.coast_continentalness, self.near_inland_continentalness);
+        let mid_to_far_inland = Parameter::span_of(self.mid_inland_continentalness, self.far_inland_continentalness);
+        for i in 0..self.temperatures.len() {
+            let temperature = self.temperatures[i];
+            for j in 0..self.humidities.len() {
+                let humidity = self.humidities[j];
+                let middle = pick_middle_biome(i, j, weirdness);
+                let middle_or_badlands_if_hot = pick_middle_biome_or_badlands_if_hot(i, j, weirdness);
+                let middle_or_badlands_if_hot_or_slope_if_cold = pick_middle_biome_or_badlands_if_hot_or_slope_if_cold(i, j, weirdness);
+                let plateau = pick_plateau_biome(i, j, weirdness);
+                let shattered = pick_shattered_biome(i, j, weirdness);
+                let windswept_savanna = maybe_pick_windswept_savanna_biome(i, j, weirdness, middle);
+                let slope = pick_slope_biome(i, j, weirdness);
+                let peak = pick_peak_biome(i, j, weirdness);
+                self.add_surface_biome(temperature, humidity, self.coast_continentalness, Parameter::span_of(erosions[0], erosions[1]), weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, erosions[0], weirdness, 0.0f32, slope);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[0], weirdness, 0.0f32, peak);
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, erosions[1], weirdness, 0.0f32, middle_or_badlands_if_hot_or_slope_if_cold);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[1], weirdness, 0.0f32, slope);
+                self.add_surface_biome(temperature, humidity, coast_to_near_inland, Parameter::span_of(erosions[2], erosions[3]), weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[2], weirdness, 0.0f32, plateau);
+                self.add_surface_biome(temperature, humidity, self.mid_inland_continentalness, erosions[3], weirdness, 0.0f32, middle_or_badlands_if_hot);
+                self.add_surface_biome(temperature, humidity, self.far_inland_continentalness, erosions[3], weirdness, 0.0f32, plateau);
+                self.add_surface_biome(temperature, humidity, coast_to_far_inland, erosions[4], weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, coast_to_near_inland, erosions[5], weirdness, 0.0f32, windswept_savanna);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[5], weirdness, 0.0f32, shattered);
+                self.add_surface_biome(temperature, humidity, coast_to_far_inland, erosions[6], weirdness, 0.0f32, middle);
+            }
+        }
+    }
+
+    fn add_mid_slice(&mut self, weirdness: Parameter) {
+        let (full_range, erosions) = (self.full_range, self.erosions);
+        let coast_to_far_inland = Parameter::span_of(self.coast_continentalness, self.far_inland_continentalness);
+        let coast_to_near_inland = Parameter::span_of(self.coast_continentalness, self.near_inland_continentalness);
+        let near_to_far_inland = Parameter::span_of(self.near_inland_continentalness, self.far_inland_continentalness);
+        let near_to_mid_inland = Parameter::span_of(self.near_inland_continentalness, self.mid_inland_continentalness);
+        let mid_to_far_inland = Parameter::span_of(self.mid_inland_continentalness, self.far_inland_continentalness);
+        self.add_surface_biome(full_range, full_range, self.coast_continentalness, Parameter::span_of(erosions[0], erosions[2]), weirdness, 0.0f32, Biome::StonyShore);
+        self.add_surface_biome(self.unfrozen_range, full_range, near_to_far_inland, erosions[6], weirdness, 0.0f32, Biome::Swamp);
+        for i in 0..self.temperatures.len() {
+            let temperature = self.temperatures[i];
+            for j in 0..self.humidities.len() {
+                let humidity = self.humidities[j];
+                let middle = pick_middle_biome(i, j, weirdness);
+                let middle_or_badlands_if_hot = pick_middle_biome_or_badlands_if_hot(i, j, weirdness);
+                let middle_or_badlands_if_hot_or_slope_if_cold = pick_middle_biome_or_badlands_if_hot_or_slope_if_cold(i, j, weirdness);
+                let shattered = pick_shattered_biome(i, j, weirdness);
+                let plateau = pick_plateau_biome(i, j, weirdness);
+                let beach = pick_beach_biome(i, j);
+                let windswept_savanna = maybe_pick_windswept_savanna_biome(i, j, weirdness, middle);
+                let shattered_coast = pick_shattered_coast_biome(i, j, weirdness);
+                let slope = pick_slope_biome(i, j, weirdness);
+                self.add_surface_biome(temperature, humidity, near_to_far_inland, erosions[0], weirdness, 0.0f32, slope);
+                self.add_surface_biome(temperature, humidity, near_to_mid_inland, erosions[1], weirdness, 0.0f32, middle_or_badlands_if_hot_or_slope_if_cold);
+                self.add_surface_biome(temperature, humidity, self.far_inland_continentalness, erosions[1], weirdness, 0.0f32, if i == 0 { slope } else { plateau });
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, erosions[2], weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, self.mid_inland_continentalness, erosions[2], weirdness, 0.0f32, middle_or_badlands_if_hot);
+                self.add_surface_biome(temperature, humidity, self.far_inland_continentalness, erosions[2], weirdness, 0.0f32, plateau);
+                self.add_surface_biome(temperature, humidity, coast_to_near_inland, erosions[3], weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[3], weirdness, 0.0f32, middle_or_badlands_if_hot);
+                if weirdness.get_max() < 0 {
+                    self.add_surface_biome(temperature, humidity, self.coast_continentalness, erosions[4], weirdness, 0.0f32, beach);
+                    self.add_surface_biome(temperature, humidity, near_to_far_inland, erosions[4], weirdness, 0.0f32, middle);
+                } else {
+                    self.add_surface_biome(temperature, humidity, coast_to_far_inland, erosions[4], weirdness, 0.0f32, middle);
+                }
+                self.add_surface_biome(temperature, humidity, self.coast_continentalness, erosions[5], weirdness, 0.0f32, shattered_coast);
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, erosions[5], weirdness, 0.0f32, windswept_savanna);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[5], weirdness, 0.0f32, shattered);
+                if weirdness.get_max() < 0 {
+                    self.add_surface_biome(temperature, humidity, self.coast_continentalness, erosions[6], weirdness, 0.0f32, beach);
+                } else {
+                    self.add_surface_biome(temperature, humidity, self.coast_continentalness, erosions[6], weirdness, 0.0f32, middle);
+                }
+                if i == 0 {
+                    self.add_surface_biome(temperature, humidity, near_to_far_inland, erosions[6], weirdness, 0.0f32, middle);
+                }
+            }
+        }
+    }
+
+    fn add_low_slice(&mut self, weirdness: Parameter) {
+        let (full_range, erosions) = (self.full_range, self.erosions);
+        let near_to_far_inland = Parameter::span_of(self.near_inland_continentalness, self.far_inland_continentalness);
+        let mid_to_far_inland = Parameter::span_of(self.mid_inland_continentalness, self.far_inland_continentalness);
+        self.add_surface_biome(full_range, full_range, self.coast_continentalness, Parameter::span_of(erosions[0], erosions[2]), weirdness, 0.0f32, Biome::StonyShore);
+        self.add_surface_biome(self.unfrozen_range, full_range, near_to_far_inland, erosions[6], weirdness, 0.0f32, Biome::Swamp);
+        for i in 0..self.temperatures.len() {
+            let temperature = self.temperatures[i];
+            for j in 0..self.humidities.len() {
+                let humidity = self.humidities[j];
+                let middle = pick_middle_biome(i, j, weirdness);
+                let middle_or_badlands_if_hot = pick_middle_biome_or_badlands_if_hot(i, j, weirdness);
+                let middle_or_badlands_if_hot_or_slope_if_cold = pick_middle_biome_or_badlands_if_hot_or_slope_if_cold(i, j, weirdness);
+                let beach = pick_beach_biome(i, j);
+                let windswept_savanna = maybe_pick_windswept_savanna_biome(i, j, weirdness, middle);
+                let shattered_coast = pick_shattered_coast_biome(i, j, weirdness);
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, Parameter::span_of(erosions[0], erosions[1]), weirdness, 0.0f32, middle_or_badlands_if_hot);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, Parameter::span_of(erosions[0], erosions[1]), weirdness, 0.0f32, middle_or_badlands_if_hot_or_slope_if_cold);
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, Parameter::span_of(erosions[2], erosions[3]), weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, Parameter::span_of(erosions[2], erosions[3]), weirdness, 0.0f32, middle_or_badlands_if_hot);
+                self.add_surface_biome(temperature, humidity, self.coast_continentalness, Parameter::span_of(erosions[3], erosions[4]), weirdness, 0.0f32, beach);
+                self.add_surface_biome(temperature, humidity, near_to_far_inland, erosions[4], weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, self.coast_continentalness, erosions[5], weirdness, 0.0f32, shattered_coast);
+                self.add_surface_biome(temperature, humidity, self.near_inland_continentalness, erosions[5], weirdness, 0.0f32, windswept_savanna);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, erosions[5], weirdness, 0.0f32, middle);
+                self.add_surface_biome(temperature, humidity, self.coast_continentalness, erosions[6], weirdness, 0.0f32, beach);
+                if i == 0 {
+                    self.add_surface_biome(temperature, humidity, near_to_far_inland, erosions[6], weirdness, 0.0f32, middle);
+                }
+            }
+        }
+    }
+
+    fn add_valleys(&mut self, weirdness: Parameter) {
+        let (full_range, frozen_range, unfrozen_range, erosions) = (self.full_range, self.frozen_range, self.unfrozen_range, self.erosions);
+        let coast = self.coast_continentalness;
+        let near_inland = self.near_inland_continentalness;
+        let coast_to_far_inland = Parameter::span_of(self.coast_continentalness, self.far_inland_continentalness);
+        let inland_to_far_inland = Parameter::span_of(self.inland_continentalness, self.far_inland_continentalness);
+        let mid_to_far_inland = Parameter::span_of(self.mid_inland_continentalness, self.far_inland_continentalness);
+        let eroded = Parameter::span_of(erosions[0], erosions[1]);
+        let negative = weirdness.get_max() < 0;
+        self.add_surface_biome(frozen_range, full_range, coast, eroded, weirdness, 0.0f32, if negative { Biome::StonyShore } else { Biome::FrozenRiver });
+        self.add_surface_biome(unfrozen_range, full_range, coast, eroded, weirdness, 0.0f32, if negative { Biome::StonyShore } else { Biome::River });
+        self.add_surface_biome(frozen_range, full_range, near_inland, eroded, weirdness, 0.0f32, Biome::FrozenRiver);
+        self.add_surface_biome(unfrozen_range, full_range, near_inland, eroded, weirdness, 0.0f32, Biome::River);
+        self.add_surface_biome(frozen_range, full_range, coast_to_far_inland, Parameter::span_of(erosions[2], erosions[5]), weirdness, 0.0f32, Biome::FrozenRiver);
+        self.add_surface_biome(unfrozen_range, full_range, coast_to_far_inland, Parameter::span_of(erosions[2], erosions[5]), weirdness, 0.0f32, Biome::River);
+        self.add_surface_biome(frozen_range, full_range, coast, erosions[6], weirdness, 0.0f32, Biome::FrozenRiver);
+        self.add_surface_biome(unfrozen_range, full_range, coast, erosions[6], weirdness, 0.0f32, Biome::River);
+        self.add_surface_biome(unfrozen_range, full_range, inland_to_far_inland, erosions[6], weirdness, 0.0f32, Biome::Swamp);
+        self.add_surface_biome(frozen_range, full_range, inland_to_far_inland, erosions[6], weirdness, 0.0f32, Biome::FrozenRiver);
+        for i in 0..self.temperatures.len() {
+            let temperature = self.temperatures[i];
+            for j in 0..self.humidities.len() {
+                let humidity = self.humidities[j];
+                let middle_or_badlands_if_hot = pick_middle_biome_or_badlands_if_hot(i, j, weirdness);
+                self.add_surface_biome(temperature, humidity, mid_to_far_inland, eroded, weirdness, 0.0f32, middle_or_badlands_if_hot);
+            }
+        }
+    }
+
+    fn add_underground_biomes(&mut self) {
+        let full_range = self.full_range;
+        self.add_underground_biome(full_range, full_range, Parameter::span(0.8f32, 1.0f32), full_range, full_range, 0.0f32, Biome::DripstoneCaves);
+        self.add_underground_biome(full_range, Parameter::span(0.7f32, 1.0f32), full_range, full_range, full_range, 0.0f32, Biome::LushCaves);
+    }
+
+    /// Adds the biome at the surface (depth 0) and one block layer below it (depth 1).
+    #[allow(clippy::too_many_arguments)]
+    fn add_surface_biome(&mut self, temperature: Parameter, humidity: Parameter, continentalness: Parameter, erosion: Parameter, weirdness: Parameter, offset: f32, biome: Biome) {
+        for depth in [0.0f32, 1.0f32].iter() {
+            let point = ParameterPoint::new(temperature, humidity, continentalness, erosion, Parameter::point(*depth), weirdness, offset);
+            self.values.push((point, biome));
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_underground_biome(&mut self, temperature: Parameter, humidity: Parameter, continentalness: Parameter, erosion: Parameter, weirdness: Parameter, offset: f32, biome: Biome) {
+        let point = ParameterPoint::new(temperature, humidity, continentalness, erosion, Parameter::span(0.2f32, 0.9f32), weirdness, offset);
+        self.values.push((point, biome));
+    }
+}
+
+fn pick_middle_biome(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    if weirdness.get_max() < 0 {
+        return MIDDLE_BIOMES[i][j];
+    }
+    MIDDLE_BIOMES_VARIANT[i][j].unwrap_or(MIDDLE_BIOMES[i][j])
+}
+
+fn pick_middle_biome_or_badlands_if_hot(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    if i == 4 { pick_badlands_biome(j, weirdness) } else { pick_middle_biome(i, j, weirdness) }
+}
+
+fn pick_middle_biome_or_badlands_if_hot_or_slope_if_cold(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    if i == 0 { pick_slope_biome(i, j, weirdness) } else { pick_middle_biome_or_badlands_if_hot(i, j, weirdness) }
+}
+
+fn maybe_pick_windswept_savanna_biome(i: usize, j: usize, weirdness: Parameter, biome: Biome) -> Biome {
+    if i > 1 && j < 4 && weirdness.get_max() >= 0 { Biome::WindsweptSavanna } else { biome }
+}
+
+fn pick_shattered_coast_biome(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    let biome = if weirdness.get_max() >= 0 { pick_middle_biome(i, j, weirdness) } else { pick_beach_biome(i, j) };
+    maybe_pick_windswept_savanna_biome(i, j, weirdness, biome)
+}
+
+fn pick_beach_biome(i: usize, _j: usize) -> Biome {
+    match i {
+        0 => Biome::SnowyBeach,
+        4 => Biome::Desert,
+        _ => Biome::Beach,
+    }
+}
+
+fn pick_badlands_biome(j: usize, weirdness: Parameter) -> Biome {
+    if j < 2 {
+        if weirdness.get_max() < 0 { Biome::ErodedBadlands } else { Biome::Badlands }
+    } else if j < 3 {
+        Biome::Badlands
+    } else {
+        Biome::WoodedBadlands
+    }
+}
+
+fn pick_plateau_biome(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    if weirdness.get_max() < 0 {
+        return PLATEAU_BIOMES[i][j];
+    }
+    PLATEAU_BIOMES_VARIANT[i][j].unwrap_or(PLATEAU_BIOMES[i][j])
+}
+
+fn pick_peak_biome(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    if i <= 2 {
+        if weirdness.get_max() < 0 { Biome::JaggedPeaks } else { Biome::FrozenPeaks }
+    } else if i == 3 {
+        Biome::StonyPeaks
+    } else {
+        pick_badlands_biome(j, weirdness)
+    }
+}
+
+fn pick_slope_biome(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    if i >= 3 {
+        return pick_plateau_biome(i, j, weirdness);
+    }
+    if j <= 1 { Biome::SnowySlopes } else { Biome::Grove }
+}
+
+fn pick_shattered_biome(i: usize, j: usize, weirdness: Parameter) -> Biome {
+    SHATTERED_BIOMES[i][j].unwrap_or_else(|| pick_middle_biome(i, j, weirdness))
+}
@@ -1,13 +1,23 @@
 #![allow(dead_code)]
 
+use alloc::format;
+use core::fmt;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
 
-use intmap::IntMap;
+#[cfg(feature = "std")]
+use crate::int_cache::IntCache;
 use java_random::LCG;
 use java_random::Random;
 
-use crate::math::wrap;
+use crate::math::{pow2, wrap};
 use crate::noise::Noise;
+use crate::noise_trace::{NoiseTrace, TraceKind};
+use crate::random_source::RandomSource;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "const_fn")]
 pub const SKIP_262: LCG = LCG::combine_java(262);
@@ -15,16 +25,33 @@ pub const SKIP_262: LCG = LCG::combine_java(262);
 pub const SKIP_262: LCG = LCG{ multiplier: 253119540505593, addend: 184089911826014 };
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PerlinNoise {
     lacunarity: f64,
     persistence: f64,
     noise_octaves: Vec<Option<Noise>>,
-    cache2d: IntMap<f64>,
+    amplitudes: Vec<f64>,
+    // 1.16.2+ layouts are sampled from the lowest frequency octave up
+    lowest_frequency_first: bool,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "IntCache::new"))]
+    cache2d: IntCache<f64>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     cache3d: HashMap<u128, f64>,
 }
 
+/// Why an octave list or an amplitude list can not make a noise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OctaveError {
+    Empty,
+    /// The octaves are not in increasing order.
+    Unsorted,
+    Duplicate(i32),
+}
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod perlin_test {
     use crate::create_range;
 
@@ -51,10 +78,95 @@ mod perlin_test {
         }
         assert_eq!(score, 2.5123135162530326);
     }
+
+    #[test]
+    fn test_sample_surface() {
+        // values of the 1.16.1 getSurfaceNoiseValue and getValue
+        let perlin = PerlinNoise::new(&mut Random::with_seed(1), create_range(-3, 0));
+        assert_eq!(perlin.sample_surface(1.5f64, -2.25f64, 0.0625f64, 1.5f64), -0.03196719901331806f64);
+        assert_eq!(perlin.sample_surface(6.25f64, 2.3125f64, 0.0625f64, 6.25f64), 0.15190243999368552f64);
+        assert_eq!(perlin.sample_default(1.5f64, 2.0f64, -3.25f64), 0.2901472439773032f64);
+    }
+
+    #[test]
+    fn test_legacy_amplitudes_match_octaves() {
+        let perlin = PerlinNoise::new(&mut Random::with_seed(1), create_range(-7, 0));
+        let amplitudes = PerlinNoise::create_legacy(&mut Random::with_seed(1), -7, vec![1.0f64; 8]);
+        for i in 0..8 {
+            assert_eq!(perlin.get_octave(i).map(|noise| noise.x0), amplitudes.get_octave(i).map(|noise| noise.x0));
+        }
+        let value = perlin.sample_default(12.5f64, 3.25f64, -7.75f64);
+        assert!((value - amplitudes.sample_default(12.5f64, 3.25f64, -7.75f64)).abs() < 1E-15);
+    }
+
+    #[test]
+    fn test_check_octaves() {
+        assert_eq!(check_octaves(&create_range(-7, 0)), Ok(()));
+        assert_eq!(check_octaves(&[-3, 1, 2]), Ok(()));
+        assert_eq!(check_octaves(&[]), Err(OctaveError::Empty));
+        assert_eq!(check_octaves(&[0, -7]), Err(OctaveError::Unsorted));
+        assert_eq!(check_octaves(&[-7, -3, -3, 0]), Err(OctaveError::Duplicate(-3)));
+        assert_eq!(check_amplitudes(&[]), Err(OctaveError::Empty));
+        assert_eq!(check_amplitudes(&[0.0f64]), Ok(()));
+    }
+
+    #[test]
+    fn test_create_skips_zero_amplitudes() {
+        let perlin = PerlinNoise::create(&mut Random::with_seed(1), -3, vec![1.0f64, 0.0f64, 2.0f64]);
+        assert!(perlin.get_octave(0).is_some());
+        assert!(perlin.get_octave(1).is_none());
+        assert!(perlin.get_octave(2).is_some());
+    }
+}
+
+impl fmt::Display for OctaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OctaveError::Empty => write!(f, "at least one octave is needed"),
+            OctaveError::Unsorted => write!(f, "octaves must be in increasing order"),
+            OctaveError::Duplicate(octave) => write!(f, "octave {} is repeated", octave),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OctaveError {}
+
+/// Checks an octave list of [`PerlinNoise::new`], which panics on an empty or unsorted one.
+pub fn check_octaves(octaves: &[i32]) -> Result<(), OctaveError> {
+    if octaves.is_empty() {
+        return Err(OctaveError::Empty);
+    }
+    for pair in octaves.windows(2) {
+        if pair[0] == pair[1] {
+            return Err(OctaveError::Duplicate(pair[0]));
+        }
+        if pair[0] > pair[1] {
+            return Err(OctaveError::Unsorted);
+        }
+    }
+    Ok(())
+}
+
+/// Checks an amplitude list of [`PerlinNoise::create_legacy`] and [`PerlinNoise::create`].
+pub fn check_amplitudes(amplitudes: &[f64]) -> Result<(), OctaveError> {
+    if amplitudes.is_empty() {
+        return Err(OctaveError::Empty);
+    }
+    Ok(())
 }
 
 impl PerlinNoise {
     pub fn new(random: &mut Random, octaves: Vec<i32>) -> PerlinNoise {
+        Self::build(random, octaves, None)
+    }
+
+    /// `PerlinNoise::new`, recording the random calls of each octave in the trace.
+    pub fn new_traced(random: &mut Random, octaves: Vec<i32>, trace: &mut NoiseTrace) -> PerlinNoise {
+        Self::build(random, octaves, Some(trace))
+    }
+
+    fn build(random: &mut Random, octaves: Vec<i32>, mut trace: Option<&mut NoiseTrace>) -> PerlinNoise {
         if octaves.is_empty() {
             panic!("No octaves defined")
         }
@@ -64,37 +176,124 @@ impl PerlinNoise {
         if length < 1 {
             panic!("You need at least one octave")
         }
+        let mut record = |kind: TraceKind, octave: Option<i32>, state_before: u64, random: &Random| {
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.record(kind, octave, state_before, random.get_raw_seed());
+            }
+        };
+        let mut state_before = random.get_raw_seed();
         let noise: Noise = Noise::new(random);
         let mut noise_octaves: Vec<Option<Noise>> = vec![None; length as usize];
 
         if end >= 0 && end < length && octaves.contains(&0) {
             noise_octaves[end as usize] = Option::from(noise.clone());
+            record(TraceKind::Built, Some(0), state_before, random);
+        } else {
+            record(TraceKind::Discarded, Some(0), state_before, random);
         }
         for i in end + 1..length {
+            state_before = random.get_raw_seed();
             if i >= 0 && octaves.contains(&(end - i)) {
                 noise_octaves[i as usize] = Option::from(Noise::new(random));
+                record(TraceKind::Built, Some(end - i), state_before, random);
                 continue;
             }
             random.advance(SKIP_262);
+            record(TraceKind::Skipped, Some(end - i), state_before, random);
         }
         if end > 0 {
             let noise_seed: i64 = (noise.get_noise_value(0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64) * 9.223372036854776E18) as i64;
+            state_before = random.get_raw_seed();
             random.set_seed(noise_seed as u64);
+            record(TraceKind::Reseeded, None, state_before, random);
             for i in (0..end).rev() {
+                state_before = random.get_raw_seed();
                 if i < length && octaves.contains(&(end - i)) {
                     noise_octaves[i as usize] = Option::from(Noise::new(random));
+                    record(TraceKind::Built, Some(end - i), state_before, random);
                 } else {
                     random.advance(SKIP_262);
+                    record(TraceKind::Skipped, Some(end - i), state_before, random);
                 }
             }
         }
-        let persistence: f64 = 2f64.powi(end);
-        let lacunarity: f64 = 1.0f64 / (2f64.powi(length) - 1.0);
+        let persistence: f64 = pow2(end);
+        let lacunarity: f64 = 1.0f64 / (pow2(length) - 1.0);
         PerlinNoise {
             lacunarity,
             persistence,
+            amplitudes: vec![1.0f64; noise_octaves.len()],
             noise_octaves,
-            cache2d: IntMap::with_capacity(1024),
+            lowest_frequency_first: false,
+            #[cfg(feature = "std")]
+            cache2d: IntCache::with_capacity(1024),
+            #[cfg(feature = "std")]
+            cache3d: Default::default(),
+        }
+    }
+
+    /// Octaves `first_octave..first_octave + amplitudes.len()` drawn one after the other from `random`,
+    /// octaves with a zero amplitude are skipped (1.16.2-1.17 and the legacy 1.18+ layout).
+    pub fn create_legacy<R: RandomSource>(random: &mut R, first_octave: i32, amplitudes: Vec<f64>) -> PerlinNoise {
+        let length = amplitudes.len() as i32;
+        if length < 1 {
+            panic!("You need at least one octave")
+        }
+        let start = -first_octave;
+        let noise: Noise = Noise::new(random);
+        let mut noise_octaves: Vec<Option<Noise>> = vec![None; length as usize];
+        if start >= 0 && start < length && amplitudes[start as usize] != 0.0f64 {
+            noise_octaves[start as usize] = Option::from(noise.clone());
+        }
+        for i in (0..start).rev() {
+            if i < length && amplitudes[i as usize] != 0.0f64 {
+                noise_octaves[i as usize] = Option::from(Noise::new(random));
+            } else {
+                random.consume_count(262);
+            }
+        }
+        if start < length - 1 {
+            let noise_seed: i64 = (noise.get_noise_value(0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64) * 9.223372036854776E18) as i64;
+            let mut positive_random = Random::with_seed(noise_seed as u64);
+            for i in start + 1..length {
+                if i >= 0 && amplitudes[i as usize] != 0.0f64 {
+                    noise_octaves[i as usize] = Option::from(Noise::new(&mut positive_random));
+                } else {
+                    positive_random.advance(SKIP_262);
+                }
+            }
+        }
+        Self::from_octaves(first_octave, amplitudes, noise_octaves)
+    }
+
+    /// Octaves `first_octave..first_octave + amplitudes.len()` each seeded from the name of the
+    /// octave through a positional random forked from `random` (1.18+ layout).
+    pub fn create<R: RandomSource>(random: &mut R, first_octave: i32, amplitudes: Vec<f64>) -> PerlinNoise {
+        if amplitudes.is_empty() {
+            panic!("You need at least one octave")
+        }
+        let factory = random.fork_positional();
+        let noise_octaves: Vec<Option<Noise>> = amplitudes.iter().enumerate().map(|(i, &amplitude)| {
+            if amplitude != 0.0f64 {
+                Some(Noise::new(&mut factory.from_hash_of(&format!("octave_{}", first_octave + i as i32))))
+            } else {
+                None
+            }
+        }).collect();
+        Self::from_octaves(first_octave, amplitudes, noise_octaves)
+    }
+
+    fn from_octaves(first_octave: i32, amplitudes: Vec<f64>, noise_octaves: Vec<Option<Noise>>) -> PerlinNoise {
+        let length = amplitudes.len() as i32;
+        PerlinNoise {
+            lacunarity: pow2(length - 1) / (pow2(length) - 1.0f64),
+            persistence: pow2(first_octave),
+            noise_octaves,
+            amplitudes,
+            lowest_frequency_first: true,
+            #[cfg(feature = "std")]
+            cache2d: IntCache::with_capacity(1024),
+            #[cfg(feature = "std")]
             cache3d: Default::default(),
         }
     }
@@ -103,15 +302,16 @@ impl PerlinNoise {
         self.sample(x, y, z, 0.0f64, 0.0f64, false)
     }
 
+    #[allow(clippy::needless_return)]
     pub fn sample(&self, x: f64, y: f64, z: f64, y_amplification: f64, y_min: f64, use_default_y: bool) -> f64 {
         let mut noise_value: f64 = 0.0f64;
         // contribution of each octaves to the final noise, diminished by a factor of 2 (or increased by factor of 0.5)
         let mut persistence: f64 = self.persistence;
         // distance between octaves, increased for each by a factor of 2
         let mut lacunarity: f64 = self.lacunarity;
-        for sampler in &self.noise_octaves {
+        for (sampler, amplitude) in self.noise_octaves.iter().zip(&self.amplitudes) {
             if let Some(noise) = sampler {
-                noise_value += noise.get_noise_value(
+                noise_value += amplitude * noise.get_noise_value(
                     wrap(x * persistence),
                     if use_default_y { -noise.y0 } else { wrap(y * persistence) },
                     wrap(z * persistence),
@@ -119,14 +319,30 @@ impl PerlinNoise {
                     y_min * persistence) * lacunarity;
             }
 
-            persistence /= 2.0f64;
-            lacunarity *= 2.0f64;
+            if self.lowest_frequency_first {
+                persistence *= 2.0f64;
+                lacunarity /= 2.0f64;
+            } else {
+                persistence /= 2.0f64;
+                lacunarity *= 2.0f64;
+            }
         }
 
         return noise_value;
     }
 
-    pub fn sample_surface(&self, x: f64, z: f64, y_amplification: f64, y_min: f64) -> f64 {
-        self.sample(x, 0.0f64, z, y_amplification, y_min, false)
+    /// Octave at the given index, index 0 being the highest frequency one.
+    pub fn get_octave(&self, index: usize) -> Option<&Noise> {
+        let index = if self.lowest_frequency_first {
+            self.noise_octaves.len().checked_sub(index + 1)?
+        } else {
+            index
+        };
+        self.noise_octaves.get(index).and_then(Option::as_ref)
+    }
+
+    /// Vanilla `getSurfaceNoiseValue`, sampling `x`, `y` at z 0 (callers pass the block z as `y`).
+    pub fn sample_surface(&self, x: f64, y: f64, y_amplification: f64, y_min: f64) -> f64 {
+        self.sample(x, y, 0.0f64, y_amplification, y_min, false)
     }
 }
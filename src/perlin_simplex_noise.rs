@@ -0,0 +1,113 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use java_random::Random;
+
+use crate::math::pow2;
+use crate::noise::Noise;
+use crate::perlin_noise::SKIP_262;
+use crate::simplex_noise::SimplexNoise;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Octaves of 2d simplex noise (`PerlinSimplexNoise`), the overworld surface noise of 1.13-1.17
+/// and the noise behind the badlands bands, icebergs and swamp puddles.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PerlinSimplexNoise {
+    noise_levels: Vec<Option<SimplexNoise>>,
+    highest_freq_input_factor: f64,
+    highest_freq_value_factor: f64,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod perlin_simplex_test {
+    use super::*;
+
+    #[test]
+    fn test_single_octave() {
+        let noise = PerlinSimplexNoise::new(&mut Random::with_seed(12), vec![0]);
+        let simplex = SimplexNoise::init(Random::with_seed(12));
+        assert_eq!(noise.get_value(0.5f64, 100.0f64, false), simplex._get_value_2d(0.5f64, 100.0f64));
+        assert_eq!(noise.get_value(0.5f64, 100.0f64, true), simplex._get_value_2d(0.5f64 + simplex.get_x0(), 100.0f64 + simplex.get_y0()));
+    }
+
+    #[test]
+    fn test_octaves() {
+        let noise = PerlinSimplexNoise::new(&mut Random::with_seed(12), vec![-2, 0]);
+        let mut random = Random::with_seed(12);
+        let first = SimplexNoise::new(Noise::new(&mut random));
+        // the octave -1 is skipped
+        random.advance(SKIP_262);
+        let lowest = SimplexNoise::new(Noise::new(&mut random));
+        // octave 0 weights 1/7 and octave -2, sampled at a quarter of the frequency, 4/7
+        let expected = first._get_value_2d(3.0f64, -5.0f64) / 7.0f64 + lowest._get_value_2d(0.75f64, -1.25f64) * 4.0f64 / 7.0f64;
+        assert_eq!(noise.get_value(3.0f64, -5.0f64, false), expected);
+    }
+}
+
+impl PerlinSimplexNoise {
+    pub fn new(random: &mut Random, octaves: Vec<i32>) -> PerlinSimplexNoise {
+        if octaves.is_empty() {
+            panic!("No octaves defined")
+        }
+        let start = -*octaves.first().expect("Missing an element");
+        let end = *octaves.last().expect("Missing an element");
+        let length = start + end + 1;
+        if length < 1 {
+            panic!("You need at least one octave")
+        }
+        let mut simplex_noise = SimplexNoise::new(Noise::new(random));
+        let mut noise_levels: Vec<Option<SimplexNoise>> = vec![None; length as usize];
+        if end >= 0 && end < length && octaves.contains(&0) {
+            noise_levels[end as usize] = Some(simplex_noise.clone());
+        }
+        for i in end + 1..length {
+            if i >= 0 && octaves.contains(&(end - i)) {
+                noise_levels[i as usize] = Some(SimplexNoise::new(Noise::new(random)));
+            } else {
+                random.advance(SKIP_262);
+            }
+        }
+        if end > 0 {
+            let (x0, y0, z0) = simplex_noise.get_coordinates();
+            let noise_seed: i64 = (simplex_noise.get_value_3d(x0, y0, z0) * 9.223372036854776E18) as i64;
+            let mut random = Random::with_seed(noise_seed as u64);
+            for i in (0..end).rev() {
+                if i < length && octaves.contains(&(end - i)) {
+                    noise_levels[i as usize] = Some(SimplexNoise::new(Noise::new(&mut random)));
+                } else {
+                    random.advance(SKIP_262);
+                }
+            }
+        }
+        PerlinSimplexNoise {
+            noise_levels,
+            highest_freq_input_factor: pow2(end),
+            highest_freq_value_factor: 1.0f64 / (pow2(length) - 1.0f64),
+        }
+    }
+
+    /// The octaves summed from the highest frequency one, `use_noise_offsets` shifts each by its
+    /// own origin.
+    pub fn get_value(&self, x: f64, y: f64, use_noise_offsets: bool) -> f64 {
+        let mut value: f64 = 0.0f64;
+        let mut input_factor: f64 = self.highest_freq_input_factor;
+        let mut value_factor: f64 = self.highest_freq_value_factor;
+        for simplex_noise in self.noise_levels.iter() {
+            if let Some(simplex_noise) = simplex_noise {
+                let (x0, y0) = if use_noise_offsets { (simplex_noise.get_x0(), simplex_noise.get_y0()) } else { (0.0f64, 0.0f64) };
+                value += simplex_noise._get_value_2d(x * input_factor + x0, y * input_factor + y0) * value_factor;
+            }
+            input_factor /= 2.0f64;
+            value_factor *= 2.0f64;
+        }
+        value
+    }
+
+    /// Vanilla `getSurfaceNoiseValue`, the height arguments of the perlin version do not apply.
+    pub fn get_surface_noise_value(&self, x: f64, y: f64) -> f64 {
+        self.get_value(x, y, true) * 0.55f64
+    }
+}
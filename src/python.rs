@@ -0,0 +1,206 @@
+//! Python extension module, built with `maturin build --features python` (see `pyproject.toml`).
+//!
+//! The `*_many` methods take NumPy arrays of coordinates and return a NumPy array of results.
+
+// the pyo3 0.22 method macros convert the returned errors into themselves
+#![allow(clippy::useless_conversion)]
+
+use java_random::Random;
+use numpy::{PyArray1, PyArray2, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::bindings;
+use crate::double_perlin_noise::DoublePerlinNoise;
+use crate::perlin_noise::{OctaveError, PerlinNoise};
+use crate::simplex_noise::SimplexNoise;
+use crate::voronoi::{Voronoi, VoronoiMode};
+
+fn check_lengths(lengths: &[usize]) -> PyResult<usize> {
+    bindings::common_length(lengths).ok_or_else(|| PyValueError::new_err(bindings::LENGTH_MISMATCH))
+}
+
+fn value_error(error: OctaveError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn sample_many<'py, F: FnMut(f64, f64, f64) -> f64>(py: Python<'py>, xs: PyReadonlyArray1<f64>, ys: PyReadonlyArray1<f64>, zs: PyReadonlyArray1<f64>, mut sample: F) -> PyResult<Bound<'py, PyArray1<f64>>> {
+    let (xs, ys, zs) = (xs.as_array(), ys.as_array(), zs.as_array());
+    let count = check_lengths(&[xs.len(), ys.len(), zs.len()])?;
+    let values: Vec<f64> = (0..count).map(|i| sample(xs[i], ys[i], zs[i])).collect();
+    Ok(PyArray1::from_vec_bound(py, values))
+}
+
+#[pyclass(name = "PerlinNoise", module = "noise_rs")]
+struct PyPerlinNoise {
+    noise: PerlinNoise,
+}
+
+#[pymethods]
+impl PyPerlinNoise {
+    /// The 1.16.1 noise of the java random `seed` and the `octaves` list, for example `range(-7, 1)`.
+    #[new]
+    fn new(seed: i64, octaves: Vec<i32>) -> PyResult<Self> {
+        let noise = bindings::perlin_with_octaves(seed, octaves).map_err(value_error)?;
+        Ok(PyPerlinNoise { noise })
+    }
+
+    /// The 1.16.2+ noise of the java random `seed`, the first octave and the octave amplitudes.
+    #[staticmethod]
+    fn create_legacy(seed: i64, first_octave: i32, amplitudes: Vec<f64>) -> PyResult<Self> {
+        let noise = bindings::perlin_with_amplitudes(seed, first_octave, amplitudes).map_err(value_error)?;
+        Ok(PyPerlinNoise { noise })
+    }
+
+    fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.sample_default(x, y, z)
+    }
+
+    fn sample_many<'py>(&self, py: Python<'py>, xs: PyReadonlyArray1<f64>, ys: PyReadonlyArray1<f64>, zs: PyReadonlyArray1<f64>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        sample_many(py, xs, ys, zs, |x, y, z| self.noise.sample_default(x, y, z))
+    }
+}
+
+#[pyclass(name = "DoublePerlinNoise", module = "noise_rs")]
+struct PyDoublePerlinNoise {
+    noise: DoublePerlinNoise,
+}
+
+#[pymethods]
+impl PyDoublePerlinNoise {
+    /// The 1.16.1 noise of the java random `seed` and the `octaves` list.
+    #[new]
+    fn new(seed: i64, octaves: Vec<i32>) -> PyResult<Self> {
+        let noise = bindings::double_perlin_with_octaves(seed, octaves).map_err(value_error)?;
+        Ok(PyDoublePerlinNoise { noise })
+    }
+
+    /// The 1.16.2+ noise of the java random `seed`, the first octave and the octave amplitudes.
+    #[staticmethod]
+    fn create_legacy(seed: i64, first_octave: i32, amplitudes: Vec<f64>) -> PyResult<Self> {
+        let noise = bindings::double_perlin_with_amplitudes(seed, first_octave, amplitudes).map_err(value_error)?;
+        Ok(PyDoublePerlinNoise { noise })
+    }
+
+    /// The 1.18+ noise registered as `name` (for example `minecraft:temperature`) in a world of `world_seed`.
+    #[staticmethod]
+    #[pyo3(signature = (world_seed, name, first_octave, amplitudes, legacy_random_source = false))]
+    fn instantiate(world_seed: i64, name: &str, first_octave: i32, amplitudes: Vec<f64>, legacy_random_source: bool) -> PyResult<Self> {
+        let noise = bindings::double_perlin_with_parameters(world_seed, legacy_random_source, name, first_octave, amplitudes).map_err(value_error)?;
+        Ok(PyDoublePerlinNoise { noise })
+    }
+
+    fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.sample(x, y, z)
+    }
+
+    fn sample_many<'py>(&self, py: Python<'py>, xs: PyReadonlyArray1<f64>, ys: PyReadonlyArray1<f64>, zs: PyReadonlyArray1<f64>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        sample_many(py, xs, ys, zs, |x, y, z| self.noise.sample(x, y, z))
+    }
+}
+
+#[pyclass(name = "SimplexNoise", module = "noise_rs")]
+struct PySimplexNoise {
+    noise: SimplexNoise,
+}
+
+#[pymethods]
+impl PySimplexNoise {
+    #[new]
+    fn new(seed: i64) -> Self {
+        PySimplexNoise { noise: SimplexNoise::init(Random::with_seed(seed as u64)) }
+    }
+
+    fn sample_2d(&mut self, x: f64, z: f64) -> f64 {
+        self.noise.get_value_2d(x, z)
+    }
+
+    fn sample_3d(&mut self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.get_value_3d(x, y, z)
+    }
+
+    fn sample_2d_many<'py>(&mut self, py: Python<'py>, xs: PyReadonlyArray1<f64>, zs: PyReadonlyArray1<f64>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let (xs, zs) = (xs.as_array(), zs.as_array());
+        let count = check_lengths(&[xs.len(), zs.len()])?;
+        let values: Vec<f64> = (0..count).map(|i| self.noise.get_value_2d(xs[i], zs[i])).collect();
+        Ok(PyArray1::from_vec_bound(py, values))
+    }
+
+    fn sample_3d_many<'py>(&mut self, py: Python<'py>, xs: PyReadonlyArray1<f64>, ys: PyReadonlyArray1<f64>, zs: PyReadonlyArray1<f64>) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let noise = &mut self.noise;
+        sample_many(py, xs, ys, zs, |x, y, z| noise.get_value_3d(x, y, z))
+    }
+}
+
+#[pyclass(name = "Voronoi", module = "noise_rs")]
+struct PyVoronoi {
+    voronoi: Voronoi,
+}
+
+#[pymethods]
+impl PyVoronoi {
+    /// The biome zoom of a world, `column` selecting the y independent 1.15-1.17 overworld zoom.
+    #[new]
+    #[pyo3(signature = (world_seed, column = false))]
+    fn new(world_seed: i64, column: bool) -> Self {
+        let mode = if column { VoronoiMode::Column } else { VoronoiMode::Fuzzy };
+        PyVoronoi { voronoi: Voronoi::with_mode(world_seed, mode) }
+    }
+
+    /// The biome (quart) position the block at `x`, `y`, `z` takes its biome from.
+    fn get_fuzzy_positions(&mut self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        self.voronoi.get_fuzzy_positions(x, y, z)
+    }
+
+    /// The biome positions of many blocks, as an `n x 3` array.
+    fn get_fuzzy_positions_many<'py>(&mut self, py: Python<'py>, xs: PyReadonlyArray1<i32>, ys: PyReadonlyArray1<i32>, zs: PyReadonlyArray1<i32>) -> PyResult<Bound<'py, PyArray2<i32>>> {
+        let (xs, ys, zs) = (xs.as_array(), ys.as_array(), zs.as_array());
+        let count = check_lengths(&[xs.len(), ys.len(), zs.len()])?;
+        let positions: Vec<Vec<i32>> = (0..count)
+            .map(|i| {
+                let (x, y, z) = self.voronoi.get_fuzzy_positions(xs[i], ys[i], zs[i]);
+                vec![x, y, z]
+            })
+            .collect();
+        if positions.is_empty() {
+            return Ok(PyArray2::zeros_bound(py, [0, 3], false));
+        }
+        PyArray2::from_vec2_bound(py, &positions).map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+}
+
+#[pymodule]
+fn noise_rs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyPerlinNoise>()?;
+    module.add_class::<PyDoublePerlinNoise>()?;
+    module.add_class::<PySimplexNoise>()?;
+    module.add_class::<PyVoronoi>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod python_test {
+    use super::*;
+
+    // the constructors and batch methods need a python interpreter, the wrappers are built
+    // directly and sampled through their single value methods
+    #[test]
+    fn test_samples_match_rust() {
+        let perlin = PyPerlinNoise { noise: bindings::perlin_with_octaves(1, vec![-3, -2, -1, 0]).unwrap() };
+        let expected = PerlinNoise::new(&mut Random::with_seed(1), vec![-3, -2, -1, 0]);
+        assert_eq!(perlin.sample(1.5f64, -2.25f64, 7.0f64), expected.sample_default(1.5f64, -2.25f64, 7.0f64));
+
+        let double_perlin = PyDoublePerlinNoise { noise: bindings::double_perlin_with_amplitudes(1, -7, vec![1.0f64, 1.0f64]).unwrap() };
+        let expected = DoublePerlinNoise::create_legacy(&mut Random::with_seed(1), -7, vec![1.0f64, 1.0f64]);
+        assert_eq!(double_perlin.sample(100.0f64, 0.0f64, -35.5f64), expected.sample(100.0f64, 0.0f64, -35.5f64));
+
+        let mut simplex = PySimplexNoise::new(1);
+        let mut expected = SimplexNoise::init(Random::with_seed(1));
+        assert_eq!(simplex.sample_2d(3.25f64, -8.5f64), expected.get_value_2d(3.25f64, -8.5f64));
+        assert_eq!(simplex.sample_3d(3.25f64, 1.0f64, -8.5f64), expected.get_value_3d(3.25f64, 1.0f64, -8.5f64));
+
+        let mut voronoi = PyVoronoi::new(1, true);
+        let mut expected = Voronoi::with_mode(1, VoronoiMode::Column);
+        assert_eq!(voronoi.get_fuzzy_positions(100, 64, -20), expected.get_fuzzy_positions(100, 64, -20));
+    }
+}
@@ -0,0 +1,290 @@
+use java_random::{LCG, Random};
+use md5::{Digest, Md5};
+
+/// The random calls noise construction needs, implemented by the legacy java LCG and Xoroshiro128++.
+pub trait RandomSource {
+    fn next_int(&mut self) -> i32;
+    fn next_int_n(&mut self, bound: i32) -> i32;
+    fn next_long(&mut self) -> i64;
+    fn next_boolean(&mut self) -> bool;
+    fn next_float(&mut self) -> f32;
+    fn next_double(&mut self) -> f64;
+    fn consume_count(&mut self, count: i32);
+    fn fork_positional(&mut self) -> PositionalRandomFactory;
+}
+
+impl RandomSource for Random {
+    fn next_int(&mut self) -> i32 {
+        Random::next_int(self)
+    }
+
+    fn next_int_n(&mut self, bound: i32) -> i32 {
+        Random::next_int_n(self, bound)
+    }
+
+    fn next_long(&mut self) -> i64 {
+        Random::next_long(self)
+    }
+
+    fn next_boolean(&mut self) -> bool {
+        Random::next_boolean(self)
+    }
+
+    fn next_float(&mut self) -> f32 {
+        Random::next_float(self)
+    }
+
+    fn next_double(&mut self) -> f64 {
+        Random::next_double(self)
+    }
+
+    fn consume_count(&mut self, count: i32) {
+        self.advance(LCG::combine_java(count as u64));
+    }
+
+    fn fork_positional(&mut self) -> PositionalRandomFactory {
+        PositionalRandomFactory::Legacy(Random::next_long(self))
+    }
+}
+
+#[cfg(test)]
+mod random_source_test {
+    use super::*;
+
+    #[test]
+    fn test_hashes() {
+        assert_eq!(java_string_hash("minecraft:temperature"), -549971161);
+        assert_eq!(get_seed(123456, -64, -987654), 63092131959973);
+    }
+
+    #[test]
+    fn test_xoroshiro() {
+        let mut random = XoroshiroRandom::with_raw_seed(1, 2);
+        assert_eq!(random.next_long(), 393217);
+        let factory = PositionalRandomFactory::Xoroshiro(0, 0);
+        match factory.from_hash_of("octave_-4") {
+            WorldgenRandom::Xoroshiro(random) => assert_eq!(random.get_raw_seed(), (-4787091969620723870, -4579290196374760307)),
+            WorldgenRandom::Legacy(_) => unreachable!(),
+        }
+    }
+}
+
+pub fn mix_stafford_13(mut value: i64) -> i64 {
+    value = (value ^ ((value as u64) >> 30) as i64).wrapping_mul(-4658895280553007687i64);
+    value = (value ^ ((value as u64) >> 27) as i64).wrapping_mul(-7723592293110705685i64);
+    value ^ ((value as u64) >> 31) as i64
+}
+
+/// Vanilla `Mth.getSeed`, the seed of the positional random at a block.
+pub fn get_seed(x: i32, y: i32, z: i32) -> i64 {
+    let mut seed: i64 = (x.wrapping_mul(3129871) as i64) ^ (z as i64).wrapping_mul(116129781i64) ^ (y as i64);
+    seed = seed.wrapping_mul(seed).wrapping_mul(42317861i64).wrapping_add(seed.wrapping_mul(11i64));
+    seed >> 16
+}
+
+/// Java `String::hashCode`.
+pub fn java_string_hash(string: &str) -> i32 {
+    string.encode_utf16().fold(0i32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as i32))
+}
+
+/// The Xoroshiro128++ random of 1.18+ worldgen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XoroshiroRandom {
+    seed_lo: i64,
+    seed_hi: i64,
+}
+
+impl XoroshiroRandom {
+    pub fn new(seed: i64) -> Self {
+        let seed_lo: i64 = seed ^ 7640891576956012809i64;
+        let seed_hi: i64 = seed_lo.wrapping_add(-7046029254386353131i64);
+        Self::with_raw_seed(mix_stafford_13(seed_lo), mix_stafford_13(seed_hi))
+    }
+
+    pub fn with_raw_seed(seed_lo: i64, seed_hi: i64) -> Self {
+        if seed_lo == 0 && seed_hi == 0 {
+            return XoroshiroRandom { seed_lo: -7046029254386353131i64, seed_hi: 7640891576956012809i64 };
+        }
+        XoroshiroRandom { seed_lo, seed_hi }
+    }
+
+    pub fn get_raw_seed(&self) -> (i64, i64) {
+        (self.seed_lo, self.seed_hi)
+    }
+
+    pub fn fork(&mut self) -> XoroshiroRandom {
+        let seed_lo = self.next_long();
+        let seed_hi = self.next_long();
+        XoroshiroRandom::with_raw_seed(seed_lo, seed_hi)
+    }
+
+    fn next_bits(&mut self, bits: u32) -> i64 {
+        ((self.next_long() as u64) >> (64 - bits)) as i64
+    }
+}
+
+impl RandomSource for XoroshiroRandom {
+    fn next_int(&mut self) -> i32 {
+        self.next_long() as i32
+    }
+
+    fn next_int_n(&mut self, bound: i32) -> i32 {
+        if bound <= 0 {
+            panic!("Bound must be positive");
+        }
+        let mut value: u64 = self.next_int() as u32 as u64;
+        let mut product: u64 = value * bound as u64;
+        let mut low: u64 = product & 0xFFFF_FFFF;
+        if low < bound as u64 {
+            let threshold: u64 = ((bound as u32).wrapping_neg() % bound as u32) as u64;
+            while low < threshold {
+                value = self.next_int() as u32 as u64;
+                product = value * bound as u64;
+                low = product & 0xFFFF_FFFF;
+            }
+        }
+        (product >> 32) as i32
+    }
+
+    fn next_long(&mut self) -> i64 {
+        let seed_lo: i64 = self.seed_lo;
+        let mut seed_hi: i64 = self.seed_hi;
+        let value: i64 = seed_lo.wrapping_add(seed_hi).rotate_left(17).wrapping_add(seed_lo);
+        seed_hi ^= seed_lo;
+        self.seed_lo = seed_lo.rotate_left(49) ^ seed_hi ^ (seed_hi << 21);
+        self.seed_hi = seed_hi.rotate_left(28);
+        value
+    }
+
+    fn next_boolean(&mut self) -> bool {
+        (self.next_long() & 1) != 0
+    }
+
+    fn next_float(&mut self) -> f32 {
+        self.next_bits(24) as f32 * 5.9604645E-8f32
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next_bits(53) as f64 * 1.1102230246251565E-16f64
+    }
+
+    fn consume_count(&mut self, count: i32) {
+        for _ in 0..count {
+            self.next_long();
+        }
+    }
+
+    fn fork_positional(&mut self) -> PositionalRandomFactory {
+        let seed_lo = self.next_long();
+        let seed_hi = self.next_long();
+        PositionalRandomFactory::Xoroshiro(seed_lo, seed_hi)
+    }
+}
+
+/// A random of either kind, as handed out by a [`PositionalRandomFactory`].
+#[derive(Clone, Copy, Debug)]
+pub enum WorldgenRandom {
+    Legacy(Random),
+    Xoroshiro(XoroshiroRandom),
+}
+
+impl WorldgenRandom {
+    /// The root random of a world, `legacy` being the `legacy_random_source` flag of the noise settings.
+    pub fn new(seed: i64, legacy: bool) -> Self {
+        if legacy {
+            WorldgenRandom::Legacy(Random::with_seed(seed as u64))
+        } else {
+            WorldgenRandom::Xoroshiro(XoroshiroRandom::new(seed))
+        }
+    }
+}
+
+impl RandomSource for WorldgenRandom {
+    fn next_int(&mut self) -> i32 {
+        match self {
+            WorldgenRandom::Legacy(random) => RandomSource::next_int(random),
+            WorldgenRandom::Xoroshiro(random) => random.next_int(),
+        }
+    }
+
+    fn next_int_n(&mut self, bound: i32) -> i32 {
+        match self {
+            WorldgenRandom::Legacy(random) => RandomSource::next_int_n(random, bound),
+            WorldgenRandom::Xoroshiro(random) => random.next_int_n(bound),
+        }
+    }
+
+    fn next_long(&mut self) -> i64 {
+        match self {
+            WorldgenRandom::Legacy(random) => RandomSource::next_long(random),
+            WorldgenRandom::Xoroshiro(random) => random.next_long(),
+        }
+    }
+
+    fn next_boolean(&mut self) -> bool {
+        match self {
+            WorldgenRandom::Legacy(random) => RandomSource::next_boolean(random),
+            WorldgenRandom::Xoroshiro(random) => random.next_boolean(),
+        }
+    }
+
+    fn next_float(&mut self) -> f32 {
+        match self {
+            WorldgenRandom::Legacy(random) => RandomSource::next_float(random),
+            WorldgenRandom::Xoroshiro(random) => random.next_float(),
+        }
+    }
+
+    fn next_double(&mut self) -> f64 {
+        match self {
+            WorldgenRandom::Legacy(random) => RandomSource::next_double(random),
+            WorldgenRandom::Xoroshiro(random) => random.next_double(),
+        }
+    }
+
+    fn consume_count(&mut self, count: i32) {
+        match self {
+            WorldgenRandom::Legacy(random) => random.consume_count(count),
+            WorldgenRandom::Xoroshiro(random) => random.consume_count(count),
+        }
+    }
+
+    fn fork_positional(&mut self) -> PositionalRandomFactory {
+        match self {
+            WorldgenRandom::Legacy(random) => random.fork_positional(),
+            WorldgenRandom::Xoroshiro(random) => random.fork_positional(),
+        }
+    }
+}
+
+/// Derives randoms from a block position or a name, the `PositionalRandomFactory` of 1.18+.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionalRandomFactory {
+    Legacy(i64),
+    Xoroshiro(i64, i64),
+}
+
+impl PositionalRandomFactory {
+    pub fn at(&self, x: i32, y: i32, z: i32) -> WorldgenRandom {
+        match *self {
+            PositionalRandomFactory::Legacy(seed) => WorldgenRandom::Legacy(Random::with_seed((get_seed(x, y, z) ^ seed) as u64)),
+            PositionalRandomFactory::Xoroshiro(seed_lo, seed_hi) => {
+                WorldgenRandom::Xoroshiro(XoroshiroRandom::with_raw_seed(get_seed(x, y, z) ^ seed_lo, seed_hi))
+            }
+        }
+    }
+
+    pub fn from_hash_of(&self, name: &str) -> WorldgenRandom {
+        match *self {
+            PositionalRandomFactory::Legacy(seed) => WorldgenRandom::Legacy(Random::with_seed((java_string_hash(name) as i64 ^ seed) as u64)),
+            PositionalRandomFactory::Xoroshiro(seed_lo, seed_hi) => {
+                let hash = Md5::digest(name.as_bytes());
+                let mut lo: [u8; 8] = [0; 8];
+                let mut hi: [u8; 8] = [0; 8];
+                lo.copy_from_slice(&hash[0..8]);
+                hi.copy_from_slice(&hash[8..16]);
+                WorldgenRandom::Xoroshiro(XoroshiroRandom::with_raw_seed(i64::from_be_bytes(lo) ^ seed_lo, i64::from_be_bytes(hi) ^ seed_hi))
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Recovers java random states from approximate `Noise` offsets by lattice reduction.
+//!
+//! `Noise::new` draws `x0`, `y0` and `z0` from three `next_double` calls, each made of a 26 then a
+//! 27 bit `next`: an interval of an offset bounds the top bits of the LCG states of those calls.
+//! The states after `k` calls are `a_k * s + c_k mod 2^48` of the unknown state `s`, so the states
+//! satisfying all the bounds are the points of the lattice spanned by `(1, a_2, ..., a_n)` and the
+//! `2^48 e_i` within a box. The basis is LLL reduced (with coordinates weighted so the box is a
+//! cube), the box is mapped to bounds on the coefficients in the reduced basis and the lattice
+//! points inside are enumerated and checked exactly.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use java_random::{JAVA_LCG, LCG};
+
+use crate::math::floor_f64;
+use crate::noise_constraints::Interval;
+
+const MODULUS: u64 = 1u64 << 48;
+const MASK: u64 = MODULUS - 1;
+
+/// The LCG state after `step` calls, counted from the unknown state, lies in `min..=max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateConstraint {
+    pub step: u64,
+    pub min: u64,
+    pub max: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReversalError {
+    /// No constraint was given.
+    Unconstrained,
+    /// The bounds let more than the given limit of lattice points through.
+    TooManyCandidates,
+}
+
+impl fmt::Display for ReversalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReversalError::Unconstrained => write!(f, "no constraint on the states"),
+            ReversalError::TooManyCandidates => write!(f, "the constraints are too loose to enumerate the states"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReversalError {}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod seed_reversal_test {
+    use super::*;
+    use crate::noise::Noise;
+    use java_random::Random;
+    use crate::perlin_noise::SKIP_262;
+
+    fn around(noise: &Noise, radius: f64) -> [Interval; 3] {
+        [
+            Interval::new(noise.x0 - radius, noise.x0 + radius),
+            Interval::new(noise.y0 - radius, noise.y0 + radius),
+            Interval::new(noise.z0 - radius, noise.z0 + radius),
+        ]
+    }
+
+    #[test]
+    fn test_double_constraints() {
+        let mut random = Random::with_seed(5);
+        let value = random.next_double();
+        let constraints = get_double_constraints(1, Interval::point(value));
+        assert_eq!(constraints.len(), 2);
+        assert_eq!(find_states(&constraints, 16), Ok(vec![Random::with_seed(5).get_raw_seed()]));
+        assert_eq!(get_double_constraints(1, Interval::new(-0.5f64, -0.25f64)), Vec::new());
+        assert_eq!(get_double_constraints(1, Interval::new(1.0f64, 2.0f64)), Vec::new());
+        let below = [Interval::new(-2.0f64, -1.0f64), Interval::new(0.0f64, 256.0f64), Interval::new(0.0f64, 256.0f64)];
+        assert_eq!(find_noise_states(&below, 1000), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_exact_offsets() {
+        let noise = Noise::new(&mut Random::with_seed(123456789));
+        assert_eq!(find_noise_seeds(&around(&noise, 0.0f64), 0, 16), Ok(vec![123456789]));
+    }
+
+    #[test]
+    fn test_approximate_offsets() {
+        for seed in [0u64, 1, 42, 0xdead_beef, 0xffff_ffff_ffff] {
+            let noise = Noise::new(&mut Random::with_seed(seed));
+            let seeds = find_noise_seeds(&around(&noise, 1e-4f64), 0, 1024).unwrap();
+            assert!(seeds.contains(&seed), "{} not in {:?}", seed, seeds);
+            assert!(seeds.len() <= 4);
+        }
+    }
+
+    #[test]
+    fn test_calls_before() {
+        // the second octave of a 1.16 perlin noise starts 262 calls in
+        let mut random = Random::with_seed(987654321);
+        random.advance(SKIP_262);
+        let noise = Noise::new(&mut random);
+        let seeds = find_noise_seeds(&around(&noise, 1e-5f64), 262, 1024).unwrap();
+        assert!(seeds.contains(&987654321));
+    }
+
+    #[test]
+    fn test_loose_bounds() {
+        let everything = [Interval::new(0.0f64, 256.0f64); 3];
+        assert_eq!(find_noise_states(&everything, 1000), Err(ReversalError::TooManyCandidates));
+        assert_eq!(find_states(&[], 1000), Err(ReversalError::Unconstrained));
+    }
+}
+
+/// The state of `Random::with_seed(seed)` after `steps` calls, `steps` wrapping around the period.
+fn step_state(state: u64, steps: u64) -> u64 {
+    let lcg: LCG = LCG::combine_java(steps & MASK);
+    state.wrapping_mul(lcg.multiplier).wrapping_add(lcg.addend) & MASK
+}
+
+/// The constraints on the two states of a `next_double` whose result lies in `value`, the first
+/// of its calls being `step`. The second state is only bounded when the first 26 bits are known,
+/// and there are none if no double lies in `value`.
+pub fn get_double_constraints(step: u64, value: Interval) -> Vec<StateConstraint> {
+    if value.max < 0.0f64 {
+        return Vec::new();
+    }
+    let scale: f64 = (1u64 << 53) as f64;
+    let low: u64 = (-floor_f64(-value.min * scale)).max(0.0f64) as u64;
+    let high: u64 = (floor_f64(value.max * scale) as u64).min((1u64 << 53) - 1);
+    if low > high {
+        return Vec::new();
+    }
+    let (high_bits_min, high_bits_max) = (low >> 27, high >> 27);
+    let mut constraints = vec![StateConstraint { step, min: high_bits_min << 22, max: high_bits_max << 22 | ((1 << 22) - 1) }];
+    if high_bits_min == high_bits_max {
+        let (low_bits_min, low_bits_max) = (low & ((1 << 27) - 1), high & ((1 << 27) - 1));
+        constraints.push(StateConstraint { step: step + 1, min: low_bits_min << 21, max: low_bits_max << 21 | ((1 << 21) - 1) });
+    }
+    constraints
+}
+
+/// The internal states (`Random::get_raw_seed`) the constraints allow, at most `limit` lattice
+/// points being looked at.
+pub fn find_states(constraints: &[StateConstraint], limit: usize) -> Result<Vec<u64>, ReversalError> {
+    if constraints.is_empty() {
+        return Err(ReversalError::Unconstrained);
+    }
+    if constraints.iter().any(|constraint| constraint.min > constraint.max) {
+        return Ok(Vec::new());
+    }
+    // the state after the first constraint's step is the unknown, the others are lcgs of it
+    let reference = constraints[0].step;
+    let dimension = constraints.len();
+    let mut basis: Vec<Vec<i128>> = vec![vec![0; dimension]; dimension];
+    let mut low: Vec<i128> = vec![0; dimension];
+    let mut high: Vec<i128> = vec![0; dimension];
+    for (i, constraint) in constraints.iter().enumerate() {
+        let lcg: LCG = LCG::combine_java(constraint.step.wrapping_sub(reference) & MASK);
+        basis[0][i] = lcg.multiplier as i128;
+        if i > 0 {
+            basis[i][i] = MODULUS as i128;
+        }
+        low[i] = constraint.min as i128 - lcg.addend as i128;
+        high[i] = constraint.max as i128 - lcg.addend as i128;
+    }
+    let widest = (0..dimension).map(|i| (high[i] - low[i] + 1) as f64).fold(0.0f64, f64::max);
+    let weights: Vec<f64> = (0..dimension).map(|i| widest / (high[i] - low[i] + 1) as f64).map(|scale| scale * scale).collect();
+    reduce(&mut basis, &weights);
+
+    let inverse = invert(&basis);
+    let mut ranges: Vec<(i128, i128)> = Vec::with_capacity(dimension);
+    let mut count: f64 = 1.0f64;
+    for j in 0..dimension {
+        let (mut min, mut max, mut magnitude) = (0.0f64, 0.0f64, 0.0f64);
+        for ((row, &low), &high) in inverse.iter().zip(&low).zip(&high) {
+            let (a, b) = (row[j] * low as f64, row[j] * high as f64);
+            min += a.min(b);
+            max += a.max(b);
+            magnitude += a.abs() + b.abs();
+        }
+        // some slack against the rounding of the floating point inverse
+        let slack = 1e-6f64 + magnitude * 1e-12f64;
+        let range = (-floor_f64(-min - slack) as i128, floor_f64(max + slack) as i128);
+        count *= (range.1 - range.0 + 1).max(0) as f64;
+        ranges.push(range);
+    }
+    if count == 0.0f64 {
+        return Ok(Vec::new());
+    }
+    if count > limit as f64 {
+        return Err(ReversalError::TooManyCandidates);
+    }
+
+    let mut states: Vec<u64> = Vec::new();
+    let mut coefficients: Vec<i128> = ranges.iter().map(|range| range.0).collect();
+    loop {
+        let point: Vec<i128> = (0..dimension).map(|k| (0..dimension).map(|j| coefficients[j] * basis[j][k]).sum()).collect();
+        if (0..dimension).all(|k| low[k] <= point[k] && point[k] <= high[k]) {
+            let reference_state = point[0].rem_euclid(MODULUS as i128) as u64;
+            let state = step_state(reference_state, MODULUS - reference);
+            let valid = constraints.iter().all(|constraint| {
+                let value = step_state(state, constraint.step);
+                constraint.min <= value && value <= constraint.max
+            });
+            if valid && !states.contains(&state) {
+                states.push(state);
+            }
+        }
+        // next coefficients, odometer style
+        let mut j = 0;
+        loop {
+            if j == dimension {
+                states.sort_unstable();
+                return Ok(states);
+            }
+            if coefficients[j] < ranges[j].1 {
+                coefficients[j] += 1;
+                break;
+            }
+            coefficients[j] = ranges[j].0;
+            j += 1;
+        }
+    }
+}
+
+/// The internal states right before a `Noise::new` whose offsets lie in `offsets`.
+pub fn find_noise_states(offsets: &[Interval; 3], limit: usize) -> Result<Vec<u64>, ReversalError> {
+    let mut constraints: Vec<StateConstraint> = Vec::new();
+    for (i, offset) in offsets.iter().enumerate() {
+        let value = Interval::new(offset.min / 256.0f64, offset.max / 256.0f64);
+        let double_constraints = get_double_constraints(1 + 2 * i as u64, value);
+        if double_constraints.is_empty() {
+            // no double gives that offset
+            return Ok(Vec::new());
+        }
+        constraints.extend(double_constraints);
+    }
+    find_states(&constraints, limit)
+}
+
+/// The seeds (lower 48 bits) of the java randoms that make a `Noise` with offsets in `offsets`
+/// after `calls_before` calls, 0 for a noise made right from `Random::with_seed`.
+pub fn find_noise_seeds(offsets: &[Interval; 3], calls_before: u64, limit: usize) -> Result<Vec<u64>, ReversalError> {
+    let mut seeds: Vec<u64> = find_noise_states(offsets, limit)?
+        .into_iter()
+        .map(|state| step_state(state, MODULUS - (calls_before & MASK)) ^ JAVA_LCG.multiplier)
+        .collect();
+    seeds.sort_unstable();
+    Ok(seeds)
+}
+
+fn weighted_dot(u: &[f64], v: &[f64], weights: &[f64]) -> f64 {
+    u.iter().zip(v.iter()).zip(weights.iter()).map(|((a, b), weight)| a * b * weight).sum()
+}
+
+/// Gram-Schmidt orthogonalisation of the basis, the squared norms and the mu coefficients.
+fn gram_schmidt(basis: &[Vec<i128>], weights: &[f64]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let dimension = basis.len();
+    let mut orthogonal: Vec<Vec<f64>> = Vec::with_capacity(dimension);
+    let mut norms: Vec<f64> = Vec::with_capacity(dimension);
+    let mut mu: Vec<Vec<f64>> = vec![vec![0.0f64; dimension]; dimension];
+    for i in 0..dimension {
+        let vector: Vec<f64> = basis[i].iter().map(|&value| value as f64).collect();
+        let mut projected = vector.clone();
+        for j in 0..i {
+            mu[i][j] = weighted_dot(&vector, &orthogonal[j], weights) / norms[j];
+            for k in 0..dimension {
+                projected[k] -= mu[i][j] * orthogonal[j][k];
+            }
+        }
+        norms.push(weighted_dot(&projected, &projected, weights));
+        orthogonal.push(projected);
+    }
+    (norms, mu)
+}
+
+/// LLL reduction (delta 0.99) of the rows for the weighted inner product, the rows staying
+/// exact integer vectors.
+fn reduce(basis: &mut [Vec<i128>], weights: &[f64]) {
+    let dimension = basis.len();
+    let mut k = 1;
+    while k < dimension {
+        for j in (0..k).rev() {
+            let (_, mu) = gram_schmidt(basis, weights);
+            let quotient = floor_f64(mu[k][j] + 0.5f64) as i128;
+            if quotient != 0 {
+                let row = basis[j].clone();
+                for (value, base) in basis[k].iter_mut().zip(&row) {
+                    *value -= quotient * base;
+                }
+            }
+        }
+        let (norms, mu) = gram_schmidt(basis, weights);
+        if norms[k] >= (0.99f64 - mu[k][k - 1] * mu[k][k - 1]) * norms[k - 1] {
+            k += 1;
+        } else {
+            basis.swap(k, k - 1);
+            k = (k - 1).max(1);
+        }
+    }
+}
+
+/// The inverse of the matrix of rows, by Gauss-Jordan elimination.
+fn invert(basis: &[Vec<i128>]) -> Vec<Vec<f64>> {
+    let dimension = basis.len();
+    let mut matrix: Vec<Vec<f64>> = basis.iter().map(|row| row.iter().map(|&value| value as f64).collect()).collect();
+    let mut inverse: Vec<Vec<f64>> = (0..dimension).map(|i| (0..dimension).map(|j| if i == j { 1.0f64 } else { 0.0f64 }).collect()).collect();
+    for column in 0..dimension {
+        let pivot = (column..dimension).max_by(|&a, &b| matrix[a][column].abs().partial_cmp(&matrix[b][column].abs()).unwrap()).unwrap();
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let factor = matrix[column][column];
+        for k in 0..dimension {
+            matrix[column][k] /= factor;
+            inverse[column][k] /= factor;
+        }
+        for row in 0..dimension {
+            if row != column {
+                let factor = matrix[row][column];
+                for k in 0..dimension {
+                    matrix[row][k] -= factor * matrix[column][k];
+                    inverse[row][k] -= factor * inverse[column][k];
+                }
+            }
+        }
+    }
+    inverse
+}
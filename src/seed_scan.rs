@@ -0,0 +1,287 @@
+//! Multithreaded seed search with progress reports and resumable checkpoints.
+//!
+//! The seeds of a range are handed out to the threads in batches. A seed gets its noises built by
+//! the `build` closure and is kept if the `predicate` holds on them, the matches are reported in
+//! increasing order. The checkpoint file records the seed all the seeds below of which are
+//! scanned, and the matches among them, so an interrupted search starts again from there.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Where a running search stands, passed to the progress callback.
+#[derive(Clone, Debug)]
+pub struct ScanProgress<'a> {
+    /// seeds scanned so far, those scanned before a resume included
+    pub scanned: u64,
+    pub total: u64,
+    /// every seed below this one is scanned
+    pub next_seed: u64,
+    /// the matches below `next_seed`
+    pub matches: &'a [u64],
+    pub elapsed: Duration,
+}
+
+impl ScanProgress<'_> {
+    pub fn get_fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0f64;
+        }
+        self.scanned as f64 / self.total as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanResult {
+    pub matches: Vec<u64>,
+    /// seeds scanned by this run, not counting those of the checkpoint it resumed from
+    pub scanned: u64,
+    /// the seed the run started from, after the checkpoint if there was one
+    pub resumed_from: u64,
+}
+
+/// A search over a range of seeds, configured with the chained setters then started by `run`.
+#[derive(Clone, Debug)]
+pub struct SeedScanner {
+    range: Range<u64>,
+    threads: usize,
+    batch_size: u64,
+    progress_interval: Duration,
+    checkpoint: Option<PathBuf>,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod seed_scan_test {
+    use super::*;
+    use crate::perlin_noise::PerlinNoise;
+    use java_random::Random;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("noise_rs_{}_{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_matches_sorted() {
+        let result = SeedScanner::new(0..100_000).threads(4).batch_size(1000).run(|seed| seed, |_, seed| *seed % 10_000 == 7).unwrap();
+        assert_eq!(result.matches, (0..10).map(|i| i * 10_000 + 7).collect::<Vec<u64>>());
+        assert_eq!(result.scanned, 100_000);
+        assert_eq!(result.resumed_from, 0);
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let result = SeedScanner::new(5..5).run(|seed| seed, |_, _| true).unwrap();
+        assert_eq!((result.matches.len(), result.scanned), (0, 0));
+        assert_eq!(SeedScanner::new(Range { start: 10, end: 5 }).run(|seed| seed, |_, _| true).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_perlin_predicate() {
+        let build = |seed: u64| PerlinNoise::new(&mut Random::with_seed(seed), vec![-3, -2, -1, 0]);
+        let predicate = |_: u64, noise: &mut PerlinNoise| noise.sample_default(0.5f64, 0.0f64, 0.5f64) > 0.4f64;
+        let single = SeedScanner::new(0..2000).threads(1).run(build, predicate).unwrap();
+        let parallel = SeedScanner::new(0..2000).threads(3).batch_size(64).run(build, predicate).unwrap();
+        assert!(!single.matches.is_empty());
+        assert_eq!(single, parallel);
+    }
+
+    #[test]
+    fn test_resume() {
+        let path = temp_path("resume");
+        fs::write(&path, "range 0 1000\nnext 500\nmatch 3\n").unwrap();
+        let result = SeedScanner::new(0..1000).batch_size(100).checkpoint(&path).run(|seed| seed, |_, seed| *seed % 100 == 3).unwrap();
+        // 3 comes from the checkpoint, the seeds below 500 are not scanned again
+        assert_eq!(result.matches, vec![3, 503, 603, 703, 803, 903]);
+        assert_eq!(result.scanned, 500);
+        assert_eq!(result.resumed_from, 500);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "range 0 1000\nnext 1000\nmatch 3\nmatch 503\nmatch 603\nmatch 703\nmatch 803\nmatch 903\n");
+        let result = SeedScanner::new(0..1000).checkpoint(&path).run(|seed| seed, |_, _| true).unwrap();
+        assert_eq!((result.matches.len(), result.scanned), (6, 0));
+        assert!(SeedScanner::new(0..2000).checkpoint(&path).run(|seed| seed, |_, _| true).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut reports: Vec<(u64, u64)> = Vec::new();
+        SeedScanner::new(10..1010)
+            .batch_size(100)
+            .progress_interval(Duration::from_secs(3600))
+            .run_with_progress(|seed| seed, |_, _| false, |progress| reports.push((progress.scanned, progress.next_seed)))
+            .unwrap();
+        // the final report is always made
+        assert_eq!(reports.last(), Some(&(1000, 1010)));
+    }
+}
+
+impl SeedScanner {
+    pub fn new(range: Range<u64>) -> Self {
+        SeedScanner {
+            range,
+            threads: thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1),
+            batch_size: 4096,
+            progress_interval: Duration::from_secs(1),
+            checkpoint: None,
+        }
+    }
+
+    /// Number of worker threads, the available parallelism by default.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Seeds a thread takes at once, 4096 by default.
+    pub fn batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Time between two progress reports and checkpoint writes, a second by default.
+    pub fn progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    /// File the search resumes from if it exists and saves its state to.
+    pub fn checkpoint<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.checkpoint = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Fails with `InvalidInput` on a reversed range, an empty one scans nothing.
+    pub fn run<N, B, P>(&self, build: B, predicate: P) -> io::Result<ScanResult>
+    where
+        B: Fn(u64) -> N + Sync,
+        P: Fn(u64, &mut N) -> bool + Sync,
+    {
+        self.run_with_progress(build, predicate, |_| {})
+    }
+
+    pub fn run_with_progress<N, B, P, R>(&self, build: B, predicate: P, mut progress: R) -> io::Result<ScanResult>
+    where
+        B: Fn(u64) -> N + Sync,
+        P: Fn(u64, &mut N) -> bool + Sync,
+        R: FnMut(&ScanProgress),
+    {
+        if self.range.start > self.range.end {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("the range {:?} is reversed", self.range)));
+        }
+        let (resumed_from, mut matches) = match &self.checkpoint {
+            Some(path) if path.exists() => self.read_checkpoint(path)?,
+            _ => (self.range.start, Vec::new()),
+        };
+        let start = resumed_from;
+        let end = self.range.end;
+        let batch_size = self.batch_size;
+        let length: u64 = end.saturating_sub(start);
+        let batch_count: u64 = length.div_ceil(batch_size);
+        let batch_range = |batch: u64| start + batch * batch_size..start.saturating_add((batch + 1).saturating_mul(batch_size)).min(end);
+        let next_batch = AtomicU64::new(0);
+        let began = Instant::now();
+        let mut scanned: u64 = 0;
+        let mut confirmed_batches: u64 = 0;
+        let mut pending: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        let mut last_report = Instant::now();
+
+        thread::scope(|scope| -> io::Result<()> {
+            let (sender, receiver) = mpsc::channel::<(u64, Vec<u64>)>();
+            for _ in 0..self.threads.min(batch_count.max(1) as usize) {
+                let sender = sender.clone();
+                let (build, predicate, next_batch) = (&build, &predicate, &next_batch);
+                scope.spawn(move || loop {
+                    let batch = next_batch.fetch_add(1, Ordering::Relaxed);
+                    if batch >= batch_count {
+                        return;
+                    }
+                    let found: Vec<u64> = batch_range(batch).filter(|&seed| predicate(seed, &mut build(seed))).collect();
+                    if sender.send((batch, found)).is_err() {
+                        return;
+                    }
+                });
+            }
+            drop(sender);
+            loop {
+                let finished = match receiver.recv_timeout(self.progress_interval) {
+                    Ok((batch, found)) => {
+                        let range = batch_range(batch);
+                        scanned += range.end - range.start;
+                        pending.insert(batch, found);
+                        // matches are only confirmed once every batch before theirs is done
+                        while let Some(found) = pending.remove(&confirmed_batches) {
+                            matches.extend(found);
+                            confirmed_batches += 1;
+                        }
+                        false
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => false,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => true,
+                };
+                if finished || last_report.elapsed() >= self.progress_interval {
+                    let next_seed = start.saturating_add(confirmed_batches.saturating_mul(batch_size)).min(end);
+                    if let Some(path) = &self.checkpoint {
+                        self.write_checkpoint(path, next_seed, &matches)?;
+                    }
+                    progress(&ScanProgress {
+                        scanned: resumed_from - self.range.start + scanned,
+                        total: self.range.end - self.range.start,
+                        next_seed,
+                        matches: &matches,
+                        elapsed: began.elapsed(),
+                    });
+                    last_report = Instant::now();
+                }
+                if finished {
+                    return Ok(());
+                }
+            }
+        })?;
+        Ok(ScanResult { matches, scanned, resumed_from })
+    }
+
+    fn read_checkpoint(&self, path: &Path) -> io::Result<(u64, Vec<u64>)> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message));
+        let mut range: Option<Range<u64>> = None;
+        let mut next_seed: Option<u64> = None;
+        let mut matches: Vec<u64> = Vec::new();
+        for line in fs::read_to_string(path)?.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parse = |index: usize| fields.get(index).and_then(|field| field.parse::<u64>().ok()).ok_or_else(|| invalid(format!("invalid line {:?}", line)));
+            match fields.first() {
+                Some(&"range") => range = Some(parse(1)?..parse(2)?),
+                Some(&"next") => next_seed = Some(parse(1)?),
+                Some(&"match") => matches.push(parse(1)?),
+                None => {}
+                Some(_) => return Err(invalid(format!("invalid line {:?}", line))),
+            }
+        }
+        if range.as_ref() != Some(&self.range) {
+            return Err(invalid(format!("the checkpoint is not of the range {:?}", self.range)));
+        }
+        match next_seed {
+            Some(next_seed) if self.range.contains(&next_seed) || next_seed == self.range.end => Ok((next_seed, matches)),
+            _ => Err(invalid("missing or out of range next seed".to_string())),
+        }
+    }
+
+    fn write_checkpoint(&self, path: &Path, next_seed: u64, matches: &[u64]) -> io::Result<()> {
+        let mut content = format!("range {} {}\nnext {}\n", self.range.start, self.range.end, next_seed);
+        for seed in matches {
+            content.push_str(&format!("match {}\n", seed));
+        }
+        // written aside then renamed so an interruption never leaves half a checkpoint
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(&temporary, path)
+    }
+}
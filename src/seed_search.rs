@@ -0,0 +1,694 @@
+//! Enumerates the seeds consistent with observed biomes or noise signs.
+//!
+//! A seed is checked cheapest first. The observations are decided in turn from bounds of the
+//! octaves of their noises, refining the octave with the widest bounds until the observation
+//! holds or fails. An octave nothing is known of is first given its offsets: they take 6 random
+//! calls once the random reaches the octave, and with the permutation still unknown they bound
+//! the octave's value as [`NoiseConstraints::get_bounds`] would, each corner gradient being any
+//! of them. Its permutation is then shuffled one level of the `lookup` chains at a time, up to
+//! the furthest entry the chains at the observation reach next, the corners whose chain is drawn
+//! taking their actual gradient. Once the chains are all drawn the rest of the permutation is
+//! shuffled too, building the octave in full. Octaves built for an observation
+//! serve the next ones, and the low weight octaves of a 1.18 noise are often never built at all.
+//! Before 1.18 the octaves share a random, the skipped octaves are jumped over but those built
+//! before are built for real, a `nextInt` of their permutation drawing more than once now and then.
+//!
+//! Before 1.18 the noises only depend on the lower 48 bits of the seed, the nether climate noises
+//! being seeded by `Random::with_seed(seed + salt)`. From 1.18 the overworld climate noises come
+//! from the xoroshiro positional random and the whole 64 bit seed matters.
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use java_random::{Random, LCG};
+
+use crate::biome_source::{get_nearest_nether_biome, Biome, NETHER_BIOMES};
+use crate::double_perlin_noise::DoublePerlinNoise;
+use crate::math::{floor_f64, grad, lerp3, pow2, smooth_step, wrap};
+use crate::noise::Noise;
+use crate::noise_constraints::{Interval, NoiseConstraints, Observation};
+use crate::random_source::{PositionalRandomFactory, RandomSource, WorldgenRandom};
+
+/// How far the value summed from bounds may stray from the one `DoublePerlinNoise` computes.
+const SUM_EPSILON: f64 = 1e-9;
+/// How far a fitness computed in f64 may stray from the f32 one of the biome source.
+const FITNESS_EPSILON: f64 = 1e-5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchVersion {
+    V1_16,
+    V1_17,
+    V1_18,
+}
+
+impl SearchVersion {
+    /// The bits of the seed the observations depend on, 48 before 1.18 and 64 after.
+    pub fn get_seed_bits(&self) -> u32 {
+        match self {
+            SearchVersion::V1_16 | SearchVersion::V1_17 => 48,
+            SearchVersion::V1_18 => 64,
+        }
+    }
+}
+
+/// The climate noises: the four nether ones of 1.16 and 1.17, and the 1.18 overworld ones, the
+/// humidity being `minecraft:vegetation` and the weirdness `minecraft:ridge` there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClimateNoise {
+    Temperature,
+    Humidity,
+    Altitude,
+    Weirdness,
+    Continentalness,
+    Erosion,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expectation {
+    /// The nether biome, before 1.18.
+    Biome(Biome),
+    /// The noise is at least zero.
+    Positive(ClimateNoise),
+    /// The noise is below zero.
+    Negative(ClimateNoise),
+}
+
+/// What is expected at a position in noise (quart) coordinates. The noises are sampled at the
+/// position itself, without the shift the 1.18 biome source applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeedObservation {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub expectation: Expectation,
+}
+
+impl SeedObservation {
+    pub fn new(x: i32, y: i32, z: i32, expectation: Expectation) -> Self {
+        SeedObservation { x, y, z, expectation }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    /// The version has no such biome or noise.
+    Unsupported(SearchVersion, Expectation),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Unsupported(version, expectation) => write!(f, "{:?} can not be observed in {:?}", expectation, version),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SearchError {}
+
+/// Where the random of an octave comes from.
+#[derive(Clone, Copy, Debug)]
+enum OctaveStart {
+    /// `Random::with_seed(seed + salt)`, or the random the octave `after` leaves once built,
+    /// advanced by `skip`, through the noise vanilla builds and throws away if `discard` and by
+    /// `skip_after`. The built noises are not jumped over, their permutation takes 262 calls but
+    /// for the `nextInt` rejections.
+    Legacy { salt: i64, after: Option<usize>, skip: LCG, discard: bool, skip_after: LCG },
+    /// The random of `octave_<octave>` in the first or second perlin noise of the noise `name`.
+    Positional { name: &'static str, perlin: usize, octave: i32 },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Octave {
+    start: OctaveStart,
+    /// Whether it belongs to the second perlin noise, sampled at skewed coordinates.
+    second: bool,
+    amplitude: f64,
+    persistence: f64,
+    lacunarity: f64,
+}
+
+/// The octaves of a `DoublePerlinNoise`, in the order it sums them.
+#[derive(Clone, Debug)]
+struct NoiseLayout {
+    noise: ClimateNoise,
+    amplitude: f64,
+    octaves: Range<usize>,
+}
+
+/// A search for the seeds matching observations, see the module documentation.
+#[derive(Clone, Debug)]
+pub struct SeedSearch {
+    version: SearchVersion,
+    observations: Vec<SeedObservation>,
+    layouts: Vec<NoiseLayout>,
+    octaves: Vec<Octave>,
+    /// Bounds of an octave nothing is known about.
+    octave_bounds: Interval,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Satisfied,
+    Violated,
+    Unknown,
+}
+
+/// The stage of the checks an octave went through last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    Offsets,
+    Permutation,
+    Noise,
+}
+
+/// An octave with its permutation shuffled up to `drawn`, and the random to go on with.
+#[derive(Clone, Debug)]
+struct PartialNoise {
+    random: WorldgenRandom,
+    permutations: [u8; 256],
+    drawn: usize,
+}
+
+/// What is known of the octaves for the seed being checked.
+struct SeedState {
+    seed: u64,
+    offsets: Vec<Option<[f64; 3]>>,
+    partials: Vec<Option<PartialNoise>>,
+    noises: Vec<Option<Noise>>,
+    /// The random right after building each legacy octave.
+    ends: Vec<Option<Random>>,
+    factories: Vec<Option<[PositionalRandomFactory; 2]>>,
+    /// The octave bounds at each observation, by observation then octave, cleared as octaves load.
+    bounds: Vec<Option<Interval>>,
+    /// The stage of the octave loaded last.
+    stage: Option<Stage>,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod seed_search_test {
+    use super::*;
+    use crate::biome_source::{BiomeSource, NetherBiomeSource};
+    use crate::double_perlin_noise::NoiseParameters;
+
+    fn biome_observations(seed: i64, count: i32) -> Vec<SeedObservation> {
+        let mut source = NetherBiomeSource::new(seed);
+        (0..count).map(|i| {
+            let (x, z) = (i * 37 % 200 - 100, i * 61 % 200 - 100);
+            SeedObservation::new(x, 0, z, Expectation::Biome(source.get_noise_biome(x, 0, z)))
+        }).collect()
+    }
+
+    #[test]
+    fn test_nether_biomes() {
+        let search = SeedSearch::new(SearchVersion::V1_16, biome_observations(1000, 4)).unwrap();
+        let found: Vec<u64> = search.search(0..2000).collect();
+        let expected: Vec<u64> = (0..2000u64).filter(|&seed| {
+            let mut source = NetherBiomeSource::new(seed as i64);
+            search.get_observations().iter().all(|observation| Expectation::Biome(source.get_noise_biome(observation.x, 0, observation.z)) == observation.expectation)
+        }).collect();
+        assert!(found.contains(&1000));
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_nether_seed_recovered() {
+        let search = SeedSearch::new(SearchVersion::V1_17, biome_observations(123_456, 40)).unwrap();
+        assert_eq!(search.search(120_000..130_000).collect::<Vec<u64>>(), vec![123_456]);
+        assert!(search.test(123_456 | 0xabcd << 48));
+    }
+
+    #[test]
+    fn test_permutation_rejections() {
+        // a nextInt of the permutations of these seeds rejects a value, shifting the later octaves
+        for seed in [21237, 184020, 256846, 419981, 463898] {
+            let search = SeedSearch::new(SearchVersion::V1_16, biome_observations(seed, 60)).unwrap();
+            assert!(search.test(seed as u64), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_permutation_stage() {
+        // the stage loaded last before each rejection, none of them needing a fully built octave
+        let stages = |search: &SeedSearch, seeds: u64| {
+            let stages: Vec<Option<Stage>> = (0..seeds).filter_map(|seed| search.check(seed).err()).collect();
+            [None, Some(Stage::Offsets), Some(Stage::Permutation), Some(Stage::Noise)].map(|stage| stages.iter().filter(|&&other| other == stage).count())
+        };
+        let search = SeedSearch::new(SearchVersion::V1_16, biome_observations(1000, 4)).unwrap();
+        assert_eq!(stages(&search, 2000), [0, 0, 1996, 0]);
+        let observations: Vec<SeedObservation> = (0..6).map(|i| SeedObservation::new(i * 50, 0, -i * 30, Expectation::Positive(ClimateNoise::Continentalness))).collect();
+        let search = SeedSearch::new(SearchVersion::V1_18, observations).unwrap();
+        assert_eq!(stages(&search, 2000), [0, 402, 1233, 0]);
+    }
+
+    #[test]
+    fn test_signs() {
+        let parameters = NoiseParameters::new(-9, vec![1.0f64, 1.0f64, 2.0f64, 2.0f64, 2.0f64, 1.0f64, 1.0f64, 1.0f64, 1.0f64]);
+        let observations: Vec<SeedObservation> = (0..6).map(|i| SeedObservation::new(i * 50, 0, -i * 30, Expectation::Positive(ClimateNoise::Continentalness))).collect();
+        let search = SeedSearch::new(SearchVersion::V1_18, observations).unwrap();
+        for seed in 0..200u64 {
+            let factory = WorldgenRandom::new(seed as i64, false).fork_positional();
+            let noise = DoublePerlinNoise::instantiate(&factory, "minecraft:continentalness", &parameters);
+            let expected = search.get_observations().iter().all(|observation| noise.sample(observation.x as f64, 0.0f64, observation.z as f64) >= 0.0f64);
+            assert_eq!(search.test(seed), expected, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let biome = SeedObservation::new(0, 0, 0, Expectation::Biome(Biome::WarpedForest));
+        assert_eq!(SeedSearch::new(SearchVersion::V1_18, vec![biome]).err(), Some(SearchError::Unsupported(SearchVersion::V1_18, biome.expectation)));
+        let erosion = SeedObservation::new(0, 0, 0, Expectation::Negative(ClimateNoise::Erosion));
+        assert!(SeedSearch::new(SearchVersion::V1_16, vec![erosion]).is_err());
+    }
+}
+
+impl SeedSearch {
+    pub fn new(version: SearchVersion, observations: Vec<SeedObservation>) -> Result<Self, SearchError> {
+        let mut search = SeedSearch {
+            version,
+            observations: Vec::new(),
+            layouts: Vec::new(),
+            octaves: Vec::new(),
+            octave_bounds: NoiseConstraints::with_offsets(Interval::new(0.0f64, 1.0f64), Interval::new(0.0f64, 1.0f64), Interval::new(0.0f64, 1.0f64))
+                .get_bounds(&Observation::between(0.0f64, 0.0f64, 0.0f64, f64::NEG_INFINITY, f64::INFINITY)),
+        };
+        for observation in observations.iter() {
+            let noises: &[ClimateNoise] = match &observation.expectation {
+                Expectation::Biome(biome) if version == SearchVersion::V1_18 || NETHER_BIOMES.iter().all(|(other, _)| other != biome) => {
+                    return Err(SearchError::Unsupported(version, observation.expectation))
+                }
+                Expectation::Biome(_) => &[ClimateNoise::Temperature, ClimateNoise::Humidity, ClimateNoise::Altitude, ClimateNoise::Weirdness],
+                Expectation::Positive(noise) | Expectation::Negative(noise) => core::slice::from_ref(noise),
+            };
+            for &noise in noises {
+                if search.get_layout(noise).is_none() && !search.add_layout(noise) {
+                    return Err(SearchError::Unsupported(version, observation.expectation));
+                }
+            }
+        }
+        search.observations = observations;
+        Ok(search)
+    }
+
+    pub fn get_version(&self) -> SearchVersion {
+        self.version
+    }
+
+    pub fn get_observations(&self) -> &[SeedObservation] {
+        &self.observations
+    }
+
+    /// Whether the seed satisfies every observation. Before 1.18 only its lower 48 bits matter.
+    pub fn test(&self, seed: u64) -> bool {
+        self.check(seed).is_ok()
+    }
+
+    /// [`test`](Self::test), failing with the stage of the octave loaded last before the violated
+    /// observation, `None` if nothing was loaded.
+    fn check(&self, seed: u64) -> Result<(), Option<Stage>> {
+        let mut state = SeedState {
+            seed,
+            offsets: vec![None; self.octaves.len()],
+            partials: vec![None; self.octaves.len()],
+            noises: vec![None; self.octaves.len()],
+            ends: vec![None; self.octaves.len()],
+            factories: vec![None; self.layouts.len()],
+            bounds: vec![None; self.observations.len() * self.octaves.len()],
+            stage: None,
+        };
+        for (index, observation) in self.observations.iter().enumerate() {
+            loop {
+                match self.get_status(&mut state, index) {
+                    Status::Satisfied => break,
+                    Status::Violated => return Err(state.stage),
+                    Status::Unknown => {}
+                }
+                let widest = self.get_layouts(observation)
+                    .flat_map(|layout| self.layouts[layout].octaves.clone())
+                    .filter(|&octave| state.noises[octave].is_none())
+                    .map(|octave| (octave, state.bounds[index * self.octaves.len() + octave].map_or(f64::INFINITY, |bounds| bounds.get_width())))
+                    .fold(None, |widest: Option<(usize, f64)>, (octave, width)| match widest {
+                        Some((_, widest_width)) if widest_width >= width => widest,
+                        _ => Some((octave, width)),
+                    });
+                match widest {
+                    Some((octave, _)) if state.offsets[octave].is_none() => self.load_offsets(&mut state, octave),
+                    Some((octave, _)) => match self.get_next_entry(&state, octave, observation) {
+                        Some(entry) => self.load_permutation(&mut state, octave, entry + 1),
+                        None => self.load_noise(&mut state, octave),
+                    },
+                    // with every octave built the noise bounds are points, which decide both a
+                    // sign and a nearest biome, so `get_status` returned before this
+                    None => unreachable!("undecided observation with every octave built"),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The seeds of the range satisfying every observation, in increasing order.
+    pub fn search(&self, seeds: Range<u64>) -> impl Iterator<Item = u64> + '_ {
+        seeds.filter(move |&seed| self.test(seed))
+    }
+
+    fn get_layout(&self, noise: ClimateNoise) -> Option<usize> {
+        self.layouts.iter().position(|layout| layout.noise == noise)
+    }
+
+    fn get_layouts<'a>(&'a self, observation: &'a SeedObservation) -> impl Iterator<Item = usize> + 'a {
+        let noises: &[ClimateNoise] = match &observation.expectation {
+            Expectation::Biome(_) => &[ClimateNoise::Temperature, ClimateNoise::Humidity, ClimateNoise::Altitude, ClimateNoise::Weirdness],
+            Expectation::Positive(noise) | Expectation::Negative(noise) => core::slice::from_ref(noise),
+        };
+        noises.iter().filter_map(move |&noise| self.get_layout(noise))
+    }
+
+    /// Adds the octaves of the noise as the version builds it, false if the version has no such noise.
+    fn add_layout(&mut self, noise: ClimateNoise) -> bool {
+        let legacy_salt: Option<i64> = match noise {
+            ClimateNoise::Temperature => Some(0),
+            ClimateNoise::Humidity => Some(1),
+            ClimateNoise::Altitude => Some(2),
+            ClimateNoise::Weirdness => Some(3),
+            ClimateNoise::Continentalness | ClimateNoise::Erosion => None,
+        };
+        let (first_octave, amplitudes, name): (i32, Vec<f64>, &'static str) = match (self.version, noise) {
+            (SearchVersion::V1_16 | SearchVersion::V1_17, _) if legacy_salt.is_some() => (-7, vec![1.0f64, 1.0f64], ""),
+            (SearchVersion::V1_18, ClimateNoise::Temperature) => (-10, vec![1.5f64, 0.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64], "minecraft:temperature"),
+            (SearchVersion::V1_18, ClimateNoise::Humidity) => (-8, vec![1.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64], "minecraft:vegetation"),
+            (SearchVersion::V1_18, ClimateNoise::Continentalness) => (-9, vec![1.0f64, 1.0f64, 2.0f64, 2.0f64, 2.0f64, 1.0f64, 1.0f64, 1.0f64, 1.0f64], "minecraft:continentalness"),
+            (SearchVersion::V1_18, ClimateNoise::Erosion) => (-9, vec![1.0f64, 1.0f64, 0.0f64, 1.0f64, 1.0f64], "minecraft:erosion"),
+            (SearchVersion::V1_18, ClimateNoise::Weirdness) => (-7, vec![1.0f64, 2.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64], "minecraft:ridge"),
+            _ => return false,
+        };
+        let length = amplitudes.len() as i32;
+        // the legacy layouts searched have no positive octave, all of them come from the one random
+        let start = -first_octave;
+        debug_assert!(start >= length - 1);
+        let first = self.octaves.len();
+        let built: Vec<i32> = (0..length).filter(|&i| amplitudes[i as usize] != 0.0f64).collect();
+        for perlin in 0..2 {
+            for &i in built.iter() {
+                self.octaves.push(Octave {
+                    start: OctaveStart::Positional { name, perlin, octave: first_octave + i },
+                    second: perlin == 1,
+                    amplitude: amplitudes[i as usize],
+                    persistence: pow2(first_octave + i),
+                    lacunarity: pow2(length - 1) / (pow2(length) - 1.0f64) / pow2(i),
+                });
+            }
+        }
+        if let (Some(salt), true) = (legacy_salt, self.version != SearchVersion::V1_18) {
+            // each perlin noise builds the octave `start` then the lower ones, one after the other
+            let (mut after, mut discard, mut skip, mut skip_after): (Option<usize>, bool, u64, u64) = (None, false, 0, 0);
+            for perlin in 0..2 {
+                for i in (0..=start).rev() {
+                    match built.binary_search(&i) {
+                        Ok(rank) => {
+                            let octave = first + perlin * built.len() + rank;
+                            self.octaves[octave].start = OctaveStart::Legacy { salt, after, skip: LCG::combine_java(skip), discard, skip_after: LCG::combine_java(skip_after) };
+                            after = Some(octave);
+                            discard = false;
+                            skip = 0;
+                            skip_after = 0;
+                        }
+                        Err(_) if i == start => discard = true,
+                        Err(_) if discard => skip_after += 262,
+                        Err(_) => skip += 262,
+                    }
+                }
+            }
+        }
+        let used = amplitudes.iter().enumerate().filter(|(_, &amplitude)| amplitude != 0.0f64).map(|(i, _)| i as i32);
+        let spread = used.clone().max().unwrap_or(0) - used.min().unwrap_or(0);
+        let amplitude = 0.16666666666666666f64 / DoublePerlinNoise::create_amplitude(spread);
+        self.layouts.push(NoiseLayout { noise, amplitude, octaves: first..self.octaves.len() });
+        true
+    }
+
+    fn get_octave_random(&self, state: &mut SeedState, octave: usize) -> WorldgenRandom {
+        match self.octaves[octave].start {
+            OctaveStart::Legacy { salt, after, skip, discard, skip_after } => {
+                let mut random = match after {
+                    Some(previous) => {
+                        if state.ends[previous].is_none() {
+                            self.load_noise(state, previous);
+                        }
+                        state.ends[previous].unwrap()
+                    }
+                    None => Random::with_seed(state.seed.wrapping_add(salt as u64)),
+                };
+                random.advance(skip);
+                if discard {
+                    Noise::new(&mut random);
+                }
+                random.advance(skip_after);
+                WorldgenRandom::Legacy(random)
+            }
+            OctaveStart::Positional { name, perlin, octave: index } => {
+                let layout = self.layouts.iter().position(|layout| layout.octaves.contains(&octave)).unwrap();
+                let seed = state.seed as i64;
+                let factories = state.factories[layout].get_or_insert_with(|| {
+                    let mut random = WorldgenRandom::new(seed, false).fork_positional().from_hash_of(name);
+                    [random.fork_positional(), random.fork_positional()]
+                });
+                factories[perlin].from_hash_of(&format!("octave_{}", index))
+            }
+        }
+    }
+
+    fn load_offsets(&self, state: &mut SeedState, octave: usize) {
+        if state.offsets[octave].is_none() {
+            let mut random = self.get_octave_random(state, octave);
+            let x0: f64 = random.next_double() * 256.0;
+            let y0: f64 = random.next_double() * 256.0;
+            let z0: f64 = random.next_double() * 256.0;
+            let mut permutations: [u8; 256] = [0; 256];
+            for (index, permutation) in permutations.iter_mut().enumerate() {
+                *permutation = index as u8;
+            }
+            state.offsets[octave] = Some([x0, y0, z0]);
+            state.partials[octave] = Some(PartialNoise { random, permutations, drawn: 0 });
+            state.stage = Some(Stage::Offsets);
+            self.clear_bounds(state, octave);
+        }
+    }
+
+    /// Shuffles the first `length` permutation entries of the octave, as `Noise::new` does.
+    fn load_permutation(&self, state: &mut SeedState, octave: usize, length: usize) {
+        self.load_offsets(state, octave);
+        let partial = state.partials[octave].as_mut().unwrap();
+        for index in partial.drawn..length {
+            let random_index = partial.random.next_int_n(256i32 - index as i32) as usize;
+            partial.permutations.swap(index, index + random_index);
+        }
+        partial.drawn = partial.drawn.max(length);
+        state.stage = Some(Stage::Permutation);
+        self.clear_bounds(state, octave);
+    }
+
+    fn load_noise(&self, state: &mut SeedState, octave: usize) {
+        self.load_permutation(state, octave, 256);
+        let PartialNoise { random, permutations, .. } = state.partials[octave].take().unwrap();
+        let [x0, y0, z0] = state.offsets[octave].unwrap();
+        if let WorldgenRandom::Legacy(random) = random {
+            state.ends[octave] = Some(random);
+        }
+        state.noises[octave] = Some(Noise { x0, y0, z0, permutations });
+        state.stage = Some(Stage::Noise);
+        self.clear_bounds(state, octave);
+    }
+
+    /// The furthest permutation entry not drawn yet the `lookup` chains of the octave at the
+    /// observation reach next, `None` once the chains are drawn.
+    fn get_next_entry(&self, state: &SeedState, octave: usize, observation: &SeedObservation) -> Option<usize> {
+        let (offsets, partial) = (state.offsets[octave]?, state.partials[octave].as_ref()?);
+        let position = self.get_octave_position(octave, observation);
+        let mut cell = [0i32; 3];
+        for axis in 0..3 {
+            cell[axis] = floor_f64(position[axis] + offsets[axis]) as i32;
+        }
+        let mut next: Option<usize> = None;
+        let mut lookup = |index: i32| -> Option<i32> {
+            let index = (index & 0xff) as usize;
+            if index < partial.drawn {
+                return Some(partial.permutations[index] as i32);
+            }
+            next = Some(next.map_or(index, |next| next.max(index)));
+            None
+        };
+        for x in cell[0]..=cell[0] + 1 {
+            if let Some(px) = lookup(x) {
+                for y in cell[1]..=cell[1] + 1 {
+                    if let Some(pxy) = lookup(px + y) {
+                        for z in cell[2]..=cell[2] + 1 {
+                            lookup(pxy + z);
+                        }
+                    }
+                }
+            }
+        }
+        next
+    }
+
+    fn clear_bounds(&self, state: &mut SeedState, octave: usize) {
+        for bounds in state.bounds.iter_mut().skip(octave).step_by(self.octaves.len()) {
+            *bounds = None;
+        }
+    }
+
+    /// The octave's position at the observation, as `PerlinNoise::sample` passes it.
+    fn get_octave_position(&self, octave: usize, observation: &SeedObservation) -> [f64; 3] {
+        let octave = &self.octaves[octave];
+        // the nether climate of 1.16 and 1.17 does not depend on y
+        let y = if self.version == SearchVersion::V1_18 { observation.y as f64 } else { 0.0f64 };
+        let mut position = [observation.x as f64, y, observation.z as f64];
+        if octave.second {
+            for coordinate in position.iter_mut() {
+                *coordinate *= 1.0181268882175227f64;
+            }
+        }
+        position.map(|coordinate| wrap(coordinate * octave.persistence))
+    }
+
+    /// Bounds of what the octave adds to its perlin noise at the observation.
+    fn get_octave_bounds(&self, state: &mut SeedState, octave: usize, index: usize) -> Interval {
+        let cached = &mut state.bounds[index * self.octaves.len() + octave];
+        if let Some(bounds) = *cached {
+            return bounds;
+        }
+        let bounds = self.compute_octave_bounds(state, octave, &self.observations[index]);
+        state.bounds[index * self.octaves.len() + octave] = Some(bounds);
+        bounds
+    }
+
+    fn compute_octave_bounds(&self, state: &SeedState, octave: usize, observation: &SeedObservation) -> Interval {
+        let [x, y, z] = self.get_octave_position(octave, observation);
+        let bounds = match (&state.noises[octave], state.offsets[octave], &state.partials[octave]) {
+            (Some(noise), _, _) => Interval::point(noise.get_noise_value(x, y, z, 0.0f64, 0.0f64)),
+            (None, Some(offsets), Some(partial)) => get_partial_permutation_bounds([x, y, z], offsets, &partial.permutations, partial.drawn),
+            (None, Some(offsets), None) => get_partial_permutation_bounds([x, y, z], offsets, &[0; 256], 0),
+            (None, None, _) => self.octave_bounds,
+        };
+        let Octave { amplitude, lacunarity, .. } = self.octaves[octave];
+        if bounds.min == bounds.max {
+            return Interval::point(amplitude * bounds.min * lacunarity);
+        }
+        let (a, b) = (amplitude * bounds.min * lacunarity, amplitude * bounds.max * lacunarity);
+        Interval::new(a.min(b), a.max(b))
+    }
+
+    /// Bounds of the noise at the observation, exactly the sampled value once every octave is built.
+    fn get_noise_bounds(&self, state: &mut SeedState, layout: usize, index: usize) -> Interval {
+        let layout = &self.layouts[layout];
+        let mut sums = [Interval::point(0.0f64); 2];
+        let mut exact = true;
+        for octave in layout.octaves.clone() {
+            let bounds = self.get_octave_bounds(state, octave, index);
+            exact &= state.noises[octave].is_some();
+            let sum = &mut sums[self.octaves[octave].second as usize];
+            *sum = Interval::new(sum.min + bounds.min, sum.max + bounds.max);
+        }
+        if exact {
+            return Interval::point((sums[0].min + sums[1].min) * layout.amplitude);
+        }
+        Interval::new((sums[0].min + sums[1].min) * layout.amplitude - SUM_EPSILON, (sums[0].max + sums[1].max) * layout.amplitude + SUM_EPSILON)
+    }
+
+    fn get_status(&self, state: &mut SeedState, index: usize) -> Status {
+        let observation = &self.observations[index];
+        let values: Vec<Interval> = self.get_layouts(observation).collect::<Vec<usize>>().into_iter().map(|layout| self.get_noise_bounds(state, layout, index)).collect();
+        match observation.expectation {
+            Expectation::Positive(_) | Expectation::Negative(_) => {
+                let value = values[0];
+                let positive = matches!(observation.expectation, Expectation::Positive(_));
+                if value.min >= 0.0f64 {
+                    if positive { Status::Satisfied } else { Status::Violated }
+                } else if value.max < 0.0f64 {
+                    if positive { Status::Violated } else { Status::Satisfied }
+                } else {
+                    Status::Unknown
+                }
+            }
+            Expectation::Biome(biome) => {
+                let climate = values;
+                if climate.iter().all(|value| value.min == value.max) {
+                    let point = [climate[0].min as f32, climate[1].min as f32, climate[2].min as f32, climate[3].min as f32, 0.0f32];
+                    return if get_nearest_nether_biome(&point) == biome { Status::Satisfied } else { Status::Violated };
+                }
+                let fitnesses: Vec<(Biome, Interval)> = NETHER_BIOMES.iter().map(|(other, parameters)| {
+                    let mut fitness = Interval::point(0.0f64);
+                    for (i, value) in climate.iter().enumerate() {
+                        let (a, b) = (parameters[i] as f64 - value.max, parameters[i] as f64 - value.min);
+                        let low = if a <= 0.0f64 && 0.0f64 <= b { 0.0f64 } else { (a * a).min(b * b) };
+                        fitness = Interval::new(fitness.min + low, fitness.max + (a * a).max(b * b));
+                    }
+                    // the offset parameter against the offset of 0
+                    let offset = parameters[4] as f64 * parameters[4] as f64;
+                    (*other, Interval::new(fitness.min + offset - FITNESS_EPSILON, fitness.max + offset + FITNESS_EPSILON))
+                }).collect();
+                let own = fitnesses.iter().find(|(other, _)| *other == biome).map(|(_, fitness)| *fitness).unwrap();
+                if fitnesses.iter().any(|(other, fitness)| *other != biome && fitness.max < own.min) {
+                    Status::Violated
+                } else if fitnesses.iter().all(|(other, fitness)| *other == biome || own.max < fitness.min) {
+                    Status::Satisfied
+                } else {
+                    Status::Unknown
+                }
+            }
+        }
+    }
+}
+
+/// Bounds of a noise with the given offsets and a permutation only drawn up to `drawn` at the
+/// position. A corner whose `lookup` chain is drawn takes its gradient, any other corner any of
+/// them: the gradients are dot products with a vector of two unit coordinates, the largest of
+/// which is the sum of the two largest distances to the corner along an axis.
+fn get_partial_permutation_bounds(position: [f64; 3], offsets: [f64; 3], permutations: &[u8; 256], drawn: usize) -> Interval {
+    let mut cell = [0i32; 3];
+    let mut fractions = [0.0f64; 3];
+    for axis in 0..3 {
+        let offset_position = position[axis] + offsets[axis];
+        let floor = floor_f64(offset_position);
+        cell[axis] = floor as i32;
+        fractions[axis] = offset_position - floor;
+    }
+    let lookup = |index: i32| -> Option<i32> {
+        let index = (index & 0xff) as usize;
+        if index < drawn { Some(permutations[index] as i32) } else { None }
+    };
+    let mut lows = [0.0f64; 8];
+    let mut highs = [0.0f64; 8];
+    for i in 0..8 {
+        let mut distances = [0.0f64; 3];
+        for axis in 0..3 {
+            distances[axis] = fractions[axis] - (i >> axis & 1) as f64;
+        }
+        let hash = lookup(cell[0] + (i & 1) as i32)
+            .and_then(|px| lookup(px + cell[1] + (i >> 1 & 1) as i32))
+            .and_then(|pxy| lookup(pxy + cell[2] + (i >> 2 & 1) as i32));
+        match hash {
+            Some(hash) => {
+                let gradient = grad(hash as u8, distances[0], distances[1], distances[2]);
+                lows[i] = gradient;
+                highs[i] = gradient;
+            }
+            None => {
+                let distances = distances.map(f64::abs);
+                let smallest = distances[0].min(distances[1]).min(distances[2]);
+                highs[i] = distances[0] + distances[1] + distances[2] - smallest;
+                lows[i] = -highs[i];
+            }
+        }
+    }
+    // lerp3 weighs the corners by non negative factors
+    let [x, y, z] = fractions.map(smooth_step);
+    let low = lerp3(x, y, z, lows[0], lows[1], lows[2], lows[3], lows[4], lows[5], lows[6], lows[7]);
+    let high = lerp3(x, y, z, highs[0], highs[1], highs[2], highs[3], highs[4], highs[5], highs[6], highs[7]);
+    Interval::new(low - SUM_EPSILON, high + SUM_EPSILON)
+}
@@ -1,10 +1,14 @@
 #![allow(dead_code)]
 
 use java_random::Random;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use intmap::IntMap;
+#[cfg(feature = "std")]
+use crate::int_cache::IntCache;
 use crate::noise::Noise;
-use crate::math::dot;
+use crate::math::{dot, floor_f64};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 pub const F2: f64 = 0.3660254037844386;
 pub const G2: f64 = 0.21132486540518713;
@@ -29,14 +33,20 @@ pub const GRADIENT: [[i32; 3]; 16] =
         [0, -1, -1]];
 
 #[derive(Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SimplexNoise {
     pub noise: Noise,
-    pub cache2d: IntMap<f64>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "IntCache::new"))]
+    pub cache2d: IntCache<f64>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub cache3d: HashMap<u128, f64>,
 }
 
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod simplex_test {
     use super::*;
 
@@ -58,6 +68,16 @@ mod simplex_test {
         let mut simplex_noise:SimplexNoise=SimplexNoise::init(random);
         assert_eq!(simplex_noise.get_value_3d(0.5,0.6,100.0),-0.047980544000000055);
     }
+    #[test]
+    fn test_cache_keys() {
+        let mut simplex_noise: SimplexNoise = SimplexNoise::init(Random::with_seed(12));
+        let positive: f64 = simplex_noise.get_value_2d(0.0, 0.0);
+        assert_eq!(simplex_noise.get_value_2d(-3.0, -7.0), simplex_noise._get_value_2d(-3.0, -7.0));
+        assert_eq!(simplex_noise.get_value_2d(0.5, 0.0), simplex_noise._get_value_2d(0.5, 0.0));
+        assert_eq!(simplex_noise.get_value_2d(0.0, 0.0), positive);
+        simplex_noise.get_value_3d(0.0, 0.0, 0.0);
+        assert_eq!(simplex_noise.get_value_3d(-1.0, 0.0, -2.0), simplex_noise._get_value_3d(-1.0, 0.0, -2.0));
+    }
 }
 
 impl SimplexNoise {
@@ -65,7 +85,9 @@ impl SimplexNoise {
     pub fn new(noise: Noise) -> Self {
         SimplexNoise {
             noise,
-            cache2d: IntMap::with_capacity(1024),
+            #[cfg(feature = "std")]
+            cache2d: IntCache::with_capacity(1024),
+            #[cfg(feature = "std")]
             cache3d: HashMap::new(),
         }
     }
@@ -77,6 +99,7 @@ impl SimplexNoise {
         self.noise.lookup(n as i32)
     }
 
+    #[allow(clippy::needless_late_init)]
     fn get_corner_noise3d(n: u8, x: f64, y: f64, z: f64, max: f64) -> f64 {
         let res: f64;
         let mut contribution: f64 = max - x * x - y * y - z * z;
@@ -89,10 +112,16 @@ impl SimplexNoise {
         res
     }
 
+    #[allow(clippy::needless_return)]
+    #[cfg(feature = "std")]
     pub fn get_value_2d(&mut self, x: f64, z: f64) -> f64 {
-        let key: u64 = (((x as u32) as u64) << 32 | ((z as u32) as u64)) as u64;
-        let value: f64 = *self.cache2d.get(key).unwrap_or(&std::f64::MAX);
-        if value != std::f64::MAX {
+        // only integer coordinates are cached, the key would mix up the others
+        if x != (x as i32) as f64 || z != (z as i32) as f64 {
+            return self._get_value_2d(x, z);
+        }
+        let key: u64 = ((x as i32 as u32) as u64) << 32 | ((z as i32 as u32) as u64);
+        let value: f64 = *self.cache2d.get(key).unwrap_or(&f64::MAX);
+        if value != f64::MAX {
             return value;
         }
         let value: f64 = self._get_value_2d(x, z);
@@ -100,10 +129,15 @@ impl SimplexNoise {
         return value;
     }
 
-    fn _get_value_2d(&self, x: f64, z: f64) -> f64 {
+    #[cfg(not(feature = "std"))]
+    pub fn get_value_2d(&mut self, x: f64, z: f64) -> f64 {
+        self._get_value_2d(x, z)
+    }
+
+    pub(crate) fn _get_value_2d(&self, x: f64, z: f64) -> f64 {
         let hairy_factor: f64 = (x + z) * F2;
-        let temperature_x: i32 = (x + hairy_factor).floor() as i32;
-        let temperature_z: i32 = (z + hairy_factor).floor() as i32;
+        let temperature_x: i32 = floor_f64(x + hairy_factor) as i32;
+        let temperature_z: i32 = floor_f64(z + hairy_factor) as i32;
         let mixed_temperature_x_z: f64 = (temperature_x + temperature_z) as f64 * G2;
         let temp_diff_x_to_z: f64 = temperature_x as f64 - mixed_temperature_x_z;
         let temp_diff_z_to_x: f64 = temperature_z as f64 - mixed_temperature_x_z;
@@ -134,22 +168,33 @@ impl SimplexNoise {
         70.0f64 * (t0 + t1 + t2)
     }
 
+    #[allow(clippy::needless_return)]
+    #[cfg(feature = "std")]
     pub fn get_value_3d(&mut self, x: f64, y: f64, z: f64) -> f64 {
-        let key: u128 = (((x as u32) as u128) << 64 | ((y as u32) as u128) << 32 | ((z as u32) as u128)) as u128;
-        let value: f64 = *self.cache3d.get(&key).unwrap_or(&std::f64::MAX);
-        if value != std::f64::MAX {
+        if x != (x as i32) as f64 || y != (y as i32) as f64 || z != (z as i32) as f64 {
+            return self._get_value_3d(x, y, z);
+        }
+        let key: u128 = ((x as i32 as u32) as u128) << 64 | ((y as i32 as u32) as u128) << 32 | ((z as i32 as u32) as u128);
+        let value: f64 = *self.cache3d.get(&key).unwrap_or(&f64::MAX);
+        if value != f64::MAX {
             return value;
         }
         let value: f64 = self._get_value_3d(x, y, z);
         self.cache3d.insert(key, value);
         return value;
     }
-    fn _get_value_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+
+    #[cfg(not(feature = "std"))]
+    pub fn get_value_3d(&mut self, x: f64, y: f64, z: f64) -> f64 {
+        self._get_value_3d(x, y, z)
+    }
+
+    pub(crate) fn _get_value_3d(&self, x: f64, y: f64, z: f64) -> f64 {
         let skew_factor: f64 = (x + y + z) * F3; // F3 is 1/3
         // Skew the input space to determine which simplex cell we're in
-        let i: i32 = (x + skew_factor).floor() as i32;
-        let j: i32 = (y + skew_factor).floor() as i32;
-        let k: i32 = (z + skew_factor).floor() as i32;
+        let i: i32 = floor_f64(x + skew_factor) as i32;
+        let j: i32 = floor_f64(y + skew_factor) as i32;
+        let k: i32 = floor_f64(z + skew_factor) as i32;
         let unskew_factor: f64 = (i + j + k) as f64 * G3; // G3 is 1/6
         // Unskew the cell origin back to (x,y,z) space
         let x0: f64 = (i) as f64 - unskew_factor;
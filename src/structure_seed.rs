@@ -0,0 +1,181 @@
+//! Structure seeds, the lower 48 bits of a world seed, and the world seeds sharing them.
+//!
+//! `java.util.Random` only keeps the lower 48 bits of its seed, so every noise seeded through it
+//! is the same for the 65536 world seeds of a structure seed: searches enumerate structure seeds,
+//! build those noises once, then only go through the upper 16 bits for the components hashing or
+//! mixing the whole seed. [`SeedComponent`] tells which is which.
+
+use alloc::vec::Vec;
+
+pub const STRUCTURE_SEED_BITS: u32 = 48;
+pub const STRUCTURE_SEED_MASK: u64 = (1u64 << STRUCTURE_SEED_BITS) - 1;
+
+pub fn get_structure_seed(world_seed: i64) -> u64 {
+    world_seed as u64 & STRUCTURE_SEED_MASK
+}
+
+/// The world seed of a structure seed with the given upper 16 bits.
+pub fn get_world_seed(structure_seed: u64, upper_bits: u16) -> i64 {
+    ((upper_bits as u64) << STRUCTURE_SEED_BITS | structure_seed & STRUCTURE_SEED_MASK) as i64
+}
+
+/// The 65536 world seeds of a structure seed, by increasing upper bits.
+pub fn world_seeds(structure_seed: u64) -> WorldSeeds {
+    WorldSeeds { structure_seed: structure_seed & STRUCTURE_SEED_MASK, upper_bits: 0 }
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldSeeds {
+    structure_seed: u64,
+    upper_bits: u32,
+}
+
+impl Iterator for WorldSeeds {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        if self.upper_bits > u16::MAX as u32 {
+            return None;
+        }
+        let world_seed = get_world_seed(self.structure_seed, self.upper_bits as u16);
+        self.upper_bits += 1;
+        Some(world_seed)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (u16::MAX as u32 + 1).saturating_sub(self.upper_bits) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for WorldSeeds {}
+
+/// The seeded parts of the crate, by what they are built from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedComponent {
+    /// `PerlinNoise`, `DoublePerlinNoise` and `SimplexNoise` seeded by a `java_random::Random`.
+    LegacyNoise,
+    /// `NoiseChunkGenerator`, the 1.16 terrain noises.
+    NoiseChunkGenerator,
+    /// `NetherBiomeSource`, seeded by the world seed plus 0 to 3.
+    NetherBiomeSource,
+    /// `EndIslands` and `EndBiomeSource`.
+    EndIslands,
+    /// 1.18+ noises of settings with `legacy_random_source` (nether, end), the positional factory
+    /// being the next long of the seed's java random.
+    LegacyRandomSource,
+    /// 1.18+ noises of the xoroshiro random source (overworld), seeded by the mixed 64 bit seed.
+    XoroshiroRandomSource,
+    /// `Voronoi`, the biome zoom working on the sha256 hash of the world seed.
+    Voronoi,
+}
+
+impl SeedComponent {
+    /// Whether two world seeds of a structure seed can give the component different values, it
+    /// then has to be built for each world seed instead of once for the structure seed.
+    pub fn depends_on_upper_bits(&self) -> bool {
+        match self {
+            SeedComponent::LegacyNoise
+            | SeedComponent::NoiseChunkGenerator
+            | SeedComponent::NetherBiomeSource
+            | SeedComponent::EndIslands
+            | SeedComponent::LegacyRandomSource => false,
+            SeedComponent::XoroshiroRandomSource | SeedComponent::Voronoi => true,
+        }
+    }
+}
+
+/// Noises built once for a structure seed, shared by all of its world seeds.
+///
+/// Only components whose [`SeedComponent::depends_on_upper_bits`] is false belong in here, the
+/// others are built from the world seeds `filter_world_seeds` goes through.
+#[derive(Clone, Debug)]
+pub struct StructureSeedNoises<N> {
+    structure_seed: u64,
+    noises: N,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod structure_seed_test {
+    use super::*;
+    use crate::biome_source::{BiomeSource, NetherBiomeSource};
+    use crate::create_range;
+    use crate::perlin_noise::PerlinNoise;
+    use crate::random_source::{RandomSource, WorldgenRandom};
+    use java_random::Random;
+
+    const WORLD_SEED: i64 = -4172144997902289642;
+
+    #[test]
+    fn test_seeds() {
+        let structure_seed = get_structure_seed(WORLD_SEED);
+        assert_eq!(structure_seed, 0x903a_b89c_7516u64);
+        assert_eq!(get_world_seed(structure_seed, (WORLD_SEED as u64 >> 48) as u16), WORLD_SEED);
+        let seeds = world_seeds(structure_seed);
+        assert_eq!(seeds.len(), 65536);
+        assert!(seeds.clone().all(|seed| get_structure_seed(seed) == structure_seed));
+        assert!(seeds.clone().any(|seed| seed == WORLD_SEED));
+        assert_eq!(seeds.last(), Some(get_world_seed(structure_seed, u16::MAX)));
+    }
+
+    #[test]
+    fn test_lower_bits_components() {
+        let structure_seed = get_structure_seed(WORLD_SEED);
+        let perlin = PerlinNoise::new(&mut Random::with_seed(structure_seed), create_range(-7, 0));
+        let world_perlin = PerlinNoise::new(&mut Random::with_seed(WORLD_SEED as u64), create_range(-7, 0));
+        assert_eq!(perlin.sample_default(10.5f64, 3.0f64, -7.25f64), world_perlin.sample_default(10.5f64, 3.0f64, -7.25f64));
+        let mut nether = NetherBiomeSource::new(structure_seed as i64);
+        let mut world_nether = NetherBiomeSource::new(WORLD_SEED);
+        for x in -20..20 {
+            assert_eq!(nether.get_noise_biome(x * 37, 0, x * 11), world_nether.get_noise_biome(x * 37, 0, x * 11));
+        }
+        assert_eq!(WorldgenRandom::new(structure_seed as i64, true).fork_positional(), WorldgenRandom::new(WORLD_SEED, true).fork_positional());
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn test_upper_bits_components() {
+        let structure_seed = get_structure_seed(WORLD_SEED);
+        assert!(SeedComponent::Voronoi.depends_on_upper_bits());
+        assert_ne!(WorldgenRandom::new(structure_seed as i64, false).fork_positional(), WorldgenRandom::new(WORLD_SEED, false).fork_positional());
+        let mut voronoi = crate::voronoi::Voronoi::new(crate::math::sha2long(structure_seed) as i64);
+        let mut world_voronoi = crate::voronoi::Voronoi::new(crate::math::sha2long(WORLD_SEED as u64) as i64);
+        assert!((0..64).any(|x| voronoi.get_fuzzy_positions(x, 0, 0) != world_voronoi.get_fuzzy_positions(x, 0, 0)));
+    }
+
+    #[test]
+    fn test_filter_world_seeds() {
+        let mut noises = StructureSeedNoises::new(12345, |seed| PerlinNoise::new(&mut Random::with_seed(seed), create_range(-3, 0)));
+        let sample = noises.get_noises().sample_default(0.5f64, 0.5f64, 0.5f64);
+        let found = noises.filter_world_seeds(|world_seed, noise| world_seed < 0 && noise.sample_default(0.5f64, 0.5f64, 0.5f64) == sample);
+        assert_eq!(found.len(), 32768);
+        assert_eq!(found[0], get_world_seed(12345, 0x8000));
+    }
+}
+
+impl<N> StructureSeedNoises<N> {
+    /// Builds the noises from the structure seed, `build` taking it as the java random seed.
+    pub fn new<B: FnOnce(u64) -> N>(structure_seed: u64, build: B) -> Self {
+        let structure_seed = structure_seed & STRUCTURE_SEED_MASK;
+        StructureSeedNoises { structure_seed, noises: build(structure_seed) }
+    }
+
+    pub fn get_structure_seed(&self) -> u64 {
+        self.structure_seed
+    }
+
+    pub fn get_noises(&mut self) -> &mut N {
+        &mut self.noises
+    }
+
+    pub fn world_seeds(&self) -> WorldSeeds {
+        world_seeds(self.structure_seed)
+    }
+
+    /// The world seeds of the structure seed the predicate holds for, given the shared noises.
+    pub fn filter_world_seeds<F: FnMut(i64, &mut N) -> bool>(&mut self, mut predicate: F) -> Vec<i64> {
+        let noises = &mut self.noises;
+        world_seeds(self.structure_seed).filter(|&world_seed| predicate(world_seed, noises)).collect()
+    }
+}
@@ -0,0 +1,643 @@
+//! The 1.13-1.17 surface builders, replacing the top stone of a column by the biome materials.
+//!
+//! A column is a slice of blocks indexed by y, holding the default block where the noise is solid
+//! and the default fluid below the sea level. Vanilla runs the builders over a chunk with a single
+//! random seeded by `setBaseChunkSeed`, each column drawing from it in order (`x` then `z`).
+
+use alloc::vec;
+
+use java_random::Random;
+
+use crate::block::Block;
+use crate::math::{cos_f64, floor_f64};
+use crate::perlin_simplex_noise::PerlinSimplexNoise;
+
+/// Top, under and underwater materials of a configured surface builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SurfaceBuilderConfig {
+    pub top_material: Block,
+    pub under_material: Block,
+    pub underwater_material: Block,
+}
+
+impl SurfaceBuilderConfig {
+    pub const fn new(top_material: Block, under_material: Block, underwater_material: Block) -> Self {
+        SurfaceBuilderConfig { top_material, under_material, underwater_material }
+    }
+}
+
+pub const CONFIG_PODZOL: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Podzol, Block::Dirt, Block::Gravel);
+pub const CONFIG_GRAVEL: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Gravel, Block::Gravel, Block::Gravel);
+pub const CONFIG_GRASS: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::GrassBlock, Block::Dirt, Block::Gravel);
+pub const CONFIG_STONE: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Stone, Block::Stone, Block::Gravel);
+pub const CONFIG_COARSE_DIRT: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::CoarseDirt, Block::Dirt, Block::Gravel);
+pub const CONFIG_DESERT: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Sand, Block::Sand, Block::Gravel);
+pub const CONFIG_OCEAN_SAND: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::GrassBlock, Block::Dirt, Block::Sand);
+pub const CONFIG_FULL_SAND: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Sand, Block::Sand, Block::Sand);
+pub const CONFIG_BADLANDS: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::RedSand, Block::WhiteTerracotta, Block::Gravel);
+pub const CONFIG_MYCELIUM: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Mycelium, Block::Dirt, Block::Gravel);
+pub const CONFIG_HELL: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::Netherrack, Block::Netherrack, Block::Netherrack);
+pub const CONFIG_THEEND: SurfaceBuilderConfig = SurfaceBuilderConfig::new(Block::EndStone, Block::EndStone, Block::EndStone);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurfaceBuilderType {
+    Default,
+    Badlands,
+    ErodedBadlands,
+    /// Coarse dirt, podzol or grass depending on the surface noise, the configuration is ignored.
+    GiantTreeTaiga,
+    /// Default with water puddles at y 62.
+    Swamp,
+    /// Default with icebergs.
+    FrozenOcean,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemperatureModifier {
+    None,
+    /// Warms up patches of the frozen oceans to 0.2.
+    Frozen,
+}
+
+/// What the surface builders need from a biome.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SurfaceBiome {
+    pub builder: SurfaceBuilderType,
+    pub config: SurfaceBuilderConfig,
+    pub temperature: f32,
+    pub temperature_modifier: TemperatureModifier,
+}
+
+/// Provides the biome of the surface builders at block coordinates.
+pub trait SurfaceBiomeSource {
+    fn get_surface_biome(&mut self, x: i32, y: i32, z: i32) -> SurfaceBiome;
+}
+
+impl<F: FnMut(i32, i32, i32) -> SurfaceBiome> SurfaceBiomeSource for F {
+    fn get_surface_biome(&mut self, x: i32, y: i32, z: i32) -> SurfaceBiome {
+        self(x, y, z)
+    }
+}
+
+/// The seed dependent noises of the surface builders and the fixed seed ones of the biome
+/// temperature.
+#[derive(Clone, Debug)]
+pub struct SurfaceBuilders {
+    clay_bands: [Block; 64],
+    clay_bands_offset_noise: PerlinSimplexNoise,
+    pillar_noise: PerlinSimplexNoise,
+    pillar_roof_noise: PerlinSimplexNoise,
+    iceberg_noise: PerlinSimplexNoise,
+    iceberg_roof_noise: PerlinSimplexNoise,
+    biome_noises: BiomeNoises,
+}
+
+/// The fixed seed noises of vanilla `Biome`, shared by the 1.13-1.17 builders and the 1.18 rules.
+#[derive(Clone, Debug)]
+pub(crate) struct BiomeNoises {
+    temperature_noise: PerlinSimplexNoise,
+    frozen_temperature_noise: PerlinSimplexNoise,
+    biome_info_noise: PerlinSimplexNoise,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod surface_builder_test {
+    use super::*;
+    use alloc::vec::Vec;
+
+    const PLAINS: SurfaceBiome = SurfaceBiome::new(SurfaceBuilderType::Default, CONFIG_GRASS, 0.8f32);
+    const DESERT: SurfaceBiome = SurfaceBiome::new(SurfaceBuilderType::Default, CONFIG_DESERT, 2.0f32);
+    const BADLANDS: SurfaceBiome = SurfaceBiome::new(SurfaceBuilderType::Badlands, CONFIG_BADLANDS, 2.0f32);
+
+    /// Stone up to `top`, water up to the sea level 63.
+    fn column(top: i32) -> Vec<Block> {
+        (0..256).map(|y| if y <= top { Block::Stone } else if y < 63 { Block::Water } else { Block::Air }).collect()
+    }
+
+    fn build(builders: &SurfaceBuilders, biome: &SurfaceBiome, top: i32, noise: f64) -> Vec<Block> {
+        let mut blocks = column(top);
+        let height = top.max(62) + 1;
+        builders.build_surface(&mut Random::with_seed(1), &mut blocks, biome, 5, -7, height, noise, Block::Stone, Block::Water, 63);
+        blocks
+    }
+
+    #[test]
+    fn test_default() {
+        let builders = SurfaceBuilders::new(1);
+        let blocks = build(&builders, &PLAINS, 70, 0.0f64);
+        assert_eq!(&blocks[66..71], &[Block::Stone, Block::Dirt, Block::Dirt, Block::Dirt, Block::GrassBlock]);
+        // under water the top material turns into the under one
+        let blocks = build(&builders, &PLAINS, 60, 0.0f64);
+        assert_eq!(&blocks[56..61], &[Block::Stone, Block::Dirt, Block::Dirt, Block::Dirt, Block::Dirt]);
+        // too deep for dirt
+        let blocks = build(&builders, &PLAINS, 40, 0.0f64);
+        assert_eq!(&blocks[39..42], &[Block::Stone, Block::Gravel, Block::Water]);
+        // a negative depth strips the top block
+        let blocks = build(&builders, &PLAINS, 70, -12.0f64);
+        assert_eq!(&blocks[69..71], &[Block::Stone, Block::Air]);
+    }
+
+    #[test]
+    fn test_sandstone() {
+        let builders = SurfaceBuilders::new(1);
+        let blocks = build(&builders, &DESERT, 70, 3.0f64);
+        assert_eq!(&blocks[65..71], &[Block::Sandstone, Block::Sand, Block::Sand, Block::Sand, Block::Sand, Block::Sand]);
+    }
+
+    #[test]
+    fn test_frozen_water() {
+        let builders = SurfaceBuilders::new(1);
+        let snowy = SurfaceBiome::new(SurfaceBuilderType::Default, CONFIG_GRASS, 0.0f32);
+        // bare stone reaching y 62 is topped by the fluid or ice
+        assert_eq!(build(&builders, &snowy, 62, -12.0f64)[62], Block::Ice);
+        assert_eq!(build(&builders, &PLAINS, 62, -12.0f64)[62], Block::Water);
+    }
+
+    #[test]
+    fn test_clay_bands() {
+        let builders = SurfaceBuilders::new(1);
+        assert_eq!(&builders.get_clay_bands()[..8], &[
+            Block::Terracotta,
+            Block::Terracotta,
+            Block::RedTerracotta,
+            Block::RedTerracotta,
+            Block::RedTerracotta,
+            Block::Terracotta,
+            Block::Terracotta,
+            Block::YellowTerracotta,
+        ]);
+        // plain terracotta on top then the bands, 15 blocks deep
+        let blocks = build(&builders, &BADLANDS, 100, 1.0f64);
+        assert_eq!(&blocks[85..90], &[Block::Stone, Block::Terracotta, Block::BrownTerracotta, Block::WhiteTerracotta, Block::LightGrayTerracotta]);
+        assert_eq!(blocks[100], Block::Terracotta);
+        assert_eq!(build(&builders, &BADLANDS, 100, 4.0f64)[100], builders.get_band(5, 100, -7));
+        // red sand close to the sea level, on orange terracotta
+        let blocks = build(&builders, &BADLANDS, 66, 1.0f64);
+        assert_eq!(&blocks[59..61], &[Block::Stone, Block::OrangeTerracotta]);
+        assert_eq!(&blocks[65..67], &[Block::OrangeTerracotta, Block::RedSand]);
+    }
+
+    #[test]
+    fn test_noise_features() {
+        let builders = SurfaceBuilders::new(1);
+        let build_at = |biome: &SurfaceBiome, x: i32, top: i32, noise: f64| {
+            let mut blocks = column(top);
+            builders.build_surface(&mut Random::with_seed(1), &mut blocks, biome, x, 0, top.max(62) + 1, noise, Block::Stone, Block::Water, 63);
+            let surface = blocks.iter().rposition(|block| !block.is_air()).unwrap();
+            (surface, blocks[surface])
+        };
+        let frozen_ocean = SurfaceBiome::new(SurfaceBuilderType::FrozenOcean, CONFIG_GRASS, 0.0f32).with_temperature_modifier(TemperatureModifier::Frozen);
+        assert_eq!(build_at(&frozen_ocean, 1, 40, 10.0f64), (62, Block::Water));
+        assert_eq!(build_at(&frozen_ocean, 11, 40, 10.0f64), (77, Block::PackedIce));
+        let eroded_badlands = SurfaceBiome::new(SurfaceBuilderType::ErodedBadlands, CONFIG_BADLANDS, 2.0f32);
+        assert_eq!(build_at(&eroded_badlands, 1, 66, 10.0f64), (66, Block::RedSand));
+        assert_eq!(build_at(&eroded_badlands, 17, 66, 10.0f64), (85, Block::OrangeTerracotta));
+        let swamp = SurfaceBiome::new(SurfaceBuilderType::Swamp, CONFIG_GRASS, 0.8f32);
+        assert_eq!(build_at(&swamp, 1, 62, 1.0f64), (62, Block::Water));
+        assert_eq!(build_at(&swamp, 2, 62, 1.0f64), (62, Block::GrassBlock));
+    }
+
+    #[test]
+    fn test_giant_tree_taiga() {
+        let builders = SurfaceBuilders::new(1);
+        let taiga = SurfaceBiome::new(SurfaceBuilderType::GiantTreeTaiga, CONFIG_GRASS, 0.3f32);
+        assert_eq!(build(&builders, &taiga, 70, 2.0f64)[70], Block::CoarseDirt);
+        assert_eq!(build(&builders, &taiga, 70, 0.0f64)[70], Block::Podzol);
+        assert_eq!(build(&builders, &taiga, 70, -1.0f64)[70], Block::GrassBlock);
+    }
+}
+
+impl SurfaceBiome {
+    pub const fn new(builder: SurfaceBuilderType, config: SurfaceBuilderConfig, temperature: f32) -> Self {
+        SurfaceBiome { builder, config, temperature, temperature_modifier: TemperatureModifier::None }
+    }
+
+    pub const fn with_temperature_modifier(self, temperature_modifier: TemperatureModifier) -> Self {
+        SurfaceBiome { temperature_modifier, ..self }
+    }
+}
+
+impl BiomeNoises {
+    pub(crate) fn new() -> Self {
+        BiomeNoises {
+            temperature_noise: PerlinSimplexNoise::new(&mut Random::with_seed(1234), vec![0]),
+            frozen_temperature_noise: PerlinSimplexNoise::new(&mut Random::with_seed(3456), vec![-2, -1, 0]),
+            biome_info_noise: PerlinSimplexNoise::new(&mut Random::with_seed(2345), vec![0]),
+        }
+    }
+
+    /// The temperature before the height adjustment.
+    pub(crate) fn modify_temperature(&self, temperature: f32, temperature_modifier: TemperatureModifier, x: i32, z: i32) -> f32 {
+        match temperature_modifier {
+            TemperatureModifier::None => temperature,
+            TemperatureModifier::Frozen => {
+                let frozen: f64 = self.frozen_temperature_noise.get_value(x as f64 * 0.05f64, z as f64 * 0.05f64, false) * 7.0f64;
+                let info: f64 = self.get_biome_info(x as f64 * 0.2f64, z as f64 * 0.2f64);
+                if frozen + info < 0.3f64 && self.get_biome_info(x as f64 * 0.09f64, z as f64 * 0.09f64) < 0.8f64 {
+                    0.2f32
+                } else {
+                    temperature
+                }
+            }
+        }
+    }
+
+    /// The noise cooling down high blocks, sampled every 8 blocks.
+    pub(crate) fn get_temperature_noise(&self, x: i32, z: i32) -> f64 {
+        self.temperature_noise.get_value((x as f32 / 8.0f32) as f64, (z as f32 / 8.0f32) as f64, false)
+    }
+
+    pub(crate) fn get_biome_info(&self, x: f64, z: f64) -> f64 {
+        self.biome_info_noise.get_value(x, z, false)
+    }
+}
+
+fn get_block(column: &[Block], y: i32) -> Block {
+    if y < 0 || y as usize >= column.len() { Block::Air } else { column[y as usize] }
+}
+
+fn set_block(column: &mut [Block], y: i32, block: Block) {
+    if y >= 0 && (y as usize) < column.len() {
+        column[y as usize] = block;
+    }
+}
+
+/// Java `Math.ceil`.
+fn ceil(x: f64) -> f64 {
+    -floor_f64(-x)
+}
+
+/// Java `Math.round`.
+pub(crate) fn round(x: f64) -> i32 {
+    floor_f64(x + 0.5f64) as i32
+}
+
+fn get_depth(random: &mut Random, noise: f64) -> i32 {
+    (noise / 3.0f64 + 3.0f64 + random.next_double() * 0.25f64) as i32
+}
+
+impl SurfaceBuilders {
+    pub fn new(seed: u64) -> Self {
+        let mut bands_random = Random::with_seed(seed);
+        let clay_bands_offset_noise = PerlinSimplexNoise::new(&mut bands_random, vec![0]);
+        let clay_bands = SurfaceBuilders::generate_bands(&mut bands_random);
+        let mut pillar_random = Random::with_seed(seed);
+        let pillar_noise = PerlinSimplexNoise::new(&mut pillar_random, vec![-3, -2, -1, 0]);
+        let pillar_roof_noise = PerlinSimplexNoise::new(&mut pillar_random, vec![0]);
+        let mut iceberg_random = Random::with_seed(seed);
+        let iceberg_noise = PerlinSimplexNoise::new(&mut iceberg_random, vec![-3, -2, -1, 0]);
+        let iceberg_roof_noise = PerlinSimplexNoise::new(&mut iceberg_random, vec![0]);
+        SurfaceBuilders {
+            clay_bands,
+            clay_bands_offset_noise,
+            pillar_noise,
+            pillar_roof_noise,
+            iceberg_noise,
+            iceberg_roof_noise,
+            biome_noises: BiomeNoises::new(),
+        }
+    }
+
+    fn generate_bands(random: &mut Random) -> [Block; 64] {
+        let mut clay_bands = [Block::Terracotta; 64];
+        let mut i: i32 = 0;
+        while i < 64 {
+            i += random.next_int_n(5) + 1;
+            if i < 64 {
+                clay_bands[i as usize] = Block::OrangeTerracotta;
+            }
+            i += 1;
+        }
+        for (block, min_size) in [(Block::YellowTerracotta, 1), (Block::BrownTerracotta, 2), (Block::RedTerracotta, 1)] {
+            let count = random.next_int_n(4) + 2;
+            for _ in 0..count {
+                let size = random.next_int_n(3) + min_size;
+                let start = random.next_int_n(64);
+                for j in 0..size {
+                    if start + j >= 64 {
+                        break;
+                    }
+                    clay_bands[(start + j) as usize] = block;
+                }
+            }
+        }
+        let count = random.next_int_n(3) + 3;
+        let mut position: i32 = 0;
+        for _ in 0..count {
+            position += random.next_int_n(16) + 4;
+            if position >= 64 {
+                continue;
+            }
+            clay_bands[position as usize] = Block::WhiteTerracotta;
+            if position > 1 && random.next_boolean() {
+                clay_bands[(position - 1) as usize] = Block::LightGrayTerracotta;
+            }
+            if position < 63 && random.next_boolean() {
+                clay_bands[(position + 1) as usize] = Block::LightGrayTerracotta;
+            }
+        }
+        clay_bands
+    }
+
+    pub fn get_clay_bands(&self) -> &[Block; 64] {
+        &self.clay_bands
+    }
+
+    /// The badlands band at a position, offset by up to 2 blocks along a low frequency noise.
+    pub fn get_band(&self, x: i32, y: i32, z: i32) -> Block {
+        let offset: i32 = round(self.clay_bands_offset_noise.get_value(x as f64 / 512.0f64, z as f64 / 512.0f64, false) * 2.0f64);
+        self.clay_bands[((y + offset + 64) % 64) as usize]
+    }
+
+    /// Vanilla `Biome.getTemperature`, cooling down above y 64.
+    pub fn get_temperature(&self, biome: &SurfaceBiome, x: i32, y: i32, z: i32) -> f32 {
+        let temperature: f32 = self.biome_noises.modify_temperature(biome.temperature, biome.temperature_modifier, x, z);
+        if y > 64 {
+            let noise: f32 = (self.biome_noises.get_temperature_noise(x, z) * 4.0f64) as f32;
+            temperature - (noise + y as f32 - 64.0f32) * 0.05f32 / 30.0f32
+        } else {
+            temperature
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Builds the surface of the column at `x`, `z` from `height` down, `height` being one above
+    /// the topmost non air block and `noise` the surface depth noise of the column.
+    pub fn build_surface(&self, random: &mut Random, column: &mut [Block], biome: &SurfaceBiome, x: i32, z: i32, height: i32, noise: f64, default_block: Block, default_fluid: Block, sea_level: i32) {
+        match biome.builder {
+            SurfaceBuilderType::Default => self.build_default(random, column, biome, &biome.config, x, z, height, noise, default_block, default_fluid, sea_level),
+            SurfaceBuilderType::Badlands => self.build_badlands(random, column, biome, x, z, height, noise, default_block, sea_level),
+            SurfaceBuilderType::ErodedBadlands => self.build_eroded_badlands(random, column, biome, x, z, height, noise, default_block, sea_level),
+            SurfaceBuilderType::GiantTreeTaiga => {
+                let config = if noise > 1.75f64 {
+                    CONFIG_COARSE_DIRT
+                } else if noise > -0.95f64 {
+                    CONFIG_PODZOL
+                } else {
+                    CONFIG_GRASS
+                };
+                self.build_default(random, column, biome, &config, x, z, height, noise, default_block, default_fluid, sea_level)
+            }
+            SurfaceBuilderType::Swamp => {
+                if self.biome_noises.get_biome_info(x as f64 * 0.25f64, z as f64 * 0.25f64) > 0.0f64 {
+                    for y in (0..=height).rev() {
+                        let block = get_block(column, y);
+                        if block.is_air() {
+                            continue;
+                        }
+                        if y == 62 && block != default_fluid {
+                            set_block(column, y, default_fluid);
+                        }
+                        break;
+                    }
+                }
+                self.build_default(random, column, biome, &biome.config, x, z, height, noise, default_block, default_fluid, sea_level)
+            }
+            SurfaceBuilderType::FrozenOcean => self.build_frozen_ocean(random, column, biome, x, z, height, noise, default_block, default_fluid, sea_level),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_default(&self, random: &mut Random, column: &mut [Block], biome: &SurfaceBiome, config: &SurfaceBuilderConfig, x: i32, z: i32, height: i32, noise: f64, default_block: Block, default_fluid: Block, sea_level: i32) {
+        let mut top: Block = config.top_material;
+        let mut under: Block = config.under_material;
+        let depth: i32 = get_depth(random, noise);
+        let mut remaining: i32 = -1;
+        for y in (0..=height).rev() {
+            let block = get_block(column, y);
+            if block.is_air() {
+                remaining = -1;
+                continue;
+            }
+            if block != default_block {
+                continue;
+            }
+            if remaining == -1 {
+                if depth <= 0 {
+                    top = Block::Air;
+                    under = default_block;
+                } else if y >= sea_level - 4 && y <= sea_level + 1 {
+                    top = config.top_material;
+                    under = config.under_material;
+                }
+                if y < sea_level && top.is_air() {
+                    top = if self.get_temperature(biome, x, y, z) < 0.15f32 { Block::Ice } else { default_fluid };
+                }
+                remaining = depth;
+                if y >= sea_level - 1 {
+                    set_block(column, y, top);
+                } else if y < sea_level - 7 - depth {
+                    top = Block::Air;
+                    under = default_block;
+                    set_block(column, y, config.underwater_material);
+                } else {
+                    set_block(column, y, under);
+                }
+            } else if remaining > 0 {
+                remaining -= 1;
+                set_block(column, y, under);
+                // only plain sand turns into sandstone, the red sandstone branch of vanilla never applies
+                if remaining == 0 && under == Block::Sand && depth > 1 {
+                    remaining = random.next_int_n(4) + (y - 63).max(0);
+                    under = Block::Sandstone;
+                }
+            }
+        }
+    }
+
+    /// The block at the top of a badlands column: the bands away from the sea level, orange
+    /// terracotta out of the band range.
+    fn get_badlands_top(&self, x: i32, y: i32, z: i32, plain: bool) -> Block {
+        if !(64..=127).contains(&y) {
+            Block::OrangeTerracotta
+        } else if plain {
+            Block::Terracotta
+        } else {
+            self.get_band(x, y, z)
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_badlands(&self, random: &mut Random, column: &mut [Block], biome: &SurfaceBiome, x: i32, z: i32, height: i32, noise: f64, default_block: Block, sea_level: i32) {
+        let config = biome.config;
+        let mut under: Block = config.under_material;
+        let depth: i32 = get_depth(random, noise);
+        let plain: bool = cos_f64(noise / 3.0f64 * core::f64::consts::PI) > 0.0f64;
+        let mut remaining: i32 = -1;
+        let mut sandy: bool = false;
+        let mut processed: i32 = 0;
+        for y in (0..=height).rev() {
+            if processed >= 15 {
+                break;
+            }
+            let block = get_block(column, y);
+            if block.is_air() {
+                remaining = -1;
+                continue;
+            }
+            if block != default_block {
+                continue;
+            }
+            if remaining == -1 {
+                sandy = false;
+                if depth <= 0 {
+                    under = default_block;
+                } else if y >= sea_level - 4 && y <= sea_level + 1 {
+                    under = config.under_material;
+                }
+                remaining = depth + (y - sea_level).max(0);
+                if y >= sea_level - 1 {
+                    if y > sea_level + 3 + depth {
+                        set_block(column, y, self.get_badlands_top(x, y, z, plain));
+                    } else {
+                        set_block(column, y, config.top_material);
+                        sandy = true;
+                    }
+                } else {
+                    set_block(column, y, if is_colored_terracotta(under) { Block::OrangeTerracotta } else { under });
+                }
+            } else if remaining > 0 {
+                remaining -= 1;
+                set_block(column, y, if sandy { Block::OrangeTerracotta } else { self.get_band(x, y, z) });
+            }
+            processed += 1;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_eroded_badlands(&self, random: &mut Random, column: &mut [Block], biome: &SurfaceBiome, x: i32, z: i32, height: i32, noise: f64, default_block: Block, sea_level: i32) {
+        let mut pillar_height: f64 = 0.0f64;
+        let pillar: f64 = noise.abs().min(self.pillar_noise.get_value(x as f64 * 0.25f64, z as f64 * 0.25f64, false) * 15.0f64);
+        if pillar > 0.0f64 {
+            let roof: f64 = self.pillar_roof_noise.get_value(x as f64 * 0.001953125f64, z as f64 * 0.001953125f64, false).abs();
+            pillar_height = (pillar * pillar * 2.5f64).min(ceil(roof * 50.0f64) + 14.0f64) + 64.0f64;
+        }
+        let config = biome.config;
+        let mut under: Block = config.under_material;
+        let depth: i32 = get_depth(random, noise);
+        let plain: bool = cos_f64(noise / 3.0f64 * core::f64::consts::PI) > 0.0f64;
+        let mut remaining: i32 = -1;
+        let mut sandy: bool = false;
+        for y in (0..=height.max(pillar_height as i32 + 1)).rev() {
+            if get_block(column, y).is_air() && y < pillar_height as i32 {
+                set_block(column, y, default_block);
+            }
+            let block = get_block(column, y);
+            if block.is_air() {
+                remaining = -1;
+                continue;
+            }
+            if block != default_block {
+                continue;
+            }
+            if remaining == -1 {
+                sandy = false;
+                if depth <= 0 {
+                    under = default_block;
+                } else if y >= sea_level - 4 && y <= sea_level + 1 {
+                    under = config.under_material;
+                }
+                remaining = depth + (y - sea_level).max(0);
+                if y >= sea_level - 1 {
+                    if y > sea_level + 3 + depth {
+                        set_block(column, y, self.get_badlands_top(x, y, z, plain));
+                    } else {
+                        set_block(column, y, config.top_material);
+                        sandy = true;
+                    }
+                } else {
+                    set_block(column, y, if under == Block::WhiteTerracotta { Block::OrangeTerracotta } else { under });
+                }
+            } else if remaining > 0 {
+                remaining -= 1;
+                set_block(column, y, if sandy { Block::OrangeTerracotta } else { self.get_band(x, y, z) });
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_frozen_ocean(&self, random: &mut Random, column: &mut [Block], biome: &SurfaceBiome, x: i32, z: i32, height: i32, noise: f64, default_block: Block, default_fluid: Block, sea_level: i32) {
+        let mut iceberg_height: f64 = 0.0f64;
+        let mut iceberg_bottom: f64 = 0.0f64;
+        let temperature: f32 = self.get_temperature(biome, x, 63, z);
+        let iceberg: f64 = noise.abs().min(self.iceberg_noise.get_value(x as f64 * 0.1f64, z as f64 * 0.1f64, false) * 15.0f64);
+        if iceberg > 1.8f64 {
+            let roof: f64 = self.iceberg_roof_noise.get_value(x as f64 * 0.09765625f64, z as f64 * 0.09765625f64, false).abs();
+            iceberg_height = (iceberg * iceberg * 1.2f64).min(ceil(roof * 40.0f64) + 14.0f64);
+            if temperature > 0.1f32 {
+                iceberg_height -= 2.0f64;
+            }
+            if iceberg_height > 2.0f64 {
+                iceberg_bottom = sea_level as f64 - iceberg_height - 7.0f64;
+                iceberg_height += sea_level as f64;
+            } else {
+                iceberg_height = 0.0f64;
+            }
+        }
+        let config = biome.config;
+        let mut top: Block = config.top_material;
+        let mut under: Block = config.under_material;
+        let depth: i32 = get_depth(random, noise);
+        let mut remaining: i32 = -1;
+        let mut snow_blocks: i32 = 0;
+        let max_snow_blocks: i32 = 2 + random.next_int_n(4);
+        let snow_line: i32 = sea_level + 18 + random.next_int_n(10);
+        for y in (0..=height.max(iceberg_height as i32 + 1)).rev() {
+            let block = get_block(column, y);
+            // the air of the iceberg above the sea, its water below
+            let packed_ice: bool = if block.is_air() && y < iceberg_height as i32 {
+                random.next_double() > 0.01f64
+            } else {
+                block == Block::Water && y > iceberg_bottom as i32 && y < sea_level && iceberg_bottom != 0.0f64 && random.next_double() > 0.15f64
+            };
+            if packed_ice {
+                set_block(column, y, Block::PackedIce);
+            }
+            let block = get_block(column, y);
+            if block.is_air() {
+                remaining = -1;
+                continue;
+            }
+            if block == default_block {
+                if remaining == -1 {
+                    if depth <= 0 {
+                        top = Block::Air;
+                        under = default_block;
+                    } else if y >= sea_level - 4 && y <= sea_level + 1 {
+                        top = config.top_material;
+                        under = config.under_material;
+                    }
+                    if y < sea_level && top.is_air() {
+                        top = if self.get_temperature(biome, x, y, z) < 0.15f32 { Block::Ice } else { default_fluid };
+                    }
+                    remaining = depth;
+                    if y >= sea_level - 1 {
+                        set_block(column, y, top);
+                    } else if y < sea_level - 7 - depth {
+                        top = Block::Air;
+                        under = default_block;
+                        set_block(column, y, Block::Gravel);
+                    } else {
+                        set_block(column, y, under);
+                    }
+                } else if remaining > 0 {
+                    remaining -= 1;
+                    set_block(column, y, under);
+                    if remaining == 0 && under == Block::Sand && depth > 1 {
+                        remaining = random.next_int_n(4) + (y - 63).max(0);
+                        under = Block::Sandstone;
+                    }
+                }
+            } else if block == Block::PackedIce && snow_blocks <= max_snow_blocks && y > snow_line {
+                set_block(column, y, Block::SnowBlock);
+                snow_blocks += 1;
+            }
+        }
+    }
+}
+
+fn is_colored_terracotta(block: Block) -> bool {
+    matches!(block, Block::WhiteTerracotta | Block::OrangeTerracotta | Block::YellowTerracotta
+        | Block::BrownTerracotta | Block::RedTerracotta | Block::LightGrayTerracotta)
+}
@@ -0,0 +1,549 @@
+//! The 1.18+ surface rules, a tree of conditions choosing the block replacing each default block.
+//!
+//! Columns are slices of blocks indexed from the minimum build height. [`SurfaceSystem::build_column`]
+//! walks a column from its top the way vanilla `SurfaceSystem.buildSurface` does, tracking the
+//! stone depths and the water height the conditions test. The eroded badlands pillars and the
+//! frozen ocean icebergs vanilla adds around the rules are not built.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::block::Block;
+use crate::double_perlin_noise::{DoublePerlinNoise, NoiseParameters};
+use crate::math::lerp1;
+use crate::random_source::{PositionalRandomFactory, RandomSource, WorldgenRandom};
+use crate::surface_builder::{round, BiomeNoises, TemperatureModifier};
+
+/// A y resolved against the build height range of the dimension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerticalAnchor {
+    Absolute(i32),
+    AboveBottom(i32),
+    BelowTop(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaveSurface {
+    /// Depths counted down from the air or fluid above.
+    Floor,
+    /// Depths counted up from the air or fluid below.
+    Ceiling,
+}
+
+#[derive(Clone, Debug)]
+pub enum SurfaceCondition {
+    /// Biome names with their namespace.
+    Biome(Vec<String>),
+    NoiseThreshold { noise: Arc<DoublePerlinNoise>, min_threshold: f64, max_threshold: f64 },
+    VerticalGradient { random_factory: PositionalRandomFactory, true_at_and_below: VerticalAnchor, false_at_and_above: VerticalAnchor },
+    YAbove { anchor: VerticalAnchor, surface_depth_multiplier: i32, add_stone_depth: bool },
+    Water { offset: i32, surface_depth_multiplier: i32, add_stone_depth: bool },
+    StoneDepth { offset: i32, add_surface_depth: bool, secondary_depth_range: i32, surface_type: CaveSurface },
+    /// Cold enough to snow.
+    Temperature,
+    Steep,
+    Hole,
+    AbovePreliminarySurface,
+    Not(Box<SurfaceCondition>),
+}
+
+#[derive(Clone, Debug)]
+pub enum SurfaceRule {
+    Block(Block),
+    /// The badlands terracotta band at the block.
+    Bandlands,
+    /// The first rule giving a block.
+    Sequence(Vec<SurfaceRule>),
+    Condition(SurfaceCondition, Box<SurfaceRule>),
+}
+
+/// What the surface rules need from a biome.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleBiome {
+    pub name: String,
+    pub temperature: f32,
+    pub temperature_modifier: TemperatureModifier,
+}
+
+/// Provides the biome of the surface rules at block coordinates.
+pub trait RuleBiomeSource {
+    fn get_rule_biome(&mut self, x: i32, y: i32, z: i32) -> RuleBiome;
+}
+
+impl<F: FnMut(i32, i32, i32) -> RuleBiome> RuleBiomeSource for F {
+    fn get_rule_biome(&mut self, x: i32, y: i32, z: i32) -> RuleBiome {
+        self(x, y, z)
+    }
+}
+
+/// The state of the column walk at a block, vanilla `SurfaceRules.Context`.
+#[derive(Clone, Debug)]
+pub struct SurfaceContext {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub biome: RuleBiome,
+    pub surface_depth: i32,
+    pub surface_secondary: f64,
+    pub min_surface_level: i32,
+    pub steep: bool,
+    /// Blocks from the air or fluid above, this one included.
+    pub stone_depth_above: i32,
+    /// Blocks to the air or fluid below, this one included.
+    pub stone_depth_below: i32,
+    /// One above the top of the fluid over the block, `i32::MIN` if there is air in between.
+    pub water_height: i32,
+}
+
+/// The seed dependent noises and randoms of the surface rules in a dimension.
+#[derive(Clone, Debug)]
+pub struct SurfaceSystem {
+    random_factory: PositionalRandomFactory,
+    default_block: Block,
+    min_y: i32,
+    height: i32,
+    surface_noise: DoublePerlinNoise,
+    surface_secondary_noise: DoublePerlinNoise,
+    clay_bands_offset_noise: DoublePerlinNoise,
+    clay_bands: [Block; 192],
+    biome_noises: BiomeNoises,
+}
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod surface_rules_test {
+    use super::*;
+
+    fn plains() -> RuleBiome {
+        RuleBiome::new("plains", 0.8f32)
+    }
+
+    /// Stone up to `top`, water up to the sea level 63, from y -64.
+    fn column(top: i32) -> Vec<Block> {
+        (-64..320).map(|y| if y <= top { Block::Stone } else if y < 63 { Block::Water } else { Block::Air }).collect()
+    }
+
+    fn grass_rule() -> SurfaceRule {
+        let on_floor = SurfaceCondition::stone_depth(0, false, 0, CaveSurface::Floor);
+        let under_floor = SurfaceCondition::stone_depth(0, true, 0, CaveSurface::Floor);
+        SurfaceRule::Sequence(vec![
+            SurfaceRule::condition(on_floor, SurfaceRule::Sequence(vec![
+                SurfaceRule::condition(SurfaceCondition::water(0, 0), SurfaceRule::Block(Block::GrassBlock)),
+                SurfaceRule::Block(Block::Gravel),
+            ])),
+            SurfaceRule::condition(under_floor, SurfaceRule::Block(Block::Dirt)),
+        ])
+    }
+
+    #[test]
+    fn test_clay_bands() {
+        let system = SurfaceSystem::new(1, false, Block::Stone, -64, 384);
+        let bands = system.get_clay_bands();
+        assert_eq!(bands[..8], [Block::WhiteTerracotta, Block::LightGrayTerracotta, Block::OrangeTerracotta, Block::BrownTerracotta, Block::BrownTerracotta, Block::BrownTerracotta, Block::BrownTerracotta, Block::Terracotta]);
+        assert_eq!(bands.iter().filter(|&&band| band == Block::WhiteTerracotta).count(), 11);
+        assert_eq!(system.get_band(0, 64, 0), Block::Terracotta);
+        assert_eq!(system.get_surface_depth(0, 0), 3);
+        assert_eq!(system.get_surface_depth(100, 20), 2);
+    }
+
+    #[test]
+    fn test_grass_and_dirt() {
+        let system = SurfaceSystem::new(1, false, Block::Stone, -64, 384);
+        let depth = system.get_surface_depth(3, 5);
+        let mut blocks = column(70);
+        system.build_column(&grass_rule(), &mut blocks, 3, 5, false, 64, &mut |_, _, _| plains());
+        assert_eq!(blocks[(70 + 64) as usize], Block::GrassBlock);
+        assert_eq!(blocks[(70 + 64 - depth) as usize], Block::Dirt);
+        assert_eq!(blocks[(70 + 64 - depth - 1) as usize], Block::Stone);
+        let mut blocks = column(50);
+        system.build_column(&grass_rule(), &mut blocks, 3, 5, false, 64, &mut |_, _, _| plains());
+        assert_eq!(blocks[(50 + 64) as usize], Block::Gravel);
+        assert_eq!(blocks[(63 + 64) as usize], Block::Air);
+        assert_eq!(blocks[(62 + 64) as usize], Block::Water);
+    }
+
+    #[test]
+    fn test_vertical_gradient() {
+        let system = SurfaceSystem::new(1, false, Block::Stone, -64, 384);
+        let bedrock = SurfaceRule::condition(
+            SurfaceCondition::vertical_gradient(system.get_random_factory("bedrock_floor"), VerticalAnchor::AboveBottom(0), VerticalAnchor::AboveBottom(5)),
+            SurfaceRule::Block(Block::Bedrock),
+        );
+        let mut blocks = column(70);
+        system.build_column(&bedrock, &mut blocks, 0, 0, false, 64, &mut |_, _, _| plains());
+        assert_eq!(blocks[0], Block::Bedrock);
+        assert_eq!(blocks[5..].iter().filter(|&&block| block == Block::Bedrock).count(), 0);
+        assert_eq!(blocks[1..5], [Block::Bedrock, Block::Stone, Block::Stone, Block::Stone]);
+    }
+
+    #[test]
+    fn test_biome_and_bandlands() {
+        let system = SurfaceSystem::new(1, false, Block::Stone, -64, 384);
+        let rule = SurfaceRule::Sequence(vec![
+            SurfaceRule::condition(SurfaceCondition::biome(&["minecraft:badlands"]), SurfaceRule::Bandlands),
+            SurfaceRule::condition(SurfaceCondition::YAbove { anchor: VerticalAnchor::Absolute(60), surface_depth_multiplier: 0, add_stone_depth: false }, SurfaceRule::Block(Block::Calcite)),
+        ]);
+        let mut blocks = column(70);
+        system.build_column(&rule, &mut blocks, 0, 0, false, 64, &mut |_, y, _| if y > 65 { RuleBiome::new("badlands", 2.0f32) } else { plains() });
+        for y in 66..=70 {
+            assert_eq!(blocks[(y + 64) as usize], system.get_band(0, y, 0));
+        }
+        assert_eq!(blocks[(60 + 64) as usize], Block::Calcite);
+        assert_eq!(blocks[(59 + 64) as usize], Block::Stone);
+    }
+
+    #[test]
+    fn test_steep_and_noise() {
+        let heights = |x: i32, z: i32| if z == 8 { 80 } else { 64 + x };
+        assert!(is_steep(heights, 3, 7));
+        assert!(!is_steep(heights, 3, 3));
+        let system = SurfaceSystem::new(1, false, Block::Stone, -64, 384);
+        let noise = system.get_noise("minecraft:surface").unwrap();
+        assert!(system.get_noise("minecraft:unknown").is_none());
+        let context = system.get_context(0, 70, 0, RuleBiome::new("plains", 0.8f32), false, 64);
+        assert_eq!(context.surface_depth, system.get_surface_depth(0, 0));
+        let value = noise.sample(0.0f64, 0.0f64, 0.0f64);
+        assert!(SurfaceCondition::noise_threshold(noise.clone(), value, value).test(&system, &context));
+        assert!(!SurfaceCondition::noise_threshold(noise, value + 0.01f64, 1.0f64).test(&system, &context));
+    }
+}
+
+impl VerticalAnchor {
+    pub fn resolve_y(&self, min_y: i32, height: i32) -> i32 {
+        match *self {
+            VerticalAnchor::Absolute(y) => y,
+            VerticalAnchor::AboveBottom(offset) => min_y + offset,
+            VerticalAnchor::BelowTop(offset) => min_y + height - 1 - offset,
+        }
+    }
+}
+
+fn with_namespace(name: &str) -> String {
+    if name.contains(':') { name.to_string() } else { format!("minecraft:{}", name) }
+}
+
+impl SurfaceCondition {
+    pub fn biome(names: &[&str]) -> Self {
+        SurfaceCondition::Biome(names.iter().map(|name| with_namespace(name)).collect())
+    }
+
+    pub fn noise_threshold(noise: Arc<DoublePerlinNoise>, min_threshold: f64, max_threshold: f64) -> Self {
+        SurfaceCondition::NoiseThreshold { noise, min_threshold, max_threshold }
+    }
+
+    pub fn vertical_gradient(random_factory: PositionalRandomFactory, true_at_and_below: VerticalAnchor, false_at_and_above: VerticalAnchor) -> Self {
+        SurfaceCondition::VerticalGradient { random_factory, true_at_and_below, false_at_and_above }
+    }
+
+    pub fn water(offset: i32, surface_depth_multiplier: i32) -> Self {
+        SurfaceCondition::Water { offset, surface_depth_multiplier, add_stone_depth: false }
+    }
+
+    pub fn stone_depth(offset: i32, add_surface_depth: bool, secondary_depth_range: i32, surface_type: CaveSurface) -> Self {
+        SurfaceCondition::StoneDepth { offset, add_surface_depth, secondary_depth_range, surface_type }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        SurfaceCondition::Not(Box::new(self))
+    }
+
+    pub fn test(&self, system: &SurfaceSystem, context: &SurfaceContext) -> bool {
+        match self {
+            SurfaceCondition::Biome(names) => names.contains(&context.biome.name),
+            SurfaceCondition::NoiseThreshold { noise, min_threshold, max_threshold } => {
+                let value = noise.sample(context.x as f64, 0.0f64, context.z as f64);
+                value >= *min_threshold && value <= *max_threshold
+            }
+            SurfaceCondition::VerticalGradient { random_factory, true_at_and_below, false_at_and_above } => {
+                let true_y = true_at_and_below.resolve_y(system.min_y, system.height);
+                let false_y = false_at_and_above.resolve_y(system.min_y, system.height);
+                if context.y <= true_y {
+                    return true;
+                }
+                if context.y >= false_y {
+                    return false;
+                }
+                let chance = lerp1((context.y - true_y) as f64 / (false_y - true_y) as f64, 1.0f64, 0.0f64);
+                (random_factory.at(context.x, context.y, context.z).next_float() as f64) < chance
+            }
+            SurfaceCondition::YAbove { anchor, surface_depth_multiplier, add_stone_depth } => {
+                let y = context.y + if *add_stone_depth { context.stone_depth_above } else { 0 };
+                y >= anchor.resolve_y(system.min_y, system.height) + context.surface_depth * surface_depth_multiplier
+            }
+            SurfaceCondition::Water { offset, surface_depth_multiplier, add_stone_depth } => {
+                if context.water_height == i32::MIN {
+                    return true;
+                }
+                let y = context.y + if *add_stone_depth { context.stone_depth_above } else { 0 };
+                y >= context.water_height + offset + context.surface_depth * surface_depth_multiplier
+            }
+            SurfaceCondition::StoneDepth { offset, add_surface_depth, secondary_depth_range, surface_type } => {
+                let depth = match surface_type {
+                    CaveSurface::Floor => context.stone_depth_above,
+                    CaveSurface::Ceiling => context.stone_depth_below,
+                };
+                let surface_depth = if *add_surface_depth { context.surface_depth } else { 0 };
+                let secondary_depth = if *secondary_depth_range == 0 {
+                    0
+                } else {
+                    lerp1((context.surface_secondary + 1.0f64) / 2.0f64, 0.0f64, *secondary_depth_range as f64) as i32
+                };
+                depth <= 1 + offset + surface_depth + secondary_depth
+            }
+            SurfaceCondition::Temperature => system.get_temperature(&context.biome, context.x, context.y, context.z) < 0.15f32,
+            SurfaceCondition::Steep => context.steep,
+            SurfaceCondition::Hole => context.surface_depth <= 0,
+            SurfaceCondition::AbovePreliminarySurface => context.y >= context.min_surface_level,
+            SurfaceCondition::Not(condition) => !condition.test(system, context),
+        }
+    }
+}
+
+impl SurfaceRule {
+    pub fn condition(condition: SurfaceCondition, rule: SurfaceRule) -> Self {
+        SurfaceRule::Condition(condition, Box::new(rule))
+    }
+
+    pub fn try_apply(&self, system: &SurfaceSystem, context: &SurfaceContext) -> Option<Block> {
+        match self {
+            SurfaceRule::Block(block) => Some(*block),
+            SurfaceRule::Bandlands => Some(system.get_band(context.x, context.y, context.z)),
+            SurfaceRule::Sequence(rules) => rules.iter().find_map(|rule| rule.try_apply(system, context)),
+            SurfaceRule::Condition(condition, rule) => {
+                if condition.test(system, context) {
+                    rule.try_apply(system, context)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl RuleBiome {
+    pub fn new(name: &str, temperature: f32) -> Self {
+        RuleBiome { name: with_namespace(name), temperature, temperature_modifier: TemperatureModifier::None }
+    }
+
+    pub fn with_temperature_modifier(self, temperature_modifier: TemperatureModifier) -> Self {
+        RuleBiome { temperature_modifier, ..self }
+    }
+}
+
+/// Vanilla `SurfaceRules.Steep`, whether the heights two blocks apart around chunk relative `x`,
+/// `z` differ by 4 or more, southward or westward.
+pub fn is_steep<F: Fn(i32, i32) -> i32>(get_height: F, x: i32, z: i32) -> bool {
+    let north = get_height(x, (z - 1).max(0));
+    let south = get_height(x, (z + 1).min(15));
+    if south >= north + 4 {
+        return true;
+    }
+    let west = get_height((x - 1).max(0), z);
+    let east = get_height((x + 1).min(15), z);
+    west >= east + 4
+}
+
+/// The vanilla parameters of the noises surface rules refer to.
+fn get_noise_parameters(name: &str) -> Option<NoiseParameters> {
+    let octaves = |count: usize| vec![1.0f64; count];
+    let (first_octave, amplitudes) = match name.strip_prefix("minecraft:").unwrap_or(name) {
+        "surface" => (-6, octaves(3)),
+        "surface_secondary" => (-6, vec![1.0f64, 1.0f64, 0.0f64, 1.0f64]),
+        "clay_bands_offset" => (-8, octaves(1)),
+        "badlands_pillar" => (-2, octaves(4)),
+        "badlands_pillar_roof" => (-8, octaves(1)),
+        "badlands_surface" => (-6, octaves(3)),
+        "iceberg_pillar" => (-6, octaves(4)),
+        "iceberg_pillar_roof" => (-3, octaves(1)),
+        "iceberg_surface" => (-6, octaves(3)),
+        "surface_swamp" => (-2, octaves(1)),
+        "calcite" => (-9, octaves(4)),
+        "gravel" => (-8, octaves(4)),
+        "powder_snow" => (-6, octaves(4)),
+        "packed_ice" => (-7, octaves(4)),
+        "ice" => (-4, octaves(4)),
+        "soul_sand_layer" | "gravel_layer" => (-8, vec![1.0f64, 1.0f64, 1.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.013333333333333334f64]),
+        "patch" => (-5, vec![1.0f64, 0.0f64, 0.0f64, 0.0f64, 0.0f64, 0.013333333333333334f64]),
+        "netherrack" => (-3, vec![1.0f64, 0.0f64, 0.0f64, 0.35f64]),
+        "nether_wart" => (-3, vec![1.0f64, 0.0f64, 0.0f64, 0.9f64]),
+        "nether_state_selector" => (-4, octaves(1)),
+        _ => return None,
+    };
+    Some(NoiseParameters::new(first_octave, amplitudes))
+}
+
+impl SurfaceSystem {
+    /// The surface system of a dimension, `legacy_random_source` being the flag of its noise
+    /// settings and `min_y`, `height` its build height range.
+    pub fn new(seed: i64, legacy_random_source: bool, default_block: Block, min_y: i32, height: i32) -> Self {
+        let random_factory = WorldgenRandom::new(seed, legacy_random_source).fork_positional();
+        let noise = |name: &str| DoublePerlinNoise::instantiate(&random_factory, name, &get_noise_parameters(name).unwrap());
+        SurfaceSystem {
+            random_factory,
+            default_block,
+            min_y,
+            height,
+            surface_noise: noise("minecraft:surface"),
+            surface_secondary_noise: noise("minecraft:surface_secondary"),
+            clay_bands_offset_noise: noise("minecraft:clay_bands_offset"),
+            clay_bands: SurfaceSystem::generate_bands(&mut random_factory.from_hash_of("minecraft:clay_bands")),
+            biome_noises: BiomeNoises::new(),
+        }
+    }
+
+    fn generate_bands(random: &mut WorldgenRandom) -> [Block; 192] {
+        let mut clay_bands = [Block::Terracotta; 192];
+        let length = clay_bands.len() as i32;
+        let mut i: i32 = 0;
+        while i < length {
+            i += random.next_int_n(5) + 1;
+            if i >= length {
+                break;
+            }
+            clay_bands[i as usize] = Block::OrangeTerracotta;
+            i += 1;
+        }
+        for (block, min_size) in [(Block::YellowTerracotta, 1), (Block::BrownTerracotta, 2), (Block::RedTerracotta, 1)] {
+            let count = random.next_int_n(10) + 6;
+            for _ in 0..count {
+                let size = random.next_int_n(3) + min_size;
+                let start = random.next_int_n(length);
+                for j in 0..size {
+                    if start + j >= length {
+                        break;
+                    }
+                    clay_bands[(start + j) as usize] = block;
+                }
+            }
+        }
+        let count = random.next_int_n(7) + 9;
+        let mut position: i32 = 0;
+        let mut placed = 0;
+        while placed < count && position < length {
+            clay_bands[position as usize] = Block::WhiteTerracotta;
+            if position - 1 > 0 && random.next_boolean() {
+                clay_bands[(position - 1) as usize] = Block::LightGrayTerracotta;
+            }
+            if position + 1 < length && random.next_boolean() {
+                clay_bands[(position + 1) as usize] = Block::LightGrayTerracotta;
+            }
+            placed += 1;
+            position += random.next_int_n(16) + 4;
+        }
+        clay_bands
+    }
+
+    /// The noise registered as `name` in vanilla, for `noise_threshold` conditions.
+    pub fn get_noise(&self, name: &str) -> Option<Arc<DoublePerlinNoise>> {
+        let parameters = get_noise_parameters(name)?;
+        Some(Arc::new(DoublePerlinNoise::instantiate(&self.random_factory, &with_namespace(name), &parameters)))
+    }
+
+    /// The random factory of a `vertical_gradient` condition named `name`.
+    pub fn get_random_factory(&self, name: &str) -> PositionalRandomFactory {
+        self.random_factory.from_hash_of(&with_namespace(name)).fork_positional()
+    }
+
+    pub fn get_clay_bands(&self) -> &[Block; 192] {
+        &self.clay_bands
+    }
+
+    /// The badlands band at a position, offset by up to 4 blocks along a low frequency noise.
+    pub fn get_band(&self, x: i32, y: i32, z: i32) -> Block {
+        let offset: i32 = round(self.clay_bands_offset_noise.sample(x as f64, 0.0f64, z as f64) * 4.0f64);
+        self.clay_bands[(y + offset + 192).rem_euclid(192) as usize]
+    }
+
+    pub fn get_surface_depth(&self, x: i32, z: i32) -> i32 {
+        let noise = self.surface_noise.sample(x as f64, 0.0f64, z as f64);
+        (noise * 2.75f64 + 3.0f64 + self.random_factory.at(x, 0, z).next_double() * 0.25f64) as i32
+    }
+
+    pub fn get_surface_secondary(&self, x: i32, z: i32) -> f64 {
+        self.surface_secondary_noise.sample(x as f64, 0.0f64, z as f64)
+    }
+
+    /// Vanilla 1.18 `Biome.getTemperature`, cooling down above y 80.
+    pub fn get_temperature(&self, biome: &RuleBiome, x: i32, y: i32, z: i32) -> f32 {
+        let temperature: f32 = self.biome_noises.modify_temperature(biome.temperature, biome.temperature_modifier, x, z);
+        if y > 80 {
+            let noise: f32 = (self.biome_noises.get_temperature_noise(x, z) * 8.0f64) as f32;
+            temperature - (noise + y as f32 - 80.0f32) * 0.05f32 / 40.0f32
+        } else {
+            temperature
+        }
+    }
+
+    /// The context of a block on top of stone, before the column walk sets its depths and water
+    /// height.
+    pub fn get_context(&self, x: i32, y: i32, z: i32, biome: RuleBiome, steep: bool, preliminary_surface_level: i32) -> SurfaceContext {
+        let surface_depth = self.get_surface_depth(x, z);
+        SurfaceContext {
+            x,
+            y,
+            z,
+            biome,
+            surface_depth,
+            surface_secondary: self.get_surface_secondary(x, z),
+            min_surface_level: preliminary_surface_level + surface_depth - 8,
+            steep,
+            stone_depth_above: 1,
+            stone_depth_below: 1,
+            water_height: i32::MIN,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Applies `rule` to the default blocks of the column at `x`, `z`, `preliminary_surface_level`
+    /// being the interpolated preliminary surface of the noise chunk at the column.
+    pub fn build_column<B: RuleBiomeSource>(&self, rule: &SurfaceRule, column: &mut [Block], x: i32, z: i32, steep: bool, preliminary_surface_level: i32, biomes: &mut B) {
+        let is_stone = |block: Block| !block.is_air() && !block.is_fluid();
+        let top = column.iter().rposition(|block| !block.is_air()).map_or(0, |i| i + 1);
+        let mut context: Option<SurfaceContext> = None;
+        let mut stone_depth_above: i32 = 0;
+        let mut water_height: i32 = i32::MIN;
+        let mut stone_below: i32 = i32::MAX;
+        for i in (0..top).rev() {
+            let y = self.min_y + i as i32;
+            let block = column[i];
+            if block.is_air() {
+                stone_depth_above = 0;
+                water_height = i32::MIN;
+                continue;
+            }
+            if block.is_fluid() {
+                if water_height == i32::MIN {
+                    water_height = y + 1;
+                }
+                continue;
+            }
+            if stone_below >= y {
+                stone_below = column[..i].iter().rposition(|&below| !is_stone(below)).map_or(self.min_y, |j| self.min_y + j as i32 + 1);
+            }
+            stone_depth_above += 1;
+            if block != self.default_block {
+                continue;
+            }
+            let biome = biomes.get_rule_biome(x, y, z);
+            let context = match context.as_mut() {
+                Some(context) => {
+                    context.y = y;
+                    context.biome = biome;
+                    context
+                }
+                None => context.insert(self.get_context(x, y, z, biome, steep, preliminary_surface_level)),
+            };
+            context.stone_depth_above = stone_depth_above;
+            context.stone_depth_below = y - stone_below + 1;
+            context.water_height = water_height;
+            if let Some(replacement) = rule.try_apply(self, context) {
+                column[i] = replacement;
+            }
+        }
+    }
+}
@@ -0,0 +1,513 @@
+use std::sync::Arc;
+
+use crate::density_function::DensityFunction;
+use crate::double_perlin_noise::{DoublePerlinNoise, NoiseParameters};
+use crate::random_source::PositionalRandomFactory;
+
+/// The climate value a spline is keyed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplineCoordinate {
+    Continents,
+    Erosion,
+    Weirdness,
+    /// Peaks and valleys, the folded weirdness.
+    Ridges,
+}
+
+/// The climate values at a column, with the peaks and valleys derived from the weirdness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TerrainPoint {
+    pub continents: f32,
+    pub erosion: f32,
+    pub ridges: f32,
+    pub weirdness: f32,
+}
+
+impl TerrainPoint {
+    pub fn new(continents: f64, erosion: f64, weirdness: f64) -> Self {
+        TerrainPoint {
+            continents: continents as f32,
+            erosion: erosion as f32,
+            ridges: peaks_and_valleys(weirdness as f32),
+            weirdness: weirdness as f32,
+        }
+    }
+
+    pub fn get(&self, coordinate: SplineCoordinate) -> f32 {
+        match coordinate {
+            SplineCoordinate::Continents => self.continents,
+            SplineCoordinate::Erosion => self.erosion,
+            SplineCoordinate::Weirdness => self.weirdness,
+            SplineCoordinate::Ridges => self.ridges,
+        }
+    }
+}
+
+/// Folds the weirdness into the peaks and valleys value.
+pub fn peaks_and_valleys(weirdness: f32) -> f32 {
+    -((weirdness.abs() - 0.6666667f32).abs() - 0.33333334f32) * 3.0f32
+}
+
+/// A vanilla `CubicSpline`: hermite interpolation between points whose values may be splines themselves.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CubicSpline {
+    Constant(f32),
+    Multipoint {
+        coordinate: SplineCoordinate,
+        locations: Vec<f32>,
+        values: Vec<CubicSpline>,
+        derivatives: Vec<f32>,
+    },
+}
+
+#[cfg(test)]
+mod terrain_shaper_test {
+    use super::*;
+    use java_random::Random;
+
+    #[test]
+    fn test_spline() {
+        let spline = CubicSplineBuilder::new(SplineCoordinate::Continents)
+            .add_point(0.0, 0.0, 1.0)
+            .add_point(1.0, 1.0, 1.0)
+            .build();
+        let point = |continents: f64| TerrainPoint::new(continents, 0.0, 0.0);
+        assert_eq!(spline.apply(&point(0.5)), 0.5);
+        assert_eq!(spline.apply(&point(-2.0)), -2.0);
+        assert_eq!(spline.apply(&point(3.0)), 3.0);
+        let curved = CubicSplineBuilder::new(SplineCoordinate::Continents)
+            .add_point(0.0, 0.0, 0.0)
+            .add_spline_point(1.0, CubicSplineBuilder::new(SplineCoordinate::Erosion).add_point(0.0, 1.0, 0.0).build(), 0.0)
+            .build();
+        assert_eq!(curved.apply(&point(0.5)), 0.5);
+        assert_eq!(curved.apply(&point(0.25)), 0.15625);
+    }
+
+    #[test]
+    fn test_overworld_shaper() {
+        let shaper = TerrainShaper::overworld(false);
+        let ocean = TerrainPoint::new(-1.1, 0.0, 0.0);
+        assert_eq!(shaper.offset(&ocean), 0.044f32 + -0.50375f32);
+        assert_eq!(shaper.factor(&TerrainPoint::new(-0.5, 0.0, 0.0)), 3.95f32);
+        assert_eq!(shaper.jaggedness(&TerrainPoint::new(-0.5, 0.0, 0.0)), 0.0f32);
+        let peak = TerrainPoint::new(0.8, -1.0, -0.66);
+        assert!(shaper.jaggedness(&peak) > 0.0f32);
+        let amplified = TerrainShaper::overworld(true);
+        assert_eq!(amplified.jaggedness(&peak), shaper.jaggedness(&peak) * 2.0f32);
+    }
+
+    #[test]
+    fn test_climate_sampler() {
+        let factory = PositionalRandomFactory::Legacy(Random::with_seed(1).next_long());
+        let climate = ClimateSampler::overworld(&factory);
+        let point = climate.get_point(17, 0, 33);
+        assert_eq!(point, climate.get_point(16, 100, 32));
+        assert!(point.continents.abs() < 2.0f32 && point.erosion.abs() < 2.0f32 && point.weirdness.abs() < 2.0f32);
+    }
+}
+
+impl CubicSpline {
+    pub fn constant(value: f32) -> Self {
+        CubicSpline::Constant(value)
+    }
+
+    pub fn apply(&self, point: &TerrainPoint) -> f32 {
+        match self {
+            CubicSpline::Constant(value) => *value,
+            CubicSpline::Multipoint { coordinate, locations, values, derivatives } => {
+                let location: f32 = point.get(*coordinate);
+                let last = locations.len() - 1;
+                let index = locations.partition_point(|&l| l <= location);
+                if index == 0 {
+                    return values[0].apply(point) + derivatives[0] * (location - locations[0]);
+                }
+                let index = index - 1;
+                if index == last {
+                    return values[last].apply(point) + derivatives[last] * (location - locations[last]);
+                }
+                let location0: f32 = locations[index];
+                let location1: f32 = locations[index + 1];
+                let t: f32 = (location - location0) / (location1 - location0);
+                let value0: f32 = values[index].apply(point);
+                let value1: f32 = values[index + 1].apply(point);
+                let slope0: f32 = derivatives[index] * (location1 - location0) - (value1 - value0);
+                let slope1: f32 = -derivatives[index + 1] * (location1 - location0) + (value1 - value0);
+                lerp(t, value0, value1) + t * (1.0f32 - t) * lerp(t, slope0, slope1)
+            }
+        }
+    }
+}
+
+fn lerp(delta: f32, start: f32, end: f32) -> f32 {
+    start + delta * (end - start)
+}
+
+fn no_transform(value: f32) -> f32 {
+    value
+}
+
+/// Builds a multipoint spline, constant point values go through the builder's transform.
+#[derive(Clone, Debug)]
+pub struct CubicSplineBuilder {
+    coordinate: SplineCoordinate,
+    transform: fn(f32) -> f32,
+    locations: Vec<f32>,
+    values: Vec<CubicSpline>,
+    derivatives: Vec<f32>,
+}
+
+impl CubicSplineBuilder {
+    pub fn new(coordinate: SplineCoordinate) -> Self {
+        Self::with_transform(coordinate, no_transform)
+    }
+
+    pub fn with_transform(coordinate: SplineCoordinate, transform: fn(f32) -> f32) -> Self {
+        CubicSplineBuilder { coordinate, transform, locations: vec![], values: vec![], derivatives: vec![] }
+    }
+
+    pub fn add_point(self, location: f32, value: f32, derivative: f32) -> Self {
+        let value = (self.transform)(value);
+        self.add_spline_point(location, CubicSpline::Constant(value), derivative)
+    }
+
+    pub fn add_spline_point(mut self, location: f32, value: CubicSpline, derivative: f32) -> Self {
+        if let Some(&previous) = self.locations.last() {
+            assert!(location > previous, "Please register points in ascending order");
+        }
+        self.locations.push(location);
+        self.values.push(value);
+        self.derivatives.push(derivative);
+        self
+    }
+
+    pub fn build(self) -> CubicSpline {
+        assert!(!self.locations.is_empty(), "A spline needs at least one point");
+        CubicSpline::Multipoint { coordinate: self.coordinate, locations: self.locations, values: self.values, derivatives: self.derivatives }
+    }
+}
+
+/// The 1.18 terrain shaper: offset, factor and jaggedness splines of the overworld.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerrainShaper {
+    offset_sampler: Arc<CubicSpline>,
+    factor_sampler: Arc<CubicSpline>,
+    jaggedness_sampler: Arc<CubicSpline>,
+}
+
+const GLOBAL_OFFSET: f32 = -0.50375f32;
+
+impl TerrainShaper {
+    pub fn new(offset_sampler: CubicSpline, factor_sampler: CubicSpline, jaggedness_sampler: CubicSpline) -> Self {
+        TerrainShaper {
+            offset_sampler: Arc::new(offset_sampler),
+            factor_sampler: Arc::new(factor_sampler),
+            jaggedness_sampler: Arc::new(jaggedness_sampler),
+        }
+    }
+
+    /// The shaper of the nether, the end and the other flat presets.
+    pub fn flat() -> Self {
+        Self::new(CubicSpline::constant(0.0f32), CubicSpline::constant(0.0f32), CubicSpline::constant(0.0f32))
+    }
+
+    pub fn overworld(amplified: bool) -> Self {
+        let offset_transform: fn(f32) -> f32 = if amplified { amplified_offset } else { no_transform };
+        let factor_transform: fn(f32) -> f32 = if amplified { amplified_factor } else { no_transform };
+        let jaggedness_transform: fn(f32) -> f32 = if amplified { amplified_jaggedness } else { no_transform };
+        let erosion_offset1 = build_erosion_offset_spline(-0.15f32, 0.0f32, 0.0f32, 0.1f32, 0.0f32, -0.03f32, false, false, offset_transform);
+        let erosion_offset2 = build_erosion_offset_spline(-0.1f32, 0.03f32, 0.1f32, 0.1f32, 0.01f32, -0.03f32, false, false, offset_transform);
+        let erosion_offset3 = build_erosion_offset_spline(-0.1f32, 0.03f32, 0.1f32, 0.7f32, 0.01f32, -0.03f32, true, true, offset_transform);
+        let erosion_offset4 = build_erosion_offset_spline(-0.05f32, 0.03f32, 0.1f32, 1.0f32, 0.01f32, 0.01f32, true, true, offset_transform);
+        let offset = CubicSplineBuilder::with_transform(SplineCoordinate::Continents, offset_transform)
+            .add_point(-1.1f32, 0.044f32, 0.0f32)
+            .add_point(-1.02f32, -0.2222f32, 0.0f32)
+            .add_point(-0.51f32, -0.2222f32, 0.0f32)
+            .add_point(-0.44f32, -0.12f32, 0.0f32)
+            .add_point(-0.18f32, -0.12f32, 0.0f32)
+            .add_spline_point(-0.16f32, erosion_offset1.clone(), 0.0f32)
+            .add_spline_point(-0.15f32, erosion_offset1, 0.0f32)
+            .add_spline_point(-0.1f32, erosion_offset2, 0.0f32)
+            .add_spline_point(0.25f32, erosion_offset3, 0.0f32)
+            .add_spline_point(1.0f32, erosion_offset4, 0.0f32)
+            .build();
+        let factor = CubicSplineBuilder::new(SplineCoordinate::Continents)
+            .add_point(-0.19f32, 3.95f32, 0.0f32)
+            .add_spline_point(-0.15f32, get_erosion_factor(6.25f32, true, no_transform), 0.0f32)
+            .add_spline_point(-0.1f32, get_erosion_factor(5.47f32, true, factor_transform), 0.0f32)
+            .add_spline_point(0.03f32, get_erosion_factor(5.08f32, true, factor_transform), 0.0f32)
+            .add_spline_point(0.06f32, get_erosion_factor(4.69f32, false, factor_transform), 0.0f32)
+            .build();
+        let jaggedness = CubicSplineBuilder::with_transform(SplineCoordinate::Continents, jaggedness_transform)
+            .add_point(-0.11f32, 0.0f32, 0.0f32)
+            .add_spline_point(0.03f32, build_erosion_jaggedness_spline(1.0f32, 0.5f32, 0.0f32, 0.0f32, jaggedness_transform), 0.0f32)
+            .add_spline_point(0.65f32, build_erosion_jaggedness_spline(1.0f32, 1.0f32, 1.0f32, 0.0f32, jaggedness_transform), 0.0f32)
+            .build();
+        Self::new(offset, factor, jaggedness)
+    }
+
+    pub fn get_offset_sampler(&self) -> Arc<CubicSpline> {
+        self.offset_sampler.clone()
+    }
+
+    pub fn get_factor_sampler(&self) -> Arc<CubicSpline> {
+        self.factor_sampler.clone()
+    }
+
+    pub fn get_jaggedness_sampler(&self) -> Arc<CubicSpline> {
+        self.jaggedness_sampler.clone()
+    }
+
+    pub fn offset(&self, point: &TerrainPoint) -> f32 {
+        self.offset_sampler.apply(point) + GLOBAL_OFFSET
+    }
+
+    pub fn factor(&self, point: &TerrainPoint) -> f32 {
+        self.factor_sampler.apply(point)
+    }
+
+    pub fn jaggedness(&self, point: &TerrainPoint) -> f32 {
+        self.jaggedness_sampler.apply(point)
+    }
+}
+
+fn amplified_offset(offset: f32) -> f32 {
+    if offset < 0.0f32 { offset } else { offset * 2.0f32 }
+}
+
+fn amplified_factor(factor: f32) -> f32 {
+    1.25f32 - 6.25f32 / (factor + 5.0f32)
+}
+
+fn amplified_jaggedness(jaggedness: f32) -> f32 {
+    jaggedness * 2.0f32
+}
+
+fn build_erosion_jaggedness_spline(high_erosion_high_weirdness: f32, low_erosion_high_weirdness: f32, high_erosion_mid_weirdness: f32, low_erosion_mid_weirdness: f32, transform: fn(f32) -> f32) -> CubicSpline {
+    let high_erosion = build_ridge_jaggedness_spline(high_erosion_high_weirdness, high_erosion_mid_weirdness, transform);
+    let low_erosion = build_ridge_jaggedness_spline(low_erosion_high_weirdness, low_erosion_mid_weirdness, transform);
+    CubicSplineBuilder::with_transform(SplineCoordinate::Erosion, transform)
+        .add_spline_point(-1.0f32, high_erosion, 0.0f32)
+        .add_spline_point(-0.78f32, low_erosion.clone(), 0.0f32)
+        .add_spline_point(-0.5775f32, low_erosion, 0.0f32)
+        .add_point(-0.375f32, 0.0f32, 0.0f32)
+        .build()
+}
+
+fn build_ridge_jaggedness_spline(high_weirdness: f32, mid_weirdness: f32, transform: fn(f32) -> f32) -> CubicSpline {
+    let low_ridges: f32 = peaks_and_valleys(0.4f32);
+    let high_ridges: f32 = peaks_and_valleys(0.56666666f32);
+    let mid_ridges: f32 = (low_ridges + high_ridges) / 2.0f32;
+    let mut builder = CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform).add_point(low_ridges, 0.0f32, 0.0f32);
+    builder = if mid_weirdness > 0.0f32 {
+        builder.add_spline_point(mid_ridges, build_weirdness_jaggedness_spline(mid_weirdness, transform), 0.0f32)
+    } else {
+        builder.add_point(mid_ridges, 0.0f32, 0.0f32)
+    };
+    builder = if high_weirdness > 0.0f32 {
+        builder.add_spline_point(1.0f32, build_weirdness_jaggedness_spline(high_weirdness, transform), 0.0f32)
+    } else {
+        builder.add_point(1.0f32, 0.0f32, 0.0f32)
+    };
+    builder.build()
+}
+
+fn build_weirdness_jaggedness_spline(magnitude: f32, transform: fn(f32) -> f32) -> CubicSpline {
+    CubicSplineBuilder::with_transform(SplineCoordinate::Weirdness, transform)
+        .add_point(-0.01f32, 0.63f32 * magnitude, 0.0f32)
+        .add_point(0.01f32, 0.3f32 * magnitude, 0.0f32)
+        .build()
+}
+
+fn get_erosion_factor(value: f32, higher_values: bool, transform: fn(f32) -> f32) -> CubicSpline {
+    let weirdness = CubicSplineBuilder::with_transform(SplineCoordinate::Weirdness, transform)
+        .add_point(-0.2f32, 6.3f32, 0.0f32)
+        .add_point(0.2f32, value, 0.0f32)
+        .build();
+    let mut builder = CubicSplineBuilder::with_transform(SplineCoordinate::Erosion, transform)
+        .add_spline_point(-0.6f32, weirdness.clone(), 0.0f32)
+        .add_spline_point(-0.5f32, CubicSplineBuilder::with_transform(SplineCoordinate::Weirdness, transform)
+            .add_point(-0.05f32, 6.3f32, 0.0f32)
+            .add_point(0.05f32, 2.67f32, 0.0f32)
+            .build(), 0.0f32)
+        .add_spline_point(-0.35f32, weirdness.clone(), 0.0f32)
+        .add_spline_point(-0.25f32, weirdness.clone(), 0.0f32)
+        .add_spline_point(-0.1f32, CubicSplineBuilder::with_transform(SplineCoordinate::Weirdness, transform)
+            .add_point(-0.05f32, 2.67f32, 0.0f32)
+            .add_point(0.05f32, 6.3f32, 0.0f32)
+            .build(), 0.0f32)
+        .add_spline_point(0.03f32, weirdness.clone(), 0.0f32);
+    if higher_values {
+        let weirdness_high = CubicSplineBuilder::with_transform(SplineCoordinate::Weirdness, transform)
+            .add_point(0.0f32, value, 0.0f32)
+            .add_point(0.1f32, 0.625f32, 0.0f32)
+            .build();
+        let ridges = CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform)
+            .add_point(-0.9f32, value, 0.0f32)
+            .add_spline_point(-0.69f32, weirdness_high, 0.0f32)
+            .build();
+        builder = builder
+            .add_point(0.35f32, value, 0.0f32)
+            .add_spline_point(0.45f32, ridges.clone(), 0.0f32)
+            .add_spline_point(0.55f32, ridges, 0.0f32)
+            .add_point(0.62f32, value, 0.0f32);
+    } else {
+        let low_ridges = CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform)
+            .add_spline_point(-0.7f32, weirdness.clone(), 0.0f32)
+            .add_point(-0.15f32, 1.37f32, 0.0f32)
+            .build();
+        let high_ridges = CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform)
+            .add_spline_point(0.45f32, weirdness, 0.0f32)
+            .add_point(0.7f32, 1.56f32, 0.0f32)
+            .build();
+        builder = builder
+            .add_spline_point(0.05f32, high_ridges.clone(), 0.0f32)
+            .add_spline_point(0.4f32, high_ridges, 0.0f32)
+            .add_spline_point(0.45f32, low_ridges.clone(), 0.0f32)
+            .add_spline_point(0.55f32, low_ridges, 0.0f32)
+            .add_point(0.58f32, value, 0.0f32);
+    }
+    builder.build()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_erosion_offset_spline(low: f32, mid: f32, high: f32, mountain: f32, low_valley: f32, high_valley: f32, extended: bool, use_max_slope: bool, transform: fn(f32) -> f32) -> CubicSpline {
+    let mountain1 = build_mountain_ridge_spline_with_points(lerp(mountain, 0.6f32, 1.5f32), use_max_slope, transform);
+    let mountain2 = build_mountain_ridge_spline_with_points(lerp(mountain, 0.6f32, 1.0f32), use_max_slope, transform);
+    let mountain3 = build_mountain_ridge_spline_with_points(mountain, use_max_slope, transform);
+    let ridge1 = ridge_spline(low - 0.15f32, 0.5f32 * mountain, lerp(0.5f32, 0.5f32, 0.5f32) * mountain, 0.5f32 * mountain, 0.6f32 * mountain, 0.5f32, transform);
+    let ridge2 = ridge_spline(low, low_valley * mountain, mid * mountain, 0.5f32 * mountain, 0.6f32 * mountain, 0.5f32, transform);
+    let ridge3 = ridge_spline(low, low_valley, low_valley, mid, high, 0.5f32, transform);
+    let ridge4 = ridge_spline(low, low_valley, low_valley, mid, high, 0.5f32, transform);
+    let weirdness = CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform)
+        .add_point(-1.0f32, low, 0.0f32)
+        .add_spline_point(-0.4f32, ridge3.clone(), 0.0f32)
+        .add_point(0.0f32, high + 0.07f32, 0.0f32)
+        .build();
+    let ridge5 = ridge_spline(-0.02f32, high_valley, high_valley, mid, high, 0.0f32, transform);
+    let mut builder = CubicSplineBuilder::with_transform(SplineCoordinate::Erosion, transform)
+        .add_spline_point(-0.85f32, mountain1, 0.0f32)
+        .add_spline_point(-0.7f32, mountain2, 0.0f32)
+        .add_spline_point(-0.4f32, mountain3, 0.0f32)
+        .add_spline_point(-0.35f32, ridge1, 0.0f32)
+        .add_spline_point(-0.1f32, ridge2, 0.0f32)
+        .add_spline_point(0.2f32, ridge3, 0.0f32);
+    if extended {
+        builder = builder
+            .add_spline_point(0.4f32, ridge4.clone(), 0.0f32)
+            .add_spline_point(0.45f32, weirdness.clone(), 0.0f32)
+            .add_spline_point(0.55f32, weirdness, 0.0f32)
+            .add_spline_point(0.58f32, ridge4, 0.0f32);
+    }
+    builder.add_spline_point(0.7f32, ridge5, 0.0f32).build()
+}
+
+fn build_mountain_ridge_spline_with_points(magnitude: f32, use_max_slope: bool, transform: fn(f32) -> f32) -> CubicSpline {
+    let mut builder = CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform);
+    let low: f32 = mountain_continentalness(-1.0f32, magnitude, -0.7f32);
+    let high: f32 = mountain_continentalness(1.0f32, magnitude, -0.7f32);
+    let zero_point: f32 = calculate_mountain_ridge_zero_continentalness_point(magnitude);
+    if -0.65f32 < zero_point && zero_point < 1.0f32 {
+        let before_zero: f32 = mountain_continentalness(-0.65f32, magnitude, -0.7f32);
+        let before_cutoff: f32 = mountain_continentalness(-0.75f32, magnitude, -0.7f32);
+        let low_slope: f32 = calculate_slope(low, before_cutoff, -1.0f32, -0.75f32);
+        let at_zero: f32 = mountain_continentalness(zero_point, magnitude, -0.7f32);
+        let high_slope: f32 = calculate_slope(at_zero, high, zero_point, 1.0f32);
+        builder = builder
+            .add_point(-1.0f32, low, low_slope)
+            .add_point(-0.75f32, before_cutoff, 0.0f32)
+            .add_point(-0.65f32, before_zero, 0.0f32)
+            .add_point(zero_point - 0.01f32, at_zero, 0.0f32)
+            .add_point(zero_point, at_zero, high_slope)
+            .add_point(1.0f32, high, high_slope);
+    } else {
+        let slope: f32 = calculate_slope(low, high, -1.0f32, 1.0f32);
+        if use_max_slope {
+            builder = builder
+                .add_point(-1.0f32, low.max(0.2f32), 0.0f32)
+                .add_point(0.0f32, lerp(0.5f32, low, high), slope);
+        } else {
+            builder = builder.add_point(-1.0f32, low, slope);
+        }
+        builder = builder.add_point(1.0f32, high, slope);
+    }
+    builder.build()
+}
+
+fn mountain_continentalness(ridge: f32, magnitude: f32, cutoff: f32) -> f32 {
+    let scale: f32 = 1.0f32 - (1.0f32 - magnitude) * 0.5f32;
+    let shift: f32 = 0.5f32 * (1.0f32 - magnitude);
+    let continentalness: f32 = (ridge + 1.17f32) * 0.46082947f32 * scale - shift;
+    if ridge < cutoff { continentalness.max(-0.2222f32) } else { continentalness.max(0.0f32) }
+}
+
+fn calculate_mountain_ridge_zero_continentalness_point(magnitude: f32) -> f32 {
+    let scale: f32 = 1.0f32 - (1.0f32 - magnitude) * 0.5f32;
+    let shift: f32 = 0.5f32 * (1.0f32 - magnitude);
+    shift / (0.46082947f32 * scale) - 1.17f32
+}
+
+fn calculate_slope(y1: f32, y2: f32, x1: f32, x2: f32) -> f32 {
+    (y2 - y1) / (x2 - x1)
+}
+
+fn ridge_spline(valley: f32, low: f32, mid: f32, high: f32, peak: f32, min_valley_slope: f32, transform: fn(f32) -> f32) -> CubicSpline {
+    let valley_slope: f32 = (0.5f32 * (low - valley)).max(min_valley_slope);
+    let mid_slope: f32 = 5.0f32 * (mid - low);
+    CubicSplineBuilder::with_transform(SplineCoordinate::Ridges, transform)
+        .add_point(-1.0f32, valley, valley_slope)
+        .add_point(-0.4f32, low, valley_slope.min(mid_slope))
+        .add_point(0.0f32, mid, mid_slope)
+        .add_point(0.4f32, high, 2.0f32 * (high - mid))
+        .add_point(1.0f32, peak, 0.7f32 * (peak - high))
+        .build()
+}
+
+/// Continentalness, erosion and weirdness of the overworld as density functions, sampled at quart positions.
+#[derive(Clone, Debug)]
+pub struct ClimateSampler {
+    continents: DensityFunction,
+    erosion: DensityFunction,
+    weirdness: DensityFunction,
+}
+
+impl ClimateSampler {
+    pub fn new(shift: Arc<DoublePerlinNoise>, continentalness: Arc<DoublePerlinNoise>, erosion: Arc<DoublePerlinNoise>, ridges: Arc<DoublePerlinNoise>) -> Self {
+        let shift_x = DensityFunction::flat_cache(DensityFunction::cache_2d(DensityFunction::ShiftA(shift.clone())));
+        let shift_z = DensityFunction::flat_cache(DensityFunction::cache_2d(DensityFunction::ShiftB(shift)));
+        let shifted = |noise: Arc<DoublePerlinNoise>| DensityFunction::flat_cache(DensityFunction::shifted_noise_2d(shift_x.clone(), shift_z.clone(), 0.25f64, noise));
+        ClimateSampler {
+            continents: shifted(continentalness),
+            erosion: shifted(erosion),
+            weirdness: shifted(ridges),
+        }
+    }
+
+    /// The vanilla 1.18 overworld noises of a world.
+    pub fn overworld(factory: &PositionalRandomFactory) -> Self {
+        let noise = |name: &str, first_octave: i32, amplitudes: Vec<f64>| {
+            Arc::new(DoublePerlinNoise::instantiate(factory, name, &NoiseParameters::new(first_octave, amplitudes)))
+        };
+        Self::new(
+            noise("minecraft:offset", -3, vec![1.0f64, 1.0f64, 1.0f64, 0.0f64]),
+            noise("minecraft:continentalness", -9, vec![1.0f64, 1.0f64, 2.0f64, 2.0f64, 2.0f64, 1.0f64, 1.0f64, 1.0f64, 1.0f64]),
+            noise("minecraft:erosion", -9, vec![1.0f64, 1.0f64, 0.0f64, 1.0f64, 1.0f64]),
+            noise("minecraft:ridge", -7, vec![1.0f64, 2.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64]),
+        )
+    }
+
+    pub fn get_continents(&self) -> &DensityFunction {
+        &self.continents
+    }
+
+    pub fn get_erosion(&self) -> &DensityFunction {
+        &self.erosion
+    }
+
+    pub fn get_weirdness(&self) -> &DensityFunction {
+        &self.weirdness
+    }
+
+    pub fn get_point(&self, x: i32, y: i32, z: i32) -> TerrainPoint {
+        TerrainPoint::new(self.continents.compute(x, y, z), self.erosion.compute(x, y, z), self.weirdness.compute(x, y, z))
+    }
+}
@@ -1,36 +1,182 @@
 #![allow(dead_code)]
 use crate::math;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
 use crate::math::sqr;
+#[cfg(feature = "std")]
+use crate::int_cache::IntCache;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the biome zoom treats the y axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VoronoiMode {
+    /// Full 3D fuzzed zoom (1.15+ nether and end, 1.18+ overworld).
+    Fuzzy,
+    /// Column fuzzed zoom, the y coordinate is ignored and the column at y=0 is used (1.15-1.17 overworld).
+    Column,
+}
 
 #[derive( Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Voronoi {
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     cache: HashMap<u128,u128>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "IntCache::new"))]
+    cache2d: IntCache<u64>,
     world_seed:i64,
+    mode: VoronoiMode,
 }
+#[allow(clippy::needless_return)]
 pub fn next(world_seed: i64, salt: i64) -> i64 {
     return world_seed.wrapping_mul(world_seed.wrapping_mul(6364136223846793005i64).wrapping_add(1442695040888963407i64)).wrapping_add(salt);
 }
 
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod voronoi_test {
+    use super::*;
+
+    #[test]
+    fn test_2d_matches_column() {
+        let mut voronoi = Voronoi::new(1551515151585454);
+        for x in -20..20 {
+            for z in -20..20 {
+                let (xx, _, zz) = voronoi.get_fuzzy_positions(x, 0, z);
+                assert_eq!(voronoi.get_fuzzy_positions_2d(x, z), (xx, zz));
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let mut voronoi = Voronoi::with_mode(1551515151585454, VoronoiMode::Column);
+        let mut restored: Voronoi = serde_json::from_str(&serde_json::to_string(&voronoi).unwrap()).unwrap();
+        assert_eq!(restored.get_mode(), VoronoiMode::Column);
+        assert_eq!(restored.get_fuzzy_positions(7, 3, -13), voronoi.get_fuzzy_positions(7, 3, -13));
+    }
+
+    #[test]
+    fn test_column_ignores_y() {
+        let mut fuzzy = Voronoi::new(1551515151585454);
+        let mut column = Voronoi::with_mode(1551515151585454, VoronoiMode::Column);
+        for y in -10..10 {
+            assert_eq!(column.get_fuzzy_positions(7, y, -13), fuzzy.get_fuzzy_positions(7, 0, -13));
+        }
+    }
+}
+
 impl Voronoi {
     pub fn new(world_seed: i64) -> Self {
-        let cache: HashMap<u128,u128> = HashMap::new();
-        Voronoi { cache,world_seed }
+        Self::with_mode(world_seed, VoronoiMode::Fuzzy)
     }
+
+    pub fn with_mode(world_seed: i64, mode: VoronoiMode) -> Self {
+        Voronoi {
+            #[cfg(feature = "std")]
+            cache: HashMap::new(),
+            #[cfg(feature = "std")]
+            cache2d: IntCache::with_capacity(1024),
+            world_seed,
+            mode,
+        }
+    }
+
+    pub fn get_mode(&self) -> VoronoiMode {
+        self.mode
+    }
+
     pub fn get_fuzzy_positions(&mut self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        if self.mode == VoronoiMode::Column {
+            return self.get_fuzzy_positions_column(x, z);
+        }
+        self.get_fuzzy_positions_3d(x, y, z)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn get_fuzzy_positions_3d(&mut self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        self._get_fuzzy_positions(x, y, z)
+    }
+
+    #[allow(clippy::needless_return, clippy::unnecessary_cast)]
+    #[cfg(feature = "std")]
+    fn get_fuzzy_positions_3d(&mut self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
         let key: u128 = (((x as u32) as u128) << 64 | ((y as u32) as u128) << 32 | ((z as u32) as u128)) as u128;
-        let mut value:u128=*self.cache.get(&key).unwrap_or(&std::u128::MAX);
-        if value !=std::u128::MAX {
+        let mut value:u128=*self.cache.get(&key).unwrap_or(&u128::MAX);
+        if value !=u128::MAX {
             let x:i32= (value >> 64 & 0xFFFF_FFFFu128) as i32;
             let y:i32= (value >> 32 & 0xFFFF_FFFFu128) as i32;
             let z:i32= (value & 0xFFFF_FFFFu128) as i32;
             return (x,y,z);
         }
         let (xx,yy,zz):(i32,i32,i32)=self._get_fuzzy_positions(x,y,z);
-        value= ((xx as u128) << 64 | (yy as u128) << 32 | (zz as u128)) as u128;
+        value= (((xx as u32) as u128) << 64 | ((yy as u32) as u128) << 32 | ((zz as u32) as u128)) as u128;
         self.cache.insert(key,value);
         return (xx,yy,zz);
     }
+
+    /// Surface only zoom: the fuzzed cell of the column at y=0, without the y cell.
+    pub fn get_fuzzy_positions_2d(&mut self, x: i32, z: i32) -> (i32, i32) {
+        let (xx, _, zz) = self.get_fuzzy_positions_column(x, z);
+        (xx, zz)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn get_fuzzy_positions_column(&mut self, x: i32, z: i32) -> (i32, i32, i32) {
+        self._get_fuzzy_positions_column(x, z)
+    }
+
+    #[allow(clippy::needless_return)]
+    #[cfg(feature = "std")]
+    fn get_fuzzy_positions_column(&mut self, x: i32, z: i32) -> (i32, i32, i32) {
+        let key: u64 = ((x as u32) as u64) << 32 | ((z as u32) as u64);
+        let value: u64 = *self.cache2d.get(key).unwrap_or(&u64::MAX);
+        if value != u64::MAX {
+            // the y cell is packed in the low bit of the x half
+            let xx: i32 = (value >> 32) as i32;
+            let zz: i32 = value as i32;
+            return (xx >> 1, (xx & 1) - 1, zz);
+        }
+        let (xx, yy, zz): (i32, i32, i32) = self._get_fuzzy_positions_column(x, z);
+        self.cache2d.insert(key, (((xx << 1) | (yy + 1)) as u32 as u64) << 32 | (zz as u32 as u64));
+        return (xx, yy, zz);
+    }
+
+    #[allow(clippy::needless_return)]
+    fn _get_fuzzy_positions_column(&mut self, x: i32, z: i32) -> (i32, i32, i32) {
+        // y=0 is fixed: moved_y=-2 so the reduced y is -1 and the y fraction is 0.5
+        let moved_x: i32 = x - 2;
+        let moved_z: i32 = z - 2;
+        let reduced_x: i32 = moved_x >> 2;
+        let reduced_z: i32 = moved_z >> 2;
+        let x_scaled: f64 = (moved_x & 3) as f64 / 4.0f64;
+        let z_scaled: f64 = (moved_z & 3) as f64 / 4.0f64;
+        let mut max_index: i32 = 0;
+        let mut max: f64 = f64::MAX;
+        for cell in 0usize..8 {
+            let high4: bool = (cell & 4) == 0;
+            let high2: bool = (cell & 2) == 0;
+            let high1: bool = (cell & 1) == 0;
+            let xx: i32 = if high4 { reduced_x } else { reduced_x + 1 };
+            let yy: i32 = if high2 { -1 } else { 0 };
+            let zz: i32 = if high1 { reduced_z } else { reduced_z + 1 };
+            let xx_scaled: f64 = if high4 { x_scaled } else { x_scaled - 1.0 };
+            let yy_scaled: f64 = if high2 { 0.5 } else { -0.5 };
+            let zz_scaled: f64 = if high1 { z_scaled } else { z_scaled - 1.0 };
+            let distance: f64 = self.get_fiddled_distance(xx, yy, zz, xx_scaled, yy_scaled, zz_scaled);
+            if cell == 0 || max > distance {
+                max_index = cell as i32;
+                max = distance;
+            }
+        }
+        return (if (max_index & 4) == 0 { reduced_x } else { reduced_x + 1 }, if (max_index & 2) == 0 { -1 } else { 0 }, if (max_index & 1) == 0 { reduced_z } else { reduced_z + 1 });
+    }
+
+    #[allow(clippy::needless_range_loop, clippy::needless_return, clippy::neg_cmp_op_on_partial_ord)]
     fn _get_fuzzy_positions(&mut self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
         let moved_x: i32 = x - 2;
         let moved_y: i32 = y - 2;
@@ -64,6 +210,7 @@ impl Voronoi {
         return (if (max_index & 4) == 0 { reduced_x } else { reduced_x + 1 }, if (max_index & 2) == 0 { reduced_y } else { reduced_y + 1 }, if (max_index & 1) == 0 { reduced_z } else { reduced_z + 1 });
     }
 
+    #[allow(clippy::needless_return)]
     fn get_fiddled_distance(&mut self, x: i32, y: i32, z: i32, x_scaled: f64, y_scaled: f64, z_scaled: f64) -> f64 {
         let mut fiddle: i64;
         // TODO remove constant part due to worldseed
@@ -81,6 +228,7 @@ impl Voronoi {
         return  sqr(z_scaled + z_offset) +  sqr(y_scaled + y_offset) + sqr(x_scaled + x_offset);
     }
 
+    #[allow(clippy::needless_return)]
     fn get_fiddle(l: i64) -> f64 {
         return ((((math::floor_mod(l >> 24, 1024i64)) as i32) as f64 / 1024.0f64) - 0.5) * 0.9;
     }
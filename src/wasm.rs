@@ -0,0 +1,201 @@
+//! WebAssembly bindings, built with `wasm-pack build -- --no-default-features --features wasm,const_fn`.
+//!
+//! Seeds are `BigInt`s on the JavaScript side, batch functions take `Float64Array` coordinates and
+//! return a `Float64Array` (an `Int32Array` of interleaved x, y, z for the biome zoom).
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use java_random::Random;
+use wasm_bindgen::prelude::*;
+
+use crate::bindings;
+use crate::double_perlin_noise::DoublePerlinNoise;
+use crate::perlin_noise::{OctaveError, PerlinNoise};
+use crate::simplex_noise::SimplexNoise;
+use crate::voronoi::{Voronoi, VoronoiMode};
+
+fn js_error(error: OctaveError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn check_lengths(lengths: &[usize]) -> Result<usize, JsError> {
+    bindings::common_length(lengths).ok_or_else(|| JsError::new(bindings::LENGTH_MISMATCH))
+}
+
+#[wasm_bindgen(js_name = PerlinNoise)]
+pub struct WasmPerlinNoise {
+    noise: PerlinNoise,
+}
+
+#[wasm_bindgen(js_class = PerlinNoise)]
+impl WasmPerlinNoise {
+    /// The 1.16.1 noise of the java random `seed` and the `octaves` list.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: i64, octaves: Vec<i32>) -> Result<WasmPerlinNoise, JsError> {
+        let noise = bindings::perlin_with_octaves(seed, octaves).map_err(js_error)?;
+        Ok(WasmPerlinNoise { noise })
+    }
+
+    /// The 1.16.2+ noise of the java random `seed`, the first octave and the octave amplitudes.
+    #[wasm_bindgen(js_name = createLegacy)]
+    pub fn create_legacy(seed: i64, first_octave: i32, amplitudes: Vec<f64>) -> Result<WasmPerlinNoise, JsError> {
+        let noise = bindings::perlin_with_amplitudes(seed, first_octave, amplitudes).map_err(js_error)?;
+        Ok(WasmPerlinNoise { noise })
+    }
+
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.sample_default(x, y, z)
+    }
+
+    #[wasm_bindgen(js_name = sampleMany)]
+    pub fn sample_many(&self, xs: &[f64], ys: &[f64], zs: &[f64]) -> Result<Vec<f64>, JsError> {
+        let count = check_lengths(&[xs.len(), ys.len(), zs.len()])?;
+        Ok((0..count).map(|i| self.noise.sample_default(xs[i], ys[i], zs[i])).collect())
+    }
+}
+
+#[wasm_bindgen(js_name = DoublePerlinNoise)]
+pub struct WasmDoublePerlinNoise {
+    noise: DoublePerlinNoise,
+}
+
+#[wasm_bindgen(js_class = DoublePerlinNoise)]
+impl WasmDoublePerlinNoise {
+    /// The 1.16.1 noise of the java random `seed` and the `octaves` list.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: i64, octaves: Vec<i32>) -> Result<WasmDoublePerlinNoise, JsError> {
+        let noise = bindings::double_perlin_with_octaves(seed, octaves).map_err(js_error)?;
+        Ok(WasmDoublePerlinNoise { noise })
+    }
+
+    /// The 1.16.2+ noise of the java random `seed`, the first octave and the octave amplitudes.
+    #[wasm_bindgen(js_name = createLegacy)]
+    pub fn create_legacy(seed: i64, first_octave: i32, amplitudes: Vec<f64>) -> Result<WasmDoublePerlinNoise, JsError> {
+        let noise = bindings::double_perlin_with_amplitudes(seed, first_octave, amplitudes).map_err(js_error)?;
+        Ok(WasmDoublePerlinNoise { noise })
+    }
+
+    /// The 1.18+ noise registered as `name` (for example `minecraft:temperature`) in a world of `world_seed`.
+    pub fn instantiate(world_seed: i64, legacy_random_source: bool, name: &str, first_octave: i32, amplitudes: Vec<f64>) -> Result<WasmDoublePerlinNoise, JsError> {
+        let noise = bindings::double_perlin_with_parameters(world_seed, legacy_random_source, name, first_octave, amplitudes).map_err(js_error)?;
+        Ok(WasmDoublePerlinNoise { noise })
+    }
+
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.sample(x, y, z)
+    }
+
+    #[wasm_bindgen(js_name = sampleMany)]
+    pub fn sample_many(&self, xs: &[f64], ys: &[f64], zs: &[f64]) -> Result<Vec<f64>, JsError> {
+        let count = check_lengths(&[xs.len(), ys.len(), zs.len()])?;
+        Ok((0..count).map(|i| self.noise.sample(xs[i], ys[i], zs[i])).collect())
+    }
+}
+
+#[wasm_bindgen(js_name = SimplexNoise)]
+pub struct WasmSimplexNoise {
+    noise: SimplexNoise,
+}
+
+#[wasm_bindgen(js_class = SimplexNoise)]
+impl WasmSimplexNoise {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: i64) -> WasmSimplexNoise {
+        WasmSimplexNoise { noise: SimplexNoise::init(Random::with_seed(seed as u64)) }
+    }
+
+    #[wasm_bindgen(js_name = sample2d)]
+    pub fn sample_2d(&mut self, x: f64, z: f64) -> f64 {
+        self.noise.get_value_2d(x, z)
+    }
+
+    #[wasm_bindgen(js_name = sample3d)]
+    pub fn sample_3d(&mut self, x: f64, y: f64, z: f64) -> f64 {
+        self.noise.get_value_3d(x, y, z)
+    }
+
+    #[wasm_bindgen(js_name = sample2dMany)]
+    pub fn sample_2d_many(&mut self, xs: &[f64], zs: &[f64]) -> Result<Vec<f64>, JsError> {
+        let count = check_lengths(&[xs.len(), zs.len()])?;
+        Ok((0..count).map(|i| self.noise.get_value_2d(xs[i], zs[i])).collect())
+    }
+
+    #[wasm_bindgen(js_name = sample3dMany)]
+    pub fn sample_3d_many(&mut self, xs: &[f64], ys: &[f64], zs: &[f64]) -> Result<Vec<f64>, JsError> {
+        let count = check_lengths(&[xs.len(), ys.len(), zs.len()])?;
+        Ok((0..count).map(|i| self.noise.get_value_3d(xs[i], ys[i], zs[i])).collect())
+    }
+}
+
+#[wasm_bindgen(js_name = Voronoi)]
+pub struct WasmVoronoi {
+    voronoi: Voronoi,
+}
+
+#[wasm_bindgen(js_class = Voronoi)]
+impl WasmVoronoi {
+    /// The biome zoom of a world, `column` selecting the y independent 1.15-1.17 overworld zoom.
+    #[wasm_bindgen(constructor)]
+    pub fn new(world_seed: i64, column: bool) -> WasmVoronoi {
+        let mode = if column { VoronoiMode::Column } else { VoronoiMode::Fuzzy };
+        WasmVoronoi { voronoi: Voronoi::with_mode(world_seed, mode) }
+    }
+
+    /// The biome (quart) position the block at `x`, `y`, `z` takes its biome from, as `[x, y, z]`.
+    #[wasm_bindgen(js_name = getFuzzyPositions)]
+    pub fn get_fuzzy_positions(&mut self, x: i32, y: i32, z: i32) -> Vec<i32> {
+        let (x, y, z) = self.voronoi.get_fuzzy_positions(x, y, z);
+        vec![x, y, z]
+    }
+
+    /// The biome positions of many blocks, interleaved as `x0, y0, z0, x1, ...`.
+    #[wasm_bindgen(js_name = getFuzzyPositionsMany)]
+    pub fn get_fuzzy_positions_many(&mut self, xs: &[i32], ys: &[i32], zs: &[i32]) -> Result<Vec<i32>, JsError> {
+        let count = check_lengths(&[xs.len(), ys.len(), zs.len()])?;
+        let mut positions: Vec<i32> = Vec::with_capacity(count * 3);
+        for i in 0..count {
+            let (x, y, z) = self.voronoi.get_fuzzy_positions(xs[i], ys[i], zs[i]);
+            positions.extend_from_slice(&[x, y, z]);
+        }
+        Ok(positions)
+    }
+}
+
+#[cfg(test)]
+mod wasm_test {
+    use super::*;
+    use crate::double_perlin_noise::NoiseParameters;
+    use crate::random_source::{RandomSource, WorldgenRandom};
+
+    #[test]
+    fn test_samples_match_rust() {
+        let xs = [1.5f64, -40.25f64, 0.0f64];
+        let ys = [-2.25f64, 0.0f64, 64.0f64];
+        let zs = [7.0f64, 3.5f64, -1000.0f64];
+        let perlin = WasmPerlinNoise::new(1, vec![-3, -2, -1, 0]).ok().unwrap();
+        let expected = PerlinNoise::new(&mut Random::with_seed(1), vec![-3, -2, -1, 0]);
+        let samples = perlin.sample_many(&xs, &ys, &zs).ok().unwrap();
+        for i in 0..3 {
+            assert_eq!(samples[i], expected.sample_default(xs[i], ys[i], zs[i]));
+        }
+
+        let double_perlin = WasmDoublePerlinNoise::instantiate(1, false, "minecraft:temperature", -10, vec![1.5f64, 0.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64]).ok().unwrap();
+        let factory = WorldgenRandom::new(1, false).fork_positional();
+        let expected = DoublePerlinNoise::instantiate(&factory, "minecraft:temperature", &NoiseParameters::new(-10, vec![1.5f64, 0.0f64, 1.0f64, 0.0f64, 0.0f64, 0.0f64]));
+        let samples = double_perlin.sample_many(&xs, &ys, &zs).ok().unwrap();
+        for i in 0..3 {
+            assert_eq!(samples[i], expected.sample(xs[i], ys[i], zs[i]));
+        }
+
+        let mut simplex = WasmSimplexNoise::new(1);
+        let mut expected = SimplexNoise::init(Random::with_seed(1));
+        assert_eq!(simplex.sample_2d_many(&xs, &zs).ok().unwrap(), xs.iter().zip(&zs).map(|(&x, &z)| expected.get_value_2d(x, z)).collect::<Vec<f64>>());
+
+        let mut voronoi = WasmVoronoi::new(1, false);
+        let mut expected = Voronoi::with_mode(1, VoronoiMode::Fuzzy);
+        let (x, y, z) = expected.get_fuzzy_positions(100, 64, -20);
+        assert_eq!(voronoi.get_fuzzy_positions_many(&[100], &[64], &[-20]).ok().unwrap(), vec![x, y, z]);
+    }
+}
@@ -0,0 +1,66 @@
+//! Compiles a C program against `include/noise_rs.h` and the staticlib of the default features.
+#![cfg(feature = "capi")]
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use java_random::Random;
+use noise_rs::create_range;
+use noise_rs::perlin_noise::PerlinNoise;
+use noise_rs::voronoi::Voronoi;
+
+const MAIN: &str = r#"
+#include <stdio.h>
+#include "noise_rs.h"
+
+int main(void) {
+    int32_t octaves[] = {-7, -6, -5, -4, -3, -2, -1, 0};
+    PerlinNoise *perlin = noise_rs_perlin_noise_new(1, octaves, 8);
+    printf("%.17g\n", noise_rs_perlin_noise_sample(perlin, 1.5, 2.0, -3.25));
+    noise_rs_perlin_noise_free(perlin);
+    Voronoi *voronoi = noise_rs_voronoi_new(1551515151585454, false);
+    int32_t x, y, z;
+    noise_rs_voronoi_get_fuzzy_positions(voronoi, 7, 3, -13, &x, &y, &z);
+    printf("%d %d %d\n", x, y, z);
+    noise_rs_voronoi_free(voronoi);
+    return 0;
+}
+"#;
+
+#[test]
+fn test_header_links() {
+    // cargo only builds the rlib for the tests, the staticlib is built into the same target directory
+    let target = env::current_exe().unwrap().parent().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--manifest-path", concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"), "--target-dir"])
+        .arg(&target)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let library = target.join("debug").join("libnoise_rs.a");
+    let directory = env::temp_dir().join(format!("noise_rs_header_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let source = directory.join("main.c");
+    let executable = directory.join("main");
+    fs::write(&source, MAIN).unwrap();
+    let status = Command::new("cc")
+        .args(["-std=c99", "-Wall", "-Werror", "-I", concat!(env!("CARGO_MANIFEST_DIR"), "/include")])
+        .arg(&source)
+        .arg(&library)
+        .arg("-o")
+        .arg(&executable)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = Command::new(&executable).output().unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    let expected = PerlinNoise::new(&mut Random::with_seed(1), create_range(-7, 0));
+    assert_eq!(lines[0].parse::<f64>().unwrap(), expected.sample_default(1.5f64, 2.0f64, -3.25f64));
+    let (x, y, z) = Voronoi::new(1551515151585454).get_fuzzy_positions(7, 3, -13);
+    assert_eq!(lines[1], format!("{} {} {}", x, y, z));
+}